@@ -0,0 +1,244 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{account_info::AccountInfo, borsh::try_from_slice_unchecked};
+use std::io::{Error, ErrorKind};
+use crate::state::{
+    Auction, AuctionBid, AuditEntry, BannedPhrases, Cohort, CommentPage, CommentReaction, CommentTranslation,
+    CommenterRecord, Config, ContentEntry, GlobalStats, Group, GroupMembership, IntroAttributes, IntroEditor, IntroIndex, IntroMention, IntroSummaryCache,
+    IntroTemplate, MaintenanceThread, PendingConfigChange, Raffle, RaffleTicket, StudentIntroComment, StudentIntroCommentCounter,
+    StudentIntroState, TagIndex, TagPage, TransferReceipt, UserCommentIndex, UserIntroIndex, UserRecord,
+};
+
+// Stable one-byte tags for every account type, for indexers that would rather not handle
+// discriminator strings. Tags are never reused; new types take the next one. Zero means
+// the account isn't one of ours.
+pub const UNKNOWN: u8 = 0;
+pub const STUDENT_INTRO: u8 = 1;
+pub const COMMENT: u8 = 2;
+pub const COMMENT_COUNTER: u8 = 3;
+pub const COMMENT_PAGE: u8 = 4;
+pub const COMMENT_TRANSLATION: u8 = 5;
+pub const COMMENTER_RECORD: u8 = 6;
+pub const USER_RECORD: u8 = 7;
+pub const USER_COMMENT_INDEX: u8 = 8;
+pub const USER_INTRO_INDEX: u8 = 9;
+pub const TRANSFER_RECEIPT: u8 = 10;
+pub const CONFIG: u8 = 11;
+pub const GLOBAL_STATS: u8 = 12;
+pub const INTRO_INDEX: u8 = 13;
+pub const AUDIT_ENTRY: u8 = 14;
+pub const BANNED_PHRASES: u8 = 15;
+pub const CONTENT_ENTRY: u8 = 16;
+pub const INTRO_TEMPLATE: u8 = 17;
+pub const TAG_INDEX: u8 = 18;
+pub const TAG_PAGE: u8 = 19;
+pub const COHORT: u8 = 20;
+pub const RAFFLE: u8 = 21;
+pub const RAFFLE_TICKET: u8 = 22;
+pub const MAINTENANCE_THREAD: u8 = 23;
+pub const AUCTION: u8 = 24;
+pub const AUCTION_BID: u8 = 25;
+pub const GROUP: u8 = 26;
+pub const GROUP_MEMBERSHIP: u8 = 27;
+pub const COMMENT_REACTION: u8 = 28;
+pub const INTRO_SUMMARY: u8 = 29;
+pub const PENDING_CONFIG_CHANGE: u8 = 30;
+pub const INTRO_EDITOR: u8 = 31;
+pub const INTRO_ATTRIBUTES: u8 = 32;
+pub const INTRO_MENTION: u8 = 33;
+
+// Byte 0 of every account holds its tag, so one single-byte memcmp at offset 0 classifies
+// it; the borsh data with its discriminator follows. Accounts from before the tag start
+// right at the data until MigrateBatch shifts them. Their byte 1 is the high byte of the
+// discriminator's length, always zero, where a tagged account has the length's nonzero
+// low byte, so the two layouts never pass for each other.
+pub const TAG_LEN: usize = 1;
+
+pub const REGISTRY: &[(u8, &str)] = &[
+    (STUDENT_INTRO, StudentIntroState::DISCRIMINATOR),
+    (COMMENT, StudentIntroComment::DISCRIMINATOR),
+    (COMMENT_COUNTER, StudentIntroCommentCounter::DISCRIMINATOR),
+    (COMMENT_PAGE, CommentPage::DISCRIMINATOR),
+    (COMMENT_TRANSLATION, CommentTranslation::DISCRIMINATOR),
+    (COMMENTER_RECORD, CommenterRecord::DISCRIMINATOR),
+    (USER_RECORD, UserRecord::DISCRIMINATOR),
+    (USER_COMMENT_INDEX, UserCommentIndex::DISCRIMINATOR),
+    (USER_INTRO_INDEX, UserIntroIndex::DISCRIMINATOR),
+    (TRANSFER_RECEIPT, TransferReceipt::DISCRIMINATOR),
+    (CONFIG, Config::DISCRIMINATOR),
+    (GLOBAL_STATS, GlobalStats::DISCRIMINATOR),
+    (INTRO_INDEX, IntroIndex::DISCRIMINATOR),
+    (AUDIT_ENTRY, AuditEntry::DISCRIMINATOR),
+    (BANNED_PHRASES, BannedPhrases::DISCRIMINATOR),
+    (CONTENT_ENTRY, ContentEntry::DISCRIMINATOR),
+    (INTRO_TEMPLATE, IntroTemplate::DISCRIMINATOR),
+    (TAG_INDEX, TagIndex::DISCRIMINATOR),
+    (TAG_PAGE, TagPage::DISCRIMINATOR),
+    (COHORT, Cohort::DISCRIMINATOR),
+    (RAFFLE, Raffle::DISCRIMINATOR),
+    (RAFFLE_TICKET, RaffleTicket::DISCRIMINATOR),
+    (MAINTENANCE_THREAD, MaintenanceThread::DISCRIMINATOR),
+    (AUCTION, Auction::DISCRIMINATOR),
+    (AUCTION_BID, AuctionBid::DISCRIMINATOR),
+    (GROUP, Group::DISCRIMINATOR),
+    (GROUP_MEMBERSHIP, GroupMembership::DISCRIMINATOR),
+    (COMMENT_REACTION, CommentReaction::DISCRIMINATOR),
+    (INTRO_SUMMARY, IntroSummaryCache::DISCRIMINATOR),
+    (PENDING_CONFIG_CHANGE, PendingConfigChange::DISCRIMINATOR),
+    (INTRO_EDITOR, IntroEditor::DISCRIMINATOR),
+    (INTRO_ATTRIBUTES, IntroAttributes::DISCRIMINATOR),
+    (INTRO_MENTION, IntroMention::DISCRIMINATOR),
+];
+
+pub fn tag_of(discriminator: &str) -> u8 {
+    REGISTRY
+        .iter()
+        .find(|(_tag, name)| *name == discriminator)
+        .map(|(tag, _name)| *tag)
+        .unwrap_or(UNKNOWN)
+}
+
+pub fn is_tagged(data: &[u8]) -> bool {
+    matches!(data.get(1), Some(byte) if *byte != 0)
+}
+
+pub fn tag_len(data: &[u8]) -> usize {
+    if is_tagged(data) { TAG_LEN } else { 0 }
+}
+
+// The account's borsh data, after the tag if it has one
+pub fn body(data: &[u8]) -> &[u8] {
+    &data[tag_len(data)..]
+}
+
+pub fn body_len(account: &AccountInfo) -> usize {
+    let data = account.data.borrow();
+    data.len() - tag_len(&data)
+}
+
+pub fn read<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, Error> {
+    try_from_slice_unchecked::<T>(body(&account.data.borrow()))
+}
+
+// Writes the value behind the tag of its type. Accounts written before the tag keep their
+// layout until they are migrated; new accounts, still all zeroes, get the tag.
+pub fn write<T: BorshSerialize>(value: &T, account: &AccountInfo) -> Result<(), Error> {
+    let mut data = account.data.borrow_mut();
+    let untagged = matches!(data.first(), Some(byte) if *byte != 0) && !is_tagged(&data);
+    write_at(value, &mut data, !untagged)
+}
+
+// Zeroes the account before writing the value, keeping the layout the account had
+pub fn overwrite<T: BorshSerialize>(value: &T, account: &AccountInfo) -> Result<(), Error> {
+    let mut data = account.data.borrow_mut();
+    let tagged = is_tagged(&data);
+    data.fill(0);
+    write_at(value, &mut data, tagged)
+}
+
+// Values without a registered discriminator are never tagged, so byte 0 can't be a tag
+// of zero
+fn write_at<T: BorshSerialize>(value: &T, data: &mut [u8], tagged: bool) -> Result<(), Error> {
+    let bytes = value.try_to_vec()?;
+    let tag = tag_of(&String::deserialize(&mut &bytes[..]).unwrap_or_default());
+    let start = if tagged && tag != UNKNOWN {
+        *data.first_mut().ok_or_else(|| Error::from(ErrorKind::WriteZero))? = tag;
+        TAG_LEN
+    } else {
+        0
+    };
+    data.get_mut(start..start + bytes.len())
+        .ok_or_else(|| Error::new(ErrorKind::WriteZero, "Account is too small for its data"))?
+        .copy_from_slice(&bytes);
+
+    Ok(())
+}
+
+// Moves an untagged account's data behind its tag. The account must already have the
+// extra byte at its end.
+pub fn shift_behind_tag(data: &mut [u8]) -> Result<(), Error> {
+    if is_tagged(data) {
+        return Ok(());
+    }
+    let tag = tag_of(&String::deserialize(&mut &data[..])?);
+    if tag == UNKNOWN {
+        return Err(Error::new(ErrorKind::InvalidData, "Account type has no tag"));
+    }
+    let len = data.len();
+    data.copy_within(..len - TAG_LEN, TAG_LEN);
+    data[0] = tag;
+
+    Ok(())
+}
+
+// Bytes an untagged account of the type starts with, for indexers that still see
+// accounts MigrateBatch hasn't shifted yet
+pub fn legacy_memcmp_prefix(tag: u8) -> Option<Vec<u8>> {
+    REGISTRY
+        .iter()
+        .find(|(registered, _name)| *registered == tag)
+        .map(|(_tag, name)| name.to_string().try_to_vec().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_tags_and_prefixes_are_unambiguous() {
+        for (i, (tag, name)) in REGISTRY.iter().enumerate() {
+            assert_ne!(*tag, UNKNOWN);
+            assert_eq!(tag_of(name), *tag);
+            let prefix = legacy_memcmp_prefix(*tag).unwrap();
+            for (other_tag, other_name) in &REGISTRY[i + 1..] {
+                assert_ne!(tag, other_tag);
+                // "comment" must not match "commentpage" accounts
+                assert!(!other_name.to_string().try_to_vec().unwrap().starts_with(&prefix));
+                assert!(!prefix.starts_with(&legacy_memcmp_prefix(*other_tag).unwrap()));
+            }
+        }
+        assert_eq!(tag_of("nope"), UNKNOWN);
+        assert_eq!(legacy_memcmp_prefix(UNKNOWN), None);
+    }
+
+    #[test]
+    fn test_new_accounts_are_tagged_and_legacy_ones_shift_behind_the_tag() {
+        let counter_data = StudentIntroCommentCounter {
+            discriminator: StudentIntroCommentCounter::DISCRIMINATOR.to_string(),
+            is_intialized: true,
+            counter: 3,
+            seed_version: StudentIntroCommentCounter::CURRENT_SEED_VERSION,
+        };
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let (mut lamports, mut data) = (1, vec![0u8; StudentIntroCommentCounter::SIZE + TAG_LEN]);
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        write(&counter_data, &account).unwrap();
+        assert_eq!(account.data.borrow()[0], COMMENT_COUNTER);
+        assert_eq!(body_len(&account), StudentIntroCommentCounter::SIZE);
+        assert_eq!(read::<StudentIntroCommentCounter>(&account).unwrap().counter, 3);
+
+        let mut legacy = counter_data.try_to_vec().unwrap();
+        assert!(!is_tagged(&legacy));
+        let (mut lamports, mut data) = (1, legacy.clone());
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        write(&StudentIntroCommentCounter {
+            discriminator: StudentIntroCommentCounter::DISCRIMINATOR.to_string(),
+            counter: 4,
+            ..counter_data
+        }, &account).unwrap();
+        assert!(!is_tagged(&account.data.borrow()));
+        assert_eq!(read::<StudentIntroCommentCounter>(&account).unwrap().counter, 4);
+
+        legacy.push(0);
+        shift_behind_tag(&mut legacy).unwrap();
+        assert_eq!(legacy[0], COMMENT_COUNTER);
+        assert_eq!(body(&legacy), &counter_data.try_to_vec().unwrap()[..]);
+        shift_behind_tag(&mut legacy).unwrap();
+        assert_eq!(body(&legacy), &counter_data.try_to_vec().unwrap()[..]);
+
+        let mut full = vec![0u8; StudentIntroCommentCounter::SIZE];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut full, &owner, false, 0);
+        assert!(write(&counter_data, &account).is_err());
+    }
+}
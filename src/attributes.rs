@@ -0,0 +1,186 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::ATTRIBUTES_SEED;
+use crate::editor;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_program_account, load_writable_config};
+use crate::state::{IntroAttribute, IntroAttributes, StudentIntroState};
+
+// Sets one of the intro's attributes, or removes it when the value is empty. The
+// reviewer or an editor they granted can set them; the first one creates the account at
+// [intro, "attributes"], paid by whoever set it.
+pub fn set_attribute(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    key: String,
+    value: String,
+) -> ProgramResult {
+    msg!("Setting attribute {:?}...", key);
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let signer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_attributes = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    // An editor signing for the reviewer passes their grant
+    let pda_editor = next_account_info(account_info_iter).ok();
+
+    if !signer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    load_writable_config(program_id, ns, pda_config)?;
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+    editor::check_editor(program_id, ns, signer, pda_review.key, &review_data, pda_editor)?;
+
+    let (attributes_pda, attributes_bump) = Pubkey::find_program_address(
+        &[ns, pda_review.key.as_ref(), ATTRIBUTES_SEED], program_id);
+    if attributes_pda != *pda_attributes.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for intro attributes PDA"));
+    }
+
+    let mut attributes_data = if pda_attributes.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                signer.key,
+                pda_attributes.key,
+                rent.minimum_balance(IntroAttributes::SIZE + account_type::TAG_LEN),
+                (IntroAttributes::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[signer.clone(), pda_attributes.clone(), system_program.clone()],
+            &[&[ns, pda_review.key.as_ref(), ATTRIBUTES_SEED, &[attributes_bump]]],
+        )?;
+
+        IntroAttributes {
+            discriminator: IntroAttributes::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: *pda_review.key,
+            payer: *signer.key,
+            entries: Vec::new(),
+        }
+    } else {
+        load_attributes(program_id, pda_attributes)?
+    };
+
+    apply_attribute(&mut attributes_data.entries, key, value)?;
+    account_type::write(&attributes_data, pda_attributes)?;
+
+    Ok(())
+}
+
+pub(crate) fn load_attributes(program_id: &Pubkey, pda_attributes: &AccountInfo) -> Result<IntroAttributes, ProgramError> {
+    if pda_attributes.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let attributes_data = account_type::read::<IntroAttributes>(pda_attributes).unwrap();
+    if !attributes_data.is_initialized() || attributes_data.discriminator != IntroAttributes::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Intro attributes are not initialized"));
+    }
+
+    Ok(attributes_data)
+}
+
+// Keys are lowercase ASCII letters, digits, '_' and '-', so two spellings of the same
+// key can't both be set. Values are free text within the length limit.
+fn apply_attribute(entries: &mut Vec<IntroAttribute>, key: String, value: String) -> ProgramResult {
+    if key.is_empty() || key.len() > IntroAttributes::MAX_KEY_LEN {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "Attribute keys must be 1 to {} bytes", IntroAttributes::MAX_KEY_LEN));
+    }
+    if !key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-') {
+        msg!("Attribute keys only take lowercase letters, digits, '_' and '-'");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if value.len() > IntroAttributes::MAX_VALUE_LEN {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "Attribute values must be at most {} bytes", IntroAttributes::MAX_VALUE_LEN));
+    }
+
+    match entries.binary_search_by(|entry| entry.key.as_str().cmp(&key)) {
+        Ok(i) if value.is_empty() => {
+            entries.remove(i);
+        }
+        Ok(i) => entries[i].value = value,
+        Err(_) if value.is_empty() => {}
+        Err(i) => {
+            if entries.len() >= IntroAttributes::MAX_ATTRIBUTES {
+                return Err(reason!(ReviewError::InvalidDataLength,
+                    "An intro has at most {} attributes", IntroAttributes::MAX_ATTRIBUTES));
+            }
+            entries.insert(i, IntroAttribute { key, value });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_attributes_stay_sorted_and_bounded() {
+        let mut entries = Vec::new();
+        apply_attribute(&mut entries, "track".to_string(), "backend".to_string()).unwrap();
+        apply_attribute(&mut entries, "pronouns".to_string(), "they/them".to_string()).unwrap();
+        apply_attribute(&mut entries, "track".to_string(), "frontend".to_string()).unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.key.as_str()).collect::<Vec<_>>(), ["pronouns", "track"]);
+        assert_eq!(entries[1].value, "frontend");
+
+        apply_attribute(&mut entries, "pronouns".to_string(), String::new()).unwrap();
+        apply_attribute(&mut entries, "missing".to_string(), String::new()).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        assert!(apply_attribute(&mut entries, "Track".to_string(), "x".to_string()).is_err());
+        assert!(apply_attribute(&mut entries, String::new(), "x".to_string()).is_err());
+        assert!(apply_attribute(&mut entries, "bio".to_string(), "x".repeat(65)).is_err());
+
+        for i in 1..IntroAttributes::MAX_ATTRIBUTES {
+            apply_attribute(&mut entries, format!("key-{}", i), "x".repeat(IntroAttributes::MAX_VALUE_LEN)).unwrap();
+        }
+        assert!(apply_attribute(&mut entries, "one-more".to_string(), "x".to_string()).is_err());
+
+        let full = IntroAttributes {
+            discriminator: IntroAttributes::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            entries: (0..IntroAttributes::MAX_ATTRIBUTES).map(|i| IntroAttribute {
+                key: format!("{:x<16}", i),
+                value: "x".repeat(IntroAttributes::MAX_VALUE_LEN),
+            }).collect(),
+        };
+        assert_eq!(full.try_to_vec().unwrap().len(), IntroAttributes::SIZE);
+    }
+}
@@ -0,0 +1,430 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, rent::Rent, Sysvar, rent::ID as RENT_PROGRAM_ID},
+};
+use spl_token::{
+    instruction::{initialize_account, transfer},
+    state::Account as TokenAccount,
+    ID as TOKEN_PROGRAM_ID,
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::{AUCTION_SEED, BID_SEED, TOKEN_AUTH_SEED, TOKEN_MINT_SEED, VAULT_SEED};
+use crate::audit;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::events::IndexEvent;
+use crate::processor::{
+    burn_pda_tokens, check_account_count, check_admin, check_program_account, close_account, load_writable_config,
+};
+use crate::state::{Auction, AuctionBid, AuditEntry, StudentIntroState};
+use crate::summary;
+
+// Opens a featured slot. Bids are in the default cohort's token and sit in the auction's
+// vault, owned by the mint authority PDA like the treasury, until the auction settles.
+#[allow(clippy::too_many_arguments)]
+pub fn create_auction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    id: u64,
+    start_slot: u64,
+    end_slot: u64,
+    feature_end_slot: u64,
+) -> ProgramResult {
+    msg!("Creating auction {}...", id);
+
+    check_account_count(accounts, 10)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_auction = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+    if *sysvar_rent.key != RENT_PROGRAM_ID {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect rent program"));
+    }
+
+    if end_slot <= start_slot || feature_end_slot <= end_slot {
+        msg!("Auction needs a bidding window followed by a featured window");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (auction_pda, auction_bump) = Pubkey::find_program_address(
+        &[ns, AUCTION_SEED, id.to_be_bytes().as_ref()], program_id
+    );
+    if auction_pda != *pda_auction.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+    let vault_bump = check_vault(program_id, ns, pda_auction.key, vault)?;
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED], program_id);
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint account"));
+    }
+    check_mint_auth(program_id, ns, mint_auth)?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_auction.key,
+            rent.minimum_balance(Auction::SIZE + account_type::TAG_LEN),
+            (Auction::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_auction.clone(), system_program.clone()],
+        &[&[ns, AUCTION_SEED, id.to_be_bytes().as_ref(), &[auction_bump]]],
+    )?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            vault.key,
+            rent.minimum_balance(TokenAccount::LEN),
+            TokenAccount::LEN.try_into().unwrap(),
+            token_program.key,
+        ),
+        &[admin.clone(), vault.clone(), system_program.clone()],
+        &[&[ns, pda_auction.key.as_ref(), VAULT_SEED, &[vault_bump]]],
+    )?;
+    invoke(
+        &initialize_account(token_program.key, vault.key, token_mint.key, mint_auth.key)?,
+        &[vault.clone(), token_mint.clone(), mint_auth.clone(), sysvar_rent.clone()],
+    )?;
+
+    let auction_data = Auction {
+        discriminator: Auction::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        id,
+        start_slot,
+        end_slot,
+        feature_end_slot,
+        highest_bid: 0,
+        highest_bidder: Pubkey::default(),
+        highest_intro: Pubkey::default(),
+        bid_count: 0,
+        settled: false,
+    };
+    account_type::write(&auction_data, pda_auction)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_CREATE_AUCTION)
+}
+
+// Bids `amount` more for one of the bidder's own intros. The bidder's total has to beat
+// the highest bid, and moves their whole bid to the intro given.
+pub fn place_bid(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    msg!("Placing bid of {}...", amount);
+
+    check_account_count(accounts, 9)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let bidder = next_account_info(account_info_iter)?;
+    let pda_auction = next_account_info(account_info_iter)?;
+    let pda_bid = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let bidder_ata = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+
+    if !bidder.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+    load_writable_config(program_id, ns, pda_config)?;
+
+    let mut auction_data = load_auction(program_id, ns, pda_auction)?;
+    check_vault(program_id, ns, pda_auction.key, vault)?;
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.closing || review_data.reviewer != *bidder.key {
+        return Err(reason!(ReviewError::Unauthorized, "Bids must be for one of the bidder's open intros"));
+    }
+
+    let (bid_pda, bid_bump) = Pubkey::find_program_address(
+        &[ns, pda_auction.key.as_ref(), BID_SEED, bidder.key.as_ref()], program_id
+    );
+    if bid_pda != *pda_bid.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for bid PDA"));
+    }
+
+    let new_bid = pda_bid.data_is_empty();
+    let mut bid_data = if new_bid {
+        AuctionBid {
+            discriminator: AuctionBid::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            auction: *pda_auction.key,
+            bidder: *bidder.key,
+            intro: *pda_review.key,
+            amount: 0,
+        }
+    } else {
+        if pda_bid.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        account_type::read::<AuctionBid>(pda_bid).unwrap()
+    };
+
+    let total = bid_data.amount
+        .checked_add(amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    check_bid(&auction_data, Clock::get()?.slot, amount, total)?;
+
+    if new_bid {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                bidder.key,
+                pda_bid.key,
+                rent.minimum_balance(AuctionBid::SIZE + account_type::TAG_LEN),
+                (AuctionBid::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[bidder.clone(), pda_bid.clone(), system_program.clone()],
+            &[&[ns, pda_auction.key.as_ref(), BID_SEED, bidder.key.as_ref(), &[bid_bump]]],
+        )?;
+        auction_data.bid_count = auction_data.bid_count
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    invoke(
+        &transfer(token_program.key, bidder_ata.key, vault.key, bidder.key, &[], amount)?,
+        &[bidder_ata.clone(), vault.clone(), bidder.clone()],
+    )?;
+
+    bid_data.amount = total;
+    bid_data.intro = *pda_review.key;
+    account_type::write(&bid_data, pda_bid)?;
+
+    auction_data.highest_bid = total;
+    auction_data.highest_bidder = *bidder.key;
+    auction_data.highest_intro = *pda_review.key;
+    account_type::write(&auction_data, pda_auction)?;
+
+    msg!("{} leads auction {} with {}", bidder.key, auction_data.id, total);
+
+    Ok(())
+}
+
+// Features the winning intro once bidding has closed and burns the winning bid. Anyone
+// can settle. An intro deleted in the meantime stays unfeatured but its bid still burns.
+pub fn settle_auction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Settling auction...");
+
+    check_account_count(accounts, 8)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_auction = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+
+    let mut auction_data = load_auction(program_id, ns, pda_auction)?;
+    if auction_data.settled || Clock::get()?.slot <= auction_data.end_slot {
+        return Err(reason!(ReviewError::InvalidAuctionPhase, "Auction settles once, after bidding closes"));
+    }
+    auction_data.settled = true;
+
+    if auction_data.highest_bidder == Pubkey::default() {
+        msg!("Auction {} had no bids", auction_data.id);
+        account_type::write(&auction_data, pda_auction)?;
+        return Ok(());
+    }
+
+    if *pda_review.key != auction_data.highest_intro {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Winning intro is {}", auction_data.highest_intro));
+    }
+    check_vault(program_id, ns, pda_auction.key, vault)?;
+    let mint_auth_bump = check_mint_auth(program_id, ns, mint_auth)?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    if pda_review.owner == program_id {
+        let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+        if review_data.is_initialized() && !review_data.closing {
+            review_data.featured_until = auction_data.feature_end_slot;
+            account_type::write(&review_data, pda_review)?;
+            summary::touch(program_id, ns, pda_review.key, pda_summary, |summary_data| {
+                summary_data.boost_score = summary_data.boost_score.saturating_add(auction_data.highest_bid);
+            })?;
+            msg!("{} is featured until slot {}", pda_review.key, auction_data.feature_end_slot);
+        }
+    }
+
+    burn_pda_tokens(program_id, ns, token_program, vault, token_mint, mint_auth, mint_auth_bump, pda_stats,
+        &auction_data.highest_bidder, auction_data.highest_bid, IndexEvent::BURN_AUCTION_WIN)?;
+
+    account_type::write(&auction_data, pda_auction)?;
+    Ok(())
+}
+
+// Returns an outbid bidder's escrow and closes their bid. The highest bid can't be
+// claimed, before or after settling.
+pub fn claim_bid_refund(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Claiming bid refund...");
+
+    check_account_count(accounts, 7)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let bidder = next_account_info(account_info_iter)?;
+    let pda_auction = next_account_info(account_info_iter)?;
+    let pda_bid = next_account_info(account_info_iter)?;
+    let bidder_ata = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !bidder.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    let auction_data = load_auction(program_id, ns, pda_auction)?;
+    check_vault(program_id, ns, pda_auction.key, vault)?;
+    let mint_auth_bump = check_mint_auth(program_id, ns, mint_auth)?;
+
+    if pda_bid.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let bid_data = account_type::read::<AuctionBid>(pda_bid).unwrap();
+    if !bid_data.is_initialized() || bid_data.auction != *pda_auction.key || bid_data.bidder != *bidder.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Bid does not belong to this bidder and auction"));
+    }
+    if auction_data.highest_bidder == *bidder.key {
+        return Err(reason!(ReviewError::InvalidAuctionPhase, "The highest bid is not refundable"));
+    }
+
+    invoke_signed(
+        &transfer(token_program.key, vault.key, bidder_ata.key, mint_auth.key, &[], bid_data.amount)?,
+        &[vault.clone(), bidder_ata.clone(), mint_auth.clone()],
+        &[&[ns, TOKEN_AUTH_SEED, &[mint_auth_bump]]],
+    )?;
+    close_account(pda_bid, bidder)?;
+
+    msg!("Refunded {} to {}", bid_data.amount, bidder.key);
+
+    Ok(())
+}
+
+// A bid adds tokens while bidding is open and leaves the bidder strictly ahead
+fn check_bid(auction_data: &Auction, slot: u64, amount: u64, total: u64) -> ProgramResult {
+    if auction_data.settled || slot < auction_data.start_slot || slot > auction_data.end_slot {
+        return Err(reason!(ReviewError::InvalidAuctionPhase, "Auction {} is not taking bids", auction_data.id));
+    }
+    if amount == 0 || total <= auction_data.highest_bid {
+        return Err(reason!(ReviewError::BidTooLow, "Bid of {} does not beat {}", total, auction_data.highest_bid));
+    }
+
+    Ok(())
+}
+
+fn check_vault(program_id: &Pubkey, ns: &[u8], auction: &Pubkey, vault: &AccountInfo) -> Result<u8, ProgramError> {
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(&[ns, auction.as_ref(), VAULT_SEED], program_id);
+    if vault_pda != *vault.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect auction vault account"));
+    }
+
+    Ok(vault_bump)
+}
+
+fn check_mint_auth(program_id: &Pubkey, ns: &[u8], mint_auth: &AccountInfo) -> Result<u8, ProgramError> {
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED], program_id);
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Mint passed in and mint derived do not match"));
+    }
+
+    Ok(mint_auth_bump)
+}
+
+fn load_auction(program_id: &Pubkey, ns: &[u8], pda_auction: &AccountInfo) -> Result<Auction, ProgramError> {
+    if pda_auction.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let auction_data = account_type::read::<Auction>(pda_auction).unwrap();
+    if !auction_data.is_initialized() || auction_data.discriminator != Auction::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Auction not initialized"));
+    }
+
+    let (auction_pda, _auction_bump) = Pubkey::find_program_address(
+        &[ns, AUCTION_SEED, auction_data.id.to_be_bytes().as_ref()], program_id
+    );
+    if auction_pda != *pda_auction.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for auction PDA"));
+    }
+
+    Ok(auction_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bids_must_lead_inside_the_window() {
+        let auction_data = Auction {
+            discriminator: Auction::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            id: 1,
+            start_slot: 10,
+            end_slot: 20,
+            feature_end_slot: 100,
+            highest_bid: 50,
+            highest_bidder: Pubkey::new_unique(),
+            highest_intro: Pubkey::new_unique(),
+            bid_count: 1,
+            settled: false,
+        };
+        assert!(check_bid(&auction_data, 15, 51, 51).is_ok());
+        // Topping up an earlier bid counts the total
+        assert!(check_bid(&auction_data, 15, 11, 51).is_ok());
+        assert_eq!(check_bid(&auction_data, 15, 50, 50), Err(ReviewError::BidTooLow.into()));
+        assert_eq!(check_bid(&auction_data, 15, 0, 60), Err(ReviewError::BidTooLow.into()));
+        assert_eq!(check_bid(&auction_data, 21, 60, 60), Err(ReviewError::InvalidAuctionPhase.into()));
+        assert_eq!(check_bid(&auction_data, 9, 60, 60), Err(ReviewError::InvalidAuctionPhase.into()));
+    }
+}
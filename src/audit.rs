@@ -0,0 +1,69 @@
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::AUDIT_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::state::{AuditEntry, Config};
+
+// Appends an audit entry for an admin-gated change and saves the config with its bumped
+// audit count. Call it after check_admin, with the config the handler already loaded.
+#[allow(clippy::too_many_arguments)]
+pub fn record<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    admin: &AccountInfo<'a>,
+    pda_config: &AccountInfo<'a>,
+    config_data: &mut Config,
+    pda_audit: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    action: u8,
+) -> ProgramResult {
+    let index = config_data.audit_count;
+    let (audit_pda, audit_bump) = Pubkey::find_program_address(
+        &[ns, AUDIT_SEED, index.to_be_bytes().as_ref()], program_id
+    );
+    if audit_pda != *pda_audit.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Audit entry {} lives at {}", index, audit_pda));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_audit.key,
+            rent.minimum_balance(AuditEntry::SIZE + account_type::TAG_LEN),
+            (AuditEntry::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_audit.clone(), system_program.clone()],
+        &[&[ns, AUDIT_SEED, index.to_be_bytes().as_ref(), &[audit_bump]]],
+    )?;
+
+    let entry = AuditEntry {
+        discriminator: AuditEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        index,
+        actor: *admin.key,
+        action,
+        slot: Clock::get()?.slot,
+    };
+    account_type::write(&entry, pda_audit)?;
+    msg!("Audit entry {}: action {} by {}", index, action, admin.key);
+
+    config_data.audit_count = index
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(config_data, pda_config)?;
+
+    Ok(())
+}
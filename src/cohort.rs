@@ -0,0 +1,449 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, rent::ID as RENT_PROGRAM_ID, Sysvar},
+};
+use spl_token::{
+    instruction::{initialize_mint, set_authority, AuthorityType},
+    state::Mint,
+    ID as TOKEN_PROGRAM_ID,
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::{
+    COHORT_SEED, DEFAULT_COMMENT_REWARD, DEFAULT_INTRO_REWARD, REWARD_DECIMALS, TOKEN_AUTH_SEED, TOKEN_MINT_SEED,
+};
+use crate::audit;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::events::{self, IndexEvent};
+use crate::processor::{check_account_count, check_admin, check_program_account, load_writable_config};
+use crate::state::{AuditEntry, Cohort, StudentIntroState};
+
+// What an intro's rewards are paid in and how much. The default cohort is the empty id,
+// whose mint is the original ["token_mint"] and whose amounts are the original ones.
+pub struct RewardSchedule {
+    pub cohort: String,
+    pub intro_reward: u64,
+    pub comment_reward: u64,
+}
+
+impl RewardSchedule {
+    pub fn default_cohort() -> Self {
+        RewardSchedule {
+            cohort: String::new(),
+            intro_reward: DEFAULT_INTRO_REWARD,
+            comment_reward: DEFAULT_COMMENT_REWARD,
+        }
+    }
+}
+
+pub fn initialize_cohort(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    id: String,
+    intro_reward: u64,
+    comment_reward: u64,
+) -> ProgramResult {
+    msg!("Initializing cohort {}...", id);
+
+    check_account_count(accounts, 9)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_cohort = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    // The empty id is the default cohort, whose mint InitializeMint creates
+    if id.is_empty() || id.len() > Cohort::MAX_ID_LEN {
+        return Err(reason!(ReviewError::InvalidDataLength, "Cohort id must be 1 to {} bytes", Cohort::MAX_ID_LEN));
+    }
+
+    let (cohort_pda, cohort_bump) = Pubkey::find_program_address(&[ns, COHORT_SEED, id.as_bytes()], program_id);
+    let (mint_pda, mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED, id.as_bytes()], program_id);
+    let (mint_auth_pda, _mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED, id.as_bytes()], program_id);
+    if cohort_pda != *pda_cohort.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for cohort PDA"));
+    }
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint account"));
+    }
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect mint auth account"));
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+    if *sysvar_rent.key != RENT_PROGRAM_ID {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect rent program"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_cohort.key,
+            rent.minimum_balance(Cohort::SIZE + account_type::TAG_LEN),
+            (Cohort::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_cohort.clone(), system_program.clone()],
+        &[&[ns, COHORT_SEED, id.as_bytes(), &[cohort_bump]]],
+    )?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            token_mint.key,
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64, // Size of the token mint account
+            token_program.key,
+        ),
+        &[admin.clone(), token_mint.clone(), system_program.clone()],
+        &[&[ns, TOKEN_MINT_SEED, id.as_bytes(), &[mint_bump]]],
+    )?;
+
+    invoke_signed(
+        &initialize_mint(
+            token_program.key,
+            token_mint.key,
+            mint_auth.key,
+            Option::None, // Freeze authority - we don't want anyone to be able to freeze!
+            REWARD_DECIMALS,
+        )?,
+        &[token_mint.clone(), sysvar_rent.clone(), mint_auth.clone()],
+        &[&[ns, TOKEN_MINT_SEED, id.as_bytes(), &[mint_bump]]],
+    )?;
+
+    msg!("Cohort mint: {:?}", mint_pda);
+
+    let cohort_data = Cohort {
+        discriminator: Cohort::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        id,
+        intro_reward,
+        comment_reward,
+        member_count: 0,
+    };
+    account_type::write(&cohort_data, pda_cohort)?;
+
+    config_data.cohorts_created = config_data.cohorts_created
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_INITIALIZE_COHORT)
+}
+
+// For students who switch tracks mid-course. Rewards already paid stay in the old
+// cohort's mint, which the intro remembers so an early delete burns its creation reward
+// there; the intro's later rewards come from the new one.
+pub fn reassign_cohort(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Reassigning intro cohort...");
+
+    check_account_count(accounts, 7)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let from_cohort = next_account_info(account_info_iter)?;
+    let to_cohort = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    let from = load_cohort(program_id, ns, from_cohort)?;
+    if from.cohort != review_data.cohort {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Intro belongs to cohort {:?}", review_data.cohort));
+    }
+    let to = load_cohort(program_id, ns, to_cohort)?;
+    if to.cohort == from.cohort {
+        msg!("Intro is already in cohort {:?}", to.cohort);
+        return Ok(());
+    }
+
+    count_member(from_cohort, false)?;
+    count_member(to_cohort, true)?;
+
+    msg!("Moving intro from cohort {:?} to {:?}", from.cohort, to.cohort);
+    record_minted_cohort(&mut review_data, &to.cohort);
+    review_data.cohort = to.cohort;
+    account_type::write(&review_data, pda_review)?;
+
+    events::emit(&IndexEvent::IntroIndexed {
+        intro: *pda_review.key,
+        reviewer: review_data.reviewer,
+        name: review_data.name,
+        cohort: review_data.cohort,
+    })?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_REASSIGN_COHORT)
+}
+
+fn record_minted_cohort(review_data: &mut StudentIntroState, to: &str) {
+    let minted_cohort = review_data.minted_cohort().to_string();
+    review_data.minted_in = if minted_cohort == to { None } else { Some(minted_cohort) };
+}
+
+// FinalizeCourse takes a (cohort, mint, mint_auth) triple for each cohort ahead of the
+// admin co-signers. Splits off the leading triples, telling them apart by the cohort
+// account the co-signer wallets can't be.
+pub(crate) fn split_cohort_mints<'b, 'a>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+) -> (&'b [AccountInfo<'a>], &'b [AccountInfo<'a>]) {
+    let mut len = 0;
+    while len + 3 <= accounts.len() && is_cohort(program_id, &accounts[len]) {
+        len += 3;
+    }
+    accounts.split_at(len)
+}
+
+fn is_cohort(program_id: &Pubkey, account: &AccountInfo) -> bool {
+    account.owner == program_id
+        && account_type::read::<String>(account).unwrap_or_default() == Cohort::DISCRIMINATOR
+}
+
+// Returns the bump of the cohort's mint authority once the accounts are its mint and
+// authority. The empty id is the default cohort's.
+fn check_mint_accounts(
+    program_id: &Pubkey,
+    ns: &[u8],
+    id: &str,
+    token_mint: &AccountInfo,
+    mint_auth: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED, id.as_bytes()], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED, id.as_bytes()], program_id);
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint for cohort {:?}", id));
+    }
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Mint passed in and mint derived do not match"));
+    }
+
+    Ok(mint_auth_bump)
+}
+
+// Takes away the cohort mint's authority for good, so nothing can be minted in it again
+pub(crate) fn revoke_mint_authority<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    id: &str,
+    token_mint: &AccountInfo<'a>,
+    mint_auth: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let mint_auth_bump = check_mint_accounts(program_id, ns, id, token_mint, mint_auth)?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    msg!("Revoking the mint authority of {}", token_mint.key);
+    invoke_signed(
+        &set_authority(
+            token_program.key,
+            token_mint.key,
+            None,
+            AuthorityType::MintTokens,
+            mint_auth.key,
+            &[],
+        )?,
+        &[token_mint.clone(), mint_auth.clone(), token_program.clone()],
+        &[&[ns, TOKEN_AUTH_SEED, id.as_bytes(), &[mint_auth_bump]]],
+    )
+}
+
+// Adds or removes an intro from the cohort's member count. The default cohort has no
+// account to count on. Expects a cohort load_cohort has already checked.
+pub fn count_member(pda_cohort: &AccountInfo, joined: bool) -> ProgramResult {
+    if pda_cohort.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut cohort_data = account_type::read::<Cohort>(pda_cohort).unwrap();
+    cohort_data.member_count = if joined {
+        cohort_data.member_count.checked_add(1).ok_or(ReviewError::ArithmeticOverflow)?
+    } else {
+        cohort_data.member_count.saturating_sub(1)
+    };
+    account_type::write(&cohort_data, pda_cohort)?;
+
+    Ok(())
+}
+
+// Reads the reward schedule of the cohort passed in. The default cohort has no account,
+// so its PDA is accepted uninitialized.
+pub fn load_cohort(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_cohort: &AccountInfo,
+) -> Result<RewardSchedule, ProgramError> {
+    let schedule = if pda_cohort.data_is_empty() {
+        RewardSchedule::default_cohort()
+    } else {
+        if pda_cohort.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let cohort_data = account_type::read::<Cohort>(pda_cohort).unwrap();
+        if !cohort_data.is_initialized() || cohort_data.discriminator != Cohort::DISCRIMINATOR {
+            return Err(reason!(ReviewError::UninitializedAccount, "Cohort account is not initialized"));
+        }
+
+        RewardSchedule {
+            cohort: cohort_data.id,
+            intro_reward: cohort_data.intro_reward,
+            comment_reward: cohort_data.comment_reward,
+        }
+    };
+
+    let (cohort_pda, _cohort_bump) = Pubkey::find_program_address(
+        &[ns, COHORT_SEED, schedule.cohort.as_bytes()], program_id);
+    if cohort_pda != *pda_cohort.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for cohort PDA"));
+    }
+
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use solana_program::borsh::try_from_slice_unchecked;
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+
+    #[test]
+    fn test_load_cohort_reads_the_cohort_passed_in() {
+        let program_id = Pubkey::new_unique();
+        let (default_key, _bump) = Pubkey::find_program_address(&[COHORT_SEED, b""], &program_id);
+        let (cohort_key, _bump) = Pubkey::find_program_address(&[COHORT_SEED, b"fall24"], &program_id);
+
+        let (mut lamports, mut data) = (0, vec![]);
+        let default_cohort = AccountInfo::new(&default_key, false, false, &mut lamports, &mut data, &program_id, false, 0);
+        let schedule = load_cohort(&program_id, &[], &default_cohort).unwrap();
+        assert!(schedule.cohort.is_empty());
+        assert_eq!(schedule.intro_reward, 10 * LAMPORTS_PER_SOL);
+
+        // An uninitialized account anywhere but the default PDA is rejected
+        let (mut other_lamports, mut other_data) = (0, vec![]);
+        let other = AccountInfo::new(&cohort_key, false, false, &mut other_lamports, &mut other_data, &program_id, false, 0);
+        assert!(load_cohort(&program_id, &[], &other).is_err());
+
+        let mut cohort_lamports = 1;
+        let mut cohort_data = vec![0u8; Cohort::SIZE];
+        Cohort {
+            discriminator: Cohort::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            id: "fall24".to_string(),
+            intro_reward: 3,
+            comment_reward: 1,
+            member_count: 0,
+        }.serialize(&mut &mut cohort_data[..]).unwrap();
+        let cohort = AccountInfo::new(&cohort_key, false, false, &mut cohort_lamports, &mut cohort_data, &program_id, false, 0);
+        let schedule = load_cohort(&program_id, &[], &cohort).unwrap();
+        assert_eq!(schedule.cohort, "fall24");
+        assert_eq!((schedule.intro_reward, schedule.comment_reward), (3, 1));
+
+        count_member(&cohort, true).unwrap();
+        count_member(&cohort, true).unwrap();
+        count_member(&cohort, false).unwrap();
+        let cohort_data = account_type::read::<Cohort>(&cohort).unwrap();
+        assert_eq!(cohort_data.member_count, 1);
+        // The default cohort has nothing to count on
+        count_member(&default_cohort, true).unwrap();
+    }
+
+    #[test]
+    fn test_reassigned_intro_remembers_where_its_reward_was_minted() {
+        let mut review_data = try_from_slice_unchecked::<StudentIntroState>(&[0u8; StudentIntroState::ACCOUNT_LEN]).unwrap();
+        review_data.cohort = "fall24".to_string();
+        assert_eq!(review_data.minted_cohort(), "fall24");
+
+        record_minted_cohort(&mut review_data, "spring25");
+        review_data.cohort = "spring25".to_string();
+        record_minted_cohort(&mut review_data, "");
+        review_data.cohort = String::new();
+        assert_eq!(review_data.minted_cohort(), "fall24");
+
+        // Moving back to where the reward was minted forgets it again
+        record_minted_cohort(&mut review_data, "fall24");
+        assert_eq!(review_data.minted_in, None);
+    }
+
+    #[test]
+    fn test_cohort_mints_are_split_from_the_co_signers() {
+        let program_id = Pubkey::new_unique();
+        let (cohort_key, _bump) = Pubkey::find_program_address(&[COHORT_SEED, b"fall24"], &program_id);
+        let (mint_key, _bump) = Pubkey::find_program_address(&[TOKEN_MINT_SEED, b"fall24"], &program_id);
+        let (mint_auth_key, _bump) = Pubkey::find_program_address(&[TOKEN_AUTH_SEED, b"fall24"], &program_id);
+        let (default_mint_key, _bump) = Pubkey::find_program_address(&[TOKEN_MINT_SEED, b""], &program_id);
+        let co_signer_key = Pubkey::new_unique();
+
+        let mut cohort_data = vec![0u8; Cohort::SIZE];
+        Cohort {
+            discriminator: Cohort::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            id: "fall24".to_string(),
+            intro_reward: 3,
+            comment_reward: 1,
+            member_count: 0,
+        }.serialize(&mut &mut cohort_data[..]).unwrap();
+        let (mut cohort_lamports, mut mint_lamports, mut mint_auth_lamports, mut co_signer_lamports) = (1, 1, 0, 1);
+        let (mut mint_data, mut mint_auth_data, mut co_signer_data) = (vec![], vec![], vec![]);
+        let system = solana_program::system_program::ID;
+        let accounts = [
+            AccountInfo::new(&cohort_key, false, false, &mut cohort_lamports, &mut cohort_data, &program_id, false, 0),
+            AccountInfo::new(&mint_key, false, true, &mut mint_lamports, &mut mint_data, &TOKEN_PROGRAM_ID, false, 0),
+            AccountInfo::new(&mint_auth_key, false, false, &mut mint_auth_lamports, &mut mint_auth_data, &system, false, 0),
+            AccountInfo::new(&co_signer_key, true, false, &mut co_signer_lamports, &mut co_signer_data, &system, false, 0),
+        ];
+
+        let (cohort_mints, co_signers) = split_cohort_mints(&program_id, &accounts);
+        assert_eq!(cohort_mints.len(), 3);
+        assert_eq!(co_signers.len(), 1);
+        assert_eq!(co_signers[0].key, &co_signer_key);
+        // Without cohorts everything is a co-signer
+        assert_eq!(split_cohort_mints(&program_id, &accounts[3..]).0.len(), 0);
+
+        // The cohort's own mint is revoked, not the default one
+        assert!(check_mint_accounts(&program_id, &[], "fall24", &cohort_mints[1], &cohort_mints[2]).is_ok());
+        let (mut default_lamports, mut default_data) = (1, vec![]);
+        let default_mint = AccountInfo::new(
+            &default_mint_key, false, true, &mut default_lamports, &mut default_data, &TOKEN_PROGRAM_ID, false, 0);
+        assert!(check_mint_accounts(&program_id, &[], "fall24", &default_mint, &cohort_mints[2]).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use solana_program::native_token::LAMPORTS_PER_SOL;
+
+// Everything a fork of the program may want to rebrand or retune, in one place. The
+// processor and the cpi helpers read PDA seeds from here, so a fork that changes a seed
+// derives every address of that account type from the new one. Changing a seed or the
+// intro length of a live deployment orphans its existing accounts; forks set them before
+// their first deploy. Account headroom sizes stay with their types in state, since
+// appended fields are sized against them.
+
+// Reward token mint and its mint authority, both per cohort
+pub const TOKEN_MINT_SEED: &[u8] = b"token_mint";
+pub const TOKEN_AUTH_SEED: &[u8] = b"token_auth";
+
+// Program-wide accounts
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const PENDING_CONFIG_SEED: &[u8] = b"pending_config";
+pub const GLOBAL_STATS_SEED: &[u8] = b"global_stats";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const BANNED_PHRASES_SEED: &[u8] = b"banned_phrases";
+pub const MIGRATION_BOUNTY_SEED: &[u8] = b"migration_bounty";
+pub const AUDIT_SEED: &[u8] = b"audit";
+pub const THREAD_SEED: &[u8] = b"thread";
+pub const COHORT_SEED: &[u8] = b"cohort";
+pub const TEMPLATE_SEED: &[u8] = b"template";
+pub const CONTENT_SEED: &[u8] = b"content";
+pub const TAG_SEED: &[u8] = b"tag";
+pub const GROUP_SEED: &[u8] = b"group";
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+pub const AUCTION_SEED: &[u8] = b"auction";
+
+// Per wallet
+pub const USER_SEED: &[u8] = b"user";
+pub const TRANSFER_SEED: &[u8] = b"transfer";
+
+// Per intro, and the intro and comment indexes
+pub const INTRO_SEED: &[u8] = b"intro";
+pub const COMMENT_SEED: &[u8] = b"comment";
+pub const COMMENT_PAGE_SEED: &[u8] = b"comments";
+pub const COMMENTER_SEED: &[u8] = b"commenter";
+pub const SUMMARY_SEED: &[u8] = b"summary";
+pub const EDITOR_SEED: &[u8] = b"editor";
+pub const ATTRIBUTES_SEED: &[u8] = b"attributes";
+
+// Per comment
+pub const TRANSLATION_SEED: &[u8] = b"translation";
+pub const REACTION_SEED: &[u8] = b"reaction";
+
+// Per group, raffle and auction
+pub const MEMBER_SEED: &[u8] = b"member";
+pub const TICKET_SEED: &[u8] = b"ticket";
+pub const BID_SEED: &[u8] = b"bid";
+pub const VAULT_SEED: &[u8] = b"vault";
+
+// Fixed size intros are allocated at, which bounds how long their content can grow
+pub const INTRO_ACCOUNT_LEN: usize = 1000;
+
+// Decimals every reward mint is created with, and transfers are checked against
+pub const REWARD_DECIMALS: u8 = 9;
+
+// What the default cohort pays, in the mint's smallest unit, 10^REWARD_DECIMALS a token
+pub const DEFAULT_INTRO_REWARD: u64 = 10 * LAMPORTS_PER_SOL;
+pub const DEFAULT_COMMENT_REWARD: u64 = 5 * LAMPORTS_PER_SOL;
@@ -0,0 +1,190 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::rent::Rent,
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::CONTENT_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::moderation;
+use crate::processor::{check_account_count, close_account};
+use crate::state::ContentEntry;
+
+// Intro messages hash after the same normalization as banned phrases, so changing case,
+// punctuation or spacing doesn't make a copied intro look new
+pub fn message_hash(message: &str) -> [u8; 32] {
+    moderation::phrase_hash(message)
+}
+
+// Lets a client find out whether a message would be rejected as a copy before paying
+// for the intro. Fails when the message's registry entry exists.
+pub fn check_duplicate_content(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    message: String,
+) -> ProgramResult {
+    msg!("Checking intro content...");
+
+    check_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_entry = next_account_info(account_info_iter)?;
+
+    let hash = message_hash(&message);
+    match load_entry(program_id, ns, pda_entry, &hash)? {
+        Some(entry_data) => {
+            Err(reason!(ReviewError::DuplicateContent, "Content is already used by intro {}", entry_data.intro))
+        }
+        None => {
+            msg!("Content is not registered yet");
+            Ok(())
+        }
+    }
+}
+
+// Claims the registry entry for an intro's message hash. An entry that already names the
+// intro is fine, which lets a student delete an intro and post it again under the same
+// name; any other owner means the message was copied.
+#[allow(clippy::too_many_arguments)]
+pub fn register<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    payer: &AccountInfo<'a>,
+    pda_entry: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    hash: &[u8; 32],
+    intro: &Pubkey,
+    rent: &Rent,
+    slot: u64,
+) -> ProgramResult {
+    if !needs_entry(program_id, ns, pda_entry, hash, intro)? {
+        return Ok(());
+    }
+
+    let (_entry_pda, entry_bump) = Pubkey::find_program_address(&[ns, CONTENT_SEED, hash.as_ref()], program_id);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_entry.key,
+            rent.minimum_balance(ContentEntry::SIZE + account_type::TAG_LEN),
+            (ContentEntry::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[payer.clone(), pda_entry.clone(), system_program.clone()],
+        &[&[ns, CONTENT_SEED, hash.as_ref(), &[entry_bump]]],
+    )?;
+
+    let entry_data = ContentEntry {
+        discriminator: ContentEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        hash: *hash,
+        intro: *intro,
+        author: *payer.key,
+        slot,
+    };
+    account_type::write(&entry_data, pda_entry)?;
+
+    Ok(())
+}
+
+// Whether registering the hash for the intro would create its entry. Fails when another
+// intro holds it.
+pub fn needs_entry(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_entry: &AccountInfo,
+    hash: &[u8; 32],
+    intro: &Pubkey,
+) -> Result<bool, ProgramError> {
+    match load_entry(program_id, ns, pda_entry, hash)? {
+        Some(entry_data) if entry_data.intro != *intro => {
+            Err(reason!(ReviewError::DuplicateContent, "Content is already used by intro {}", entry_data.intro))
+        }
+        Some(_) => Ok(false),
+        None => Ok(true),
+    }
+}
+
+// Drops the entry for content an intro no longer uses, refunding its rent
+pub fn release(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_entry: &AccountInfo,
+    hash: &[u8; 32],
+    intro: &Pubkey,
+    receiver: &AccountInfo,
+) -> ProgramResult {
+    match load_entry(program_id, ns, pda_entry, hash)? {
+        Some(entry_data) if entry_data.intro == *intro => close_account(pda_entry, receiver),
+        _ => Ok(()),
+    }
+}
+
+fn load_entry(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_entry: &AccountInfo,
+    hash: &[u8; 32],
+) -> Result<Option<ContentEntry>, ProgramError> {
+    let (entry_pda, _entry_bump) = Pubkey::find_program_address(&[ns, CONTENT_SEED, hash.as_ref()], program_id);
+    if entry_pda != *pda_entry.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for content registry PDA"));
+    }
+    if pda_entry.data_is_empty() {
+        return Ok(None);
+    }
+    if pda_entry.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let entry_data = account_type::read::<ContentEntry>(pda_entry).unwrap();
+    if !entry_data.is_initialized() {
+        return Ok(None);
+    }
+
+    Ok(Some(entry_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_registered_content_rejects_other_intros() {
+        let program_id = Pubkey::new_unique();
+        let intro = Pubkey::new_unique();
+        let hash = message_hash("Hi, I'm Ada and I love Rust!");
+        assert_eq!(hash, message_hash("hi  im ada and i LOVE rust"));
+
+        let (entry_key, _bump) = Pubkey::find_program_address(&[CONTENT_SEED, hash.as_ref()], &program_id);
+        let mut data = vec![0u8; ContentEntry::SIZE];
+        ContentEntry {
+            discriminator: ContentEntry::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            hash,
+            intro,
+            author: Pubkey::new_unique(),
+            slot: 1,
+        }.serialize(&mut &mut data[..]).unwrap();
+
+        let mut lamports = 1;
+        let pda_entry = AccountInfo::new(&entry_key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        assert_eq!(
+            check_duplicate_content(&program_id, &[], std::slice::from_ref(&pda_entry), "HI, i'm Ada and I love rust".to_string()),
+            Err(ReviewError::DuplicateContent.into())
+        );
+        assert_eq!(load_entry(&program_id, &[], &pda_entry, &hash).unwrap().unwrap().intro, intro);
+        // A different message lives at a different entry
+        assert!(load_entry(&program_id, &[], &pda_entry, &message_hash("something else")).is_err());
+    }
+}
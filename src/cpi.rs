@@ -0,0 +1,463 @@
+// Instruction builders, PDA helpers and CPI wrappers for programs that call this one,
+// e.g. a grading program posting instructor feedback as comments. Depend on this crate
+// with the no-entrypoint feature so its entrypoint isn't linked into the caller.
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use borsh::BorshSerialize;
+use crate::constants::{
+    BANNED_PHRASES_SEED, COHORT_SEED, COMMENTER_SEED, COMMENT_PAGE_SEED, COMMENT_SEED, CONFIG_SEED,
+    GLOBAL_STATS_SEED, SUMMARY_SEED, TOKEN_AUTH_SEED, TOKEN_MINT_SEED, TRANSLATION_SEED, TREASURY_SEED,
+    USER_SEED,
+};
+use crate::instruction::StudentIntroInstruction;
+
+pub use crate::state::{CommentPage, CommentTranslation, StudentIntroComment, StudentIntroCommentCounter, StudentIntroState};
+
+const ADD_COMMENT: u8 = 2;
+const ADD_KEYED_COMMENT: u8 = 27;
+const UPDATE_COMMENT: u8 = 28;
+const ADD_TRANSLATION: u8 = 36;
+const DELETE_COMMENT: u8 = 37;
+
+// The program being called, the deployment namespace and the calling program's seeds
+// for any of its PDAs that sign, like a grader's commenter account
+pub struct CpiContext<'a, 'b> {
+    pub program: AccountInfo<'a>,
+    pub namespace: &'b [u8],
+    pub signer_seeds: &'b [&'b [&'b [u8]]],
+}
+
+impl<'a, 'b> CpiContext<'a, 'b> {
+    pub fn new(program: AccountInfo<'a>, namespace: &'b [u8]) -> Self {
+        CpiContext { program, namespace, signer_seeds: &[] }
+    }
+
+    pub fn with_signer(mut self, signer_seeds: &'b [&'b [&'b [u8]]]) -> Self {
+        self.signer_seeds = signer_seeds;
+        self
+    }
+}
+
+pub fn intro_address(program_id: &Pubkey, ns: &[u8], reviewer: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[ns, reviewer.as_ref(), name.as_bytes()], program_id).0
+}
+
+pub fn counter_address(program_id: &Pubkey, ns: &[u8], intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ns, intro.as_ref(), COMMENT_SEED], program_id).0
+}
+
+pub fn intro_summary_address(program_id: &Pubkey, ns: &[u8], intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ns, intro.as_ref(), SUMMARY_SEED], program_id).0
+}
+
+pub fn comment_page_address(program_id: &Pubkey, ns: &[u8], intro: &Pubkey, page_index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[ns, intro.as_ref(), COMMENT_PAGE_SEED, page_index.to_be_bytes().as_ref()], program_id).0
+}
+
+// The comment at a page slot, with the slot encoded for the intro's comment seed version
+// by StudentIntroCommentCounter::versioned_slot_seed
+pub fn paged_comment_address(program_id: &Pubkey, ns: &[u8], page: &Pubkey, slot_seed: [u8; 8]) -> Pubkey {
+    Pubkey::find_program_address(&[ns, page.as_ref(), slot_seed.as_ref()], program_id).0
+}
+
+pub fn keyed_comment_address(program_id: &Pubkey, ns: &[u8], intro: &Pubkey, commenter: &Pubkey, comment_index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ns, intro.as_ref(), commenter.as_ref(), comment_index.to_be_bytes().as_ref()], program_id).0
+}
+
+pub fn translation_address(program_id: &Pubkey, ns: &[u8], comment: &Pubkey, language: &str) -> Pubkey {
+    Pubkey::find_program_address(&[ns, comment.as_ref(), TRANSLATION_SEED, language.as_bytes()], program_id).0
+}
+
+pub fn user_record_address(program_id: &Pubkey, ns: &[u8], wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ns, USER_SEED, wallet.as_ref()], program_id).0
+}
+
+pub fn user_comment_address(program_id: &Pubkey, ns: &[u8], commenter: &Pubkey, comment_index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ns, commenter.as_ref(), COMMENT_SEED, comment_index.to_be_bytes().as_ref()], program_id).0
+}
+
+pub fn commenter_record_address(program_id: &Pubkey, ns: &[u8], intro: &Pubkey, commenter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ns, intro.as_ref(), COMMENTER_SEED, commenter.as_ref()], program_id).0
+}
+
+pub fn config_address(program_id: &Pubkey, ns: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[ns, CONFIG_SEED], program_id).0
+}
+
+pub fn stats_address(program_id: &Pubkey, ns: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[ns, GLOBAL_STATS_SEED], program_id).0
+}
+
+pub fn treasury_address(program_id: &Pubkey, ns: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[ns, TREASURY_SEED], program_id).0
+}
+
+pub fn banned_phrases_address(program_id: &Pubkey, ns: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[ns, BANNED_PHRASES_SEED], program_id).0
+}
+
+pub fn cohort_address(program_id: &Pubkey, ns: &[u8], cohort: &str) -> Pubkey {
+    Pubkey::find_program_address(&[ns, COHORT_SEED, cohort.as_bytes()], program_id).0
+}
+
+pub fn mint_address(program_id: &Pubkey, ns: &[u8], cohort: &str) -> Pubkey {
+    Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED, cohort.as_bytes()], program_id).0
+}
+
+pub fn mint_auth_address(program_id: &Pubkey, ns: &[u8], cohort: &str) -> Pubkey {
+    Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED, cohort.as_bytes()], program_id).0
+}
+
+// Accounts of AddComment and AddKeyedComment, in instruction order. A keyed comment
+// doesn't use the page, but it still takes the slot. The counter is only read, for intros
+// that don't record their comment seed version yet.
+pub struct AddCommentAccounts<'a> {
+    pub commenter: AccountInfo<'a>,
+    pub intro: AccountInfo<'a>,
+    pub counter: AccountInfo<'a>,
+    pub comment: AccountInfo<'a>,
+    pub token_mint: AccountInfo<'a>,
+    pub mint_auth: AccountInfo<'a>,
+    pub commenter_ata: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub token_program: AccountInfo<'a>,
+    pub user_record: AccountInfo<'a>,
+    pub user_comment: AccountInfo<'a>,
+    pub stats: AccountInfo<'a>,
+    pub commenter_record: AccountInfo<'a>,
+    pub reviewer_ata: AccountInfo<'a>,
+    pub config: AccountInfo<'a>,
+    pub treasury: AccountInfo<'a>,
+    pub reviewer_user_record: AccountInfo<'a>,
+    pub banned_phrases: AccountInfo<'a>,
+    pub page: AccountInfo<'a>,
+    pub cohort: AccountInfo<'a>,
+    pub summary: AccountInfo<'a>,
+}
+
+impl<'a> AddCommentAccounts<'a> {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*self.commenter.key, true),
+            AccountMeta::new(*self.intro.key, false),
+            AccountMeta::new_readonly(*self.counter.key, false),
+            AccountMeta::new(*self.comment.key, false),
+            AccountMeta::new(*self.token_mint.key, false),
+            AccountMeta::new_readonly(*self.mint_auth.key, false),
+            AccountMeta::new(*self.commenter_ata.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+            AccountMeta::new(*self.user_record.key, false),
+            AccountMeta::new(*self.user_comment.key, false),
+            AccountMeta::new(*self.stats.key, false),
+            AccountMeta::new(*self.commenter_record.key, false),
+            AccountMeta::new(*self.reviewer_ata.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+            AccountMeta::new(*self.treasury.key, false),
+            AccountMeta::new(*self.reviewer_user_record.key, false),
+            AccountMeta::new_readonly(*self.banned_phrases.key, false),
+            AccountMeta::new(*self.page.key, false),
+            AccountMeta::new(*self.cohort.key, false),
+            AccountMeta::new(*self.summary.key, false),
+        ]
+    }
+
+    pub fn to_account_infos(&self) -> Vec<AccountInfo<'a>> {
+        vec![
+            self.commenter.clone(),
+            self.intro.clone(),
+            self.counter.clone(),
+            self.comment.clone(),
+            self.token_mint.clone(),
+            self.mint_auth.clone(),
+            self.commenter_ata.clone(),
+            self.system_program.clone(),
+            self.token_program.clone(),
+            self.user_record.clone(),
+            self.user_comment.clone(),
+            self.stats.clone(),
+            self.commenter_record.clone(),
+            self.reviewer_ata.clone(),
+            self.config.clone(),
+            self.treasury.clone(),
+            self.reviewer_user_record.clone(),
+            self.banned_phrases.clone(),
+            self.page.clone(),
+            self.cohort.clone(),
+            self.summary.clone(),
+        ]
+    }
+}
+
+pub struct UpdateCommentAccounts<'a> {
+    pub commenter: AccountInfo<'a>,
+    pub intro: AccountInfo<'a>,
+    pub comment: AccountInfo<'a>,
+    pub banned_phrases: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub config: AccountInfo<'a>,
+}
+
+impl<'a> UpdateCommentAccounts<'a> {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*self.commenter.key, true),
+            AccountMeta::new_readonly(*self.intro.key, false),
+            AccountMeta::new(*self.comment.key, false),
+            AccountMeta::new_readonly(*self.banned_phrases.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+        ]
+    }
+
+    pub fn to_account_infos(&self) -> Vec<AccountInfo<'a>> {
+        vec![
+            self.commenter.clone(),
+            self.intro.clone(),
+            self.comment.clone(),
+            self.banned_phrases.clone(),
+            self.system_program.clone(),
+            self.config.clone(),
+        ]
+    }
+}
+
+pub struct DeleteCommentAccounts<'a> {
+    pub actor: AccountInfo<'a>,
+    pub intro: AccountInfo<'a>,
+    pub comment: AccountInfo<'a>,
+    pub commenter: AccountInfo<'a>,
+    pub stats: AccountInfo<'a>,
+    pub config: AccountInfo<'a>,
+    pub summary: AccountInfo<'a>,
+}
+
+impl<'a> DeleteCommentAccounts<'a> {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(*self.actor.key, true),
+            AccountMeta::new(*self.intro.key, false),
+            AccountMeta::new(*self.comment.key, false),
+            AccountMeta::new(*self.commenter.key, false),
+            AccountMeta::new(*self.stats.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+            AccountMeta::new(*self.summary.key, false),
+        ]
+    }
+
+    pub fn to_account_infos(&self) -> Vec<AccountInfo<'a>> {
+        vec![
+            self.actor.clone(),
+            self.intro.clone(),
+            self.comment.clone(),
+            self.commenter.clone(),
+            self.stats.clone(),
+            self.config.clone(),
+            self.summary.clone(),
+        ]
+    }
+}
+
+pub struct AddTranslationAccounts<'a> {
+    pub author: AccountInfo<'a>,
+    pub intro: AccountInfo<'a>,
+    pub comment: AccountInfo<'a>,
+    pub translation: AccountInfo<'a>,
+    pub banned_phrases: AccountInfo<'a>,
+    pub config: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+}
+
+impl<'a> AddTranslationAccounts<'a> {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*self.author.key, true),
+            AccountMeta::new_readonly(*self.intro.key, false),
+            AccountMeta::new(*self.comment.key, false),
+            AccountMeta::new(*self.translation.key, false),
+            AccountMeta::new_readonly(*self.banned_phrases.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+        ]
+    }
+
+    pub fn to_account_infos(&self) -> Vec<AccountInfo<'a>> {
+        vec![
+            self.author.clone(),
+            self.intro.clone(),
+            self.comment.clone(),
+            self.translation.clone(),
+            self.banned_phrases.clone(),
+            self.config.clone(),
+            self.system_program.clone(),
+        ]
+    }
+}
+
+pub fn add_comment_instruction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: Vec<AccountMeta>,
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    visibility: u8,
+) -> Result<Instruction, ProgramError> {
+    build(program_id, ns, ADD_COMMENT, accounts, &(comment, link, content_hash, visibility))
+}
+
+pub fn add_keyed_comment_instruction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: Vec<AccountMeta>,
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    visibility: u8,
+) -> Result<Instruction, ProgramError> {
+    build(program_id, ns, ADD_KEYED_COMMENT, accounts, &(comment, link, content_hash, visibility))
+}
+
+pub fn update_comment_instruction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: Vec<AccountMeta>,
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    expected_hash: Option<[u8; 32]>,
+) -> Result<Instruction, ProgramError> {
+    build(program_id, ns, UPDATE_COMMENT, accounts, &(comment, link, content_hash, expected_hash))
+}
+
+pub fn delete_comment_instruction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: Vec<AccountMeta>,
+) -> Result<Instruction, ProgramError> {
+    build(program_id, ns, DELETE_COMMENT, accounts, &())
+}
+
+pub fn add_translation_instruction(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: Vec<AccountMeta>,
+    language: String,
+    text: String,
+) -> Result<Instruction, ProgramError> {
+    build(program_id, ns, ADD_TRANSLATION, accounts, &(language, text))
+}
+
+pub fn add_comment<'a>(
+    ctx: &CpiContext<'a, '_>,
+    accounts: &AddCommentAccounts<'a>,
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    visibility: u8,
+) -> ProgramResult {
+    let instruction = add_comment_instruction(
+        ctx.program.key, ctx.namespace, accounts.to_account_metas(), comment, link, content_hash, visibility)?;
+    invoke(ctx, &instruction, accounts.to_account_infos())
+}
+
+pub fn add_keyed_comment<'a>(
+    ctx: &CpiContext<'a, '_>,
+    accounts: &AddCommentAccounts<'a>,
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    visibility: u8,
+) -> ProgramResult {
+    let instruction = add_keyed_comment_instruction(
+        ctx.program.key, ctx.namespace, accounts.to_account_metas(), comment, link, content_hash, visibility)?;
+    invoke(ctx, &instruction, accounts.to_account_infos())
+}
+
+pub fn update_comment<'a>(
+    ctx: &CpiContext<'a, '_>,
+    accounts: &UpdateCommentAccounts<'a>,
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    expected_hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    let instruction = update_comment_instruction(
+        ctx.program.key, ctx.namespace, accounts.to_account_metas(), comment, link, content_hash, expected_hash)?;
+    invoke(ctx, &instruction, accounts.to_account_infos())
+}
+
+pub fn delete_comment<'a>(ctx: &CpiContext<'a, '_>, accounts: &DeleteCommentAccounts<'a>) -> ProgramResult {
+    let instruction = delete_comment_instruction(ctx.program.key, ctx.namespace, accounts.to_account_metas())?;
+    invoke(ctx, &instruction, accounts.to_account_infos())
+}
+
+pub fn add_translation<'a>(
+    ctx: &CpiContext<'a, '_>,
+    accounts: &AddTranslationAccounts<'a>,
+    language: String,
+    text: String,
+) -> ProgramResult {
+    let instruction = add_translation_instruction(
+        ctx.program.key, ctx.namespace, accounts.to_account_metas(), language, text)?;
+    invoke(ctx, &instruction, accounts.to_account_infos())
+}
+
+fn build(
+    program_id: &Pubkey,
+    ns: &[u8],
+    variant: u8,
+    accounts: Vec<AccountMeta>,
+    payload: &impl BorshSerialize,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: StudentIntroInstruction::pack_namespaced(ns, variant, &payload.try_to_vec()?)?,
+    })
+}
+
+fn invoke<'a>(ctx: &CpiContext<'a, '_>, instruction: &Instruction, mut account_infos: Vec<AccountInfo<'a>>) -> ProgramResult {
+    account_infos.push(ctx.program.clone());
+    invoke_signed(instruction, &account_infos, ctx.signer_seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders_produce_data_the_program_reads() {
+        let program_id = Pubkey::new_unique();
+        let instruction = add_keyed_comment_instruction(
+            &program_id, b"cs50", vec![], "Graded: 9/10".to_string(), String::new(), [0u8; 32],
+            StudentIntroComment::VISIBILITY_OWNER).unwrap();
+
+        let (namespace, unpacked) = StudentIntroInstruction::unpack_namespaced(&instruction.data).unwrap();
+        assert_eq!(namespace, b"cs50");
+        assert!(matches!(
+            unpacked,
+            StudentIntroInstruction::AddKeyedComment { comment, visibility: StudentIntroComment::VISIBILITY_OWNER, .. }
+                if comment == "Graded: 9/10"
+        ));
+
+        let instruction = update_comment_instruction(
+            &program_id, &[], vec![], "Graded: 10/10".to_string(), String::new(), [0u8; 32], Some([1u8; 32])).unwrap();
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&instruction.data),
+            Ok(StudentIntroInstruction::UpdateComment { expected_hash: Some(_), .. })
+        ));
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&delete_comment_instruction(&program_id, &[], vec![]).unwrap().data),
+            Ok(StudentIntroInstruction::DeleteComment)
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::THREAD_SEED;
+use crate::audit;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_admin, load_writable_config};
+use crate::state::{AuditEntry, MaintenanceThread};
+
+pub fn initialize_thread(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    task: u8,
+    interval_slots: u64,
+) -> ProgramResult {
+    msg!("Initializing maintenance thread for task {}...", task);
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_thread = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    if task > MaintenanceThread::TASK_EXPIRE_REWARDS {
+        msg!("Unknown maintenance task {}", task);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (thread_pda, thread_bump) = Pubkey::find_program_address(&[ns, THREAD_SEED, &[task]], program_id);
+    if thread_pda != *pda_thread.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for thread PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_thread.key,
+            rent.minimum_balance(MaintenanceThread::SIZE + account_type::TAG_LEN),
+            (MaintenanceThread::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_thread.clone(), system_program.clone()],
+        &[&[ns, THREAD_SEED, &[task], &[thread_bump]]],
+    )?;
+
+    let thread_data = MaintenanceThread {
+        discriminator: MaintenanceThread::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        task,
+        interval_slots,
+        next_run_slot: 0,
+        last_run_slot: 0,
+        run_count: 0,
+    };
+    account_type::write(&thread_data, pda_thread)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_INITIALIZE_THREAD)
+}
+
+// Cranks take their task's thread as an optional last account, so a crank that is
+// called by hand keeps working without one
+pub fn split_thread<'b, 'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    task: u8,
+    accounts: &'b [AccountInfo<'a>],
+) -> (&'b [AccountInfo<'a>], Option<&'b AccountInfo<'a>>) {
+    let (thread_pda, _thread_bump) = Pubkey::find_program_address(&[ns, THREAD_SEED, &[task]], program_id);
+
+    match accounts.split_last() {
+        Some((last, rest)) if *last.key == thread_pda => (rest, Some(last)),
+        _ => (accounts, None),
+    }
+}
+
+// Books a run on the thread and returns true, or returns false when the thread's next
+// run isn't due yet so the crank can exit without doing anything
+pub fn start_run(
+    program_id: &Pubkey,
+    pda_thread: &AccountInfo,
+    slot: u64,
+) -> Result<bool, ProgramError> {
+    if pda_thread.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut thread_data = account_type::read::<MaintenanceThread>(pda_thread).unwrap();
+    if !thread_data.is_initialized() || thread_data.discriminator != MaintenanceThread::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Thread not initialized"));
+    }
+
+    if slot < thread_data.next_run_slot {
+        msg!("Task {} is not due until slot {}", thread_data.task, thread_data.next_run_slot);
+        return Ok(false);
+    }
+
+    thread_data.last_run_slot = slot;
+    thread_data.next_run_slot = slot.saturating_add(thread_data.interval_slots);
+    thread_data.run_count = thread_data.run_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&thread_data, pda_thread)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_thread_gates_runs_on_its_interval() {
+        let program_id = Pubkey::new_unique();
+        let (thread_key, _bump) = Pubkey::find_program_address(
+            &[THREAD_SEED, &[MaintenanceThread::TASK_EXPIRE_REWARDS]], &program_id);
+
+        let mut data = vec![0u8; MaintenanceThread::SIZE];
+        MaintenanceThread {
+            discriminator: MaintenanceThread::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            task: MaintenanceThread::TASK_EXPIRE_REWARDS,
+            interval_slots: 100,
+            next_run_slot: 0,
+            last_run_slot: 0,
+            run_count: 0,
+        }.serialize(&mut &mut data[..]).unwrap();
+
+        let other_key = Pubkey::new_unique();
+        let (mut lamports, mut other_lamports) = (1, 1);
+        let mut other_data = vec![];
+        let accounts = [
+            AccountInfo::new(&other_key, false, true, &mut other_lamports, &mut other_data, &program_id, false, 0),
+            AccountInfo::new(&thread_key, false, true, &mut lamports, &mut data, &program_id, false, 0),
+        ];
+
+        let (rest, thread) = split_thread(&program_id, &[], MaintenanceThread::TASK_EXPIRE_REWARDS, &accounts);
+        assert_eq!(rest.len(), 1);
+        let thread = thread.unwrap();
+        // Other tasks' threads are not picked up
+        assert!(split_thread(&program_id, &[], MaintenanceThread::TASK_DRAW_RAFFLE, &accounts).1.is_none());
+
+        assert!(start_run(&program_id, thread, 50).unwrap());
+        assert!(!start_run(&program_id, thread, 120).unwrap());
+        assert!(start_run(&program_id, thread, 150).unwrap());
+
+        let thread_data = account_type::read::<MaintenanceThread>(thread).unwrap();
+        assert_eq!(thread_data.run_count, 2);
+        assert_eq!(thread_data.next_run_slot, 250);
+    }
+}
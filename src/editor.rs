@@ -0,0 +1,223 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::EDITOR_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_program_account, close_account, load_writable_config};
+use crate::state::{IntroEditor, StudentIntroState};
+
+// Lets the editor update the reviewer's intro through a grant at [intro, "editor", editor].
+// The reviewer pays its rent and gets it back on revoke.
+pub fn grant_editor(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    editor: Pubkey,
+) -> ProgramResult {
+    msg!("Granting {} edit rights...", editor);
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_editor = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    load_writable_config(program_id, ns, pda_config)?;
+    load_owned_intro(program_id, reviewer, pda_review)?;
+    if editor == *reviewer.key {
+        msg!("The reviewer can already edit their intro");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let editor_bump = check_editor_address(program_id, ns, pda_review.key, &editor, pda_editor)?;
+    if !pda_editor.data_is_empty() {
+        msg!("{} can already edit this intro", editor);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            reviewer.key,
+            pda_editor.key,
+            rent.minimum_balance(IntroEditor::SIZE + account_type::TAG_LEN),
+            (IntroEditor::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[reviewer.clone(), pda_editor.clone(), system_program.clone()],
+        &[&[ns, pda_review.key.as_ref(), EDITOR_SEED, editor.as_ref(), &[editor_bump]]],
+    )?;
+
+    let editor_data = IntroEditor {
+        discriminator: IntroEditor::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *pda_review.key,
+        editor,
+        granted_slot: Clock::get()?.slot,
+    };
+    account_type::write(&editor_data, pda_editor)?;
+
+    Ok(())
+}
+
+// Closes the editor's grant, returning its rent to the reviewer. Works on a finalized
+// course too, so access can always be taken back.
+pub fn revoke_editor(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    editor: Pubkey,
+) -> ProgramResult {
+    msg!("Revoking {} edit rights...", editor);
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_editor = next_account_info(account_info_iter)?;
+
+    load_owned_intro(program_id, reviewer, pda_review)?;
+    check_editor_address(program_id, ns, pda_review.key, &editor, pda_editor)?;
+    load_grant(program_id, pda_editor)?;
+
+    close_account(pda_editor, reviewer)
+}
+
+// Checks that the signer may edit the intro: either it's the reviewer's, or the signer
+// holds a grant passed as pda_editor
+pub fn check_editor(
+    program_id: &Pubkey,
+    ns: &[u8],
+    signer: &AccountInfo,
+    intro: &Pubkey,
+    review_data: &StudentIntroState,
+    pda_editor: Option<&AccountInfo>,
+) -> ProgramResult {
+    if review_data.reviewer == *signer.key {
+        return Ok(());
+    }
+
+    let pda_editor = pda_editor.ok_or_else(||
+        reason!(ReviewError::Unauthorized, "Only the reviewer or an editor they granted can update the intro"))?;
+    check_editor_address(program_id, ns, intro, signer.key, pda_editor)?;
+    if pda_editor.data_is_empty() {
+        return Err(reason!(ReviewError::Unauthorized, "{} has no edit rights on this intro", signer.key));
+    }
+    load_grant(program_id, pda_editor)?;
+
+    Ok(())
+}
+
+fn load_owned_intro(program_id: &Pubkey, reviewer: &AccountInfo, pda_review: &AccountInfo) -> ProgramResult {
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    if review_data.reviewer != *reviewer.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the reviewer can manage who edits the intro"));
+    }
+
+    Ok(())
+}
+
+fn check_editor_address(
+    program_id: &Pubkey,
+    ns: &[u8],
+    intro: &Pubkey,
+    editor: &Pubkey,
+    pda_editor: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (editor_pda, editor_bump) = Pubkey::find_program_address(
+        &[ns, intro.as_ref(), EDITOR_SEED, editor.as_ref()], program_id);
+    if editor_pda != *pda_editor.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for intro editor PDA"));
+    }
+
+    Ok(editor_bump)
+}
+
+fn load_grant(program_id: &Pubkey, pda_editor: &AccountInfo) -> Result<IntroEditor, ProgramError> {
+    if pda_editor.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let editor_data = account_type::read::<IntroEditor>(pda_editor).unwrap();
+    if !editor_data.is_initialized() || editor_data.discriminator != IntroEditor::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Intro editor grant is not initialized"));
+    }
+
+    Ok(editor_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::borsh::try_from_slice_unchecked;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_only_reviewer_or_granted_editor_can_edit() {
+        let program_id = Pubkey::new_unique();
+        let ns: &[u8] = b"";
+        let intro = Pubkey::new_unique();
+        let reviewer = Pubkey::new_unique();
+        let editor = Pubkey::new_unique();
+        let review_data = StudentIntroState {
+            reviewer,
+            ..try_from_slice_unchecked::<StudentIntroState>(&[0u8; StudentIntroState::ACCOUNT_LEN]).unwrap()
+        };
+
+        let mut reviewer_lamports = 0;
+        let mut reviewer_data = [];
+        let system = SYSTEM_PROGRAM_ID;
+        let reviewer_info = AccountInfo::new(&reviewer, true, false, &mut reviewer_lamports, &mut reviewer_data, &system, false, 0);
+        assert!(check_editor(&program_id, ns, &reviewer_info, &intro, &review_data, None).is_ok());
+
+        let mut editor_lamports = 0;
+        let mut editor_wallet_data = [];
+        let editor_info = AccountInfo::new(&editor, true, false, &mut editor_lamports, &mut editor_wallet_data, &system, false, 0);
+        assert_eq!(check_editor(&program_id, ns, &editor_info, &intro, &review_data, None).err(),
+            Some(ReviewError::Unauthorized.into()));
+
+        let grant_key = Pubkey::find_program_address(&[ns, intro.as_ref(), EDITOR_SEED, editor.as_ref()], &program_id).0;
+        let mut grant_lamports = 0;
+        let mut grant_data = IntroEditor {
+            discriminator: IntroEditor::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro,
+            editor,
+            granted_slot: 1,
+        }.try_to_vec().unwrap();
+        assert_eq!(grant_data.len(), IntroEditor::SIZE);
+        let grant_info = AccountInfo::new(&grant_key, false, true, &mut grant_lamports, &mut grant_data, &program_id, false, 0);
+        assert!(check_editor(&program_id, ns, &editor_info, &intro, &review_data, Some(&grant_info)).is_ok());
+
+        // A grant is for one intro only
+        let other_intro = Pubkey::new_unique();
+        assert_eq!(check_editor(&program_id, ns, &editor_info, &other_intro, &review_data, Some(&grant_info)).err(),
+            Some(ReviewError::InvalidPDA.into()));
+    }
+}
@@ -0,0 +1,478 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, Sysvar},
+};
+use crate::account_type;
+use crate::constants::USER_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::events::{self, IndexEvent};
+use crate::processor::{
+    check_account_count, check_program_account, close_account, load_config, load_global_stats,
+    load_or_create_user_record, resize_account,
+};
+use crate::state::{CommentTranslation, IntroAttributes, StudentIntroComment, StudentIntroState, UserRecord};
+use crate::{attributes, summary};
+
+// What erased text is replaced with
+pub const REDACTION_MARKER: &str = "[redacted]";
+
+// Records that the student wants their intros and comments erased. Their content can be
+// exported with ExportPage before it is; from then on ExecuteErasure redacts it.
+pub fn request_erasure(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Requesting erasure...");
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    load_config(program_id, ns, pda_config)?;
+
+    let rent = Rent::get()?;
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, student, student.key, pda_user, system_program, &rent)?;
+    if user_data.erasure_requested_slot != 0 {
+        msg!("Erasure was already requested at slot {}", user_data.erasure_requested_slot);
+        return Ok(());
+    }
+
+    let slot = Clock::get()?.slot;
+    user_data.erasure_requested_slot = slot;
+    events::emit_for(&mut user_data, &IndexEvent::ErasureRequested {
+        wallet: *student.key,
+        slot,
+    })?;
+    account_type::write(&user_data, pda_user)?;
+
+    Ok(())
+}
+
+// Replaces the content of a student's intros and comments, taken after the first six
+// accounts, with the redaction marker. The student or the admin runs it once the student
+// requested erasure, in as many batches as it takes; accounts already erased are skipped.
+// Intros are passed alone, comments as (comment, intro, summary), translations the student
+// wrote or of the student's comments as (translation, comment, author) and the attributes
+// of the student's intros as (attributes, intro, payer). Attributes lose their entries.
+// With close set comments, translations and attributes are closed as well, returning
+// their rent to whoever paid it; a comment's translations have to be closed before it.
+// An intro keeps its name, which its address is derived from; the student deletes the
+// intro to remove it. Works on a finalized course too.
+pub fn execute_erasure(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    close: bool,
+) -> ProgramResult {
+    msg!("Executing erasure...");
+
+    check_account_count(accounts, 6)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let actor = next_account_info(account_info_iter)?;
+    let student = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !actor.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    let config_data = load_config(program_id, ns, pda_config)?;
+    if actor.key != student.key && config_data.admin != *actor.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the student or the admin can erase the student's content"));
+    }
+    check_erasure_requested(program_id, ns, student.key, pda_user)?;
+
+    let rent = Rent::get()?;
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    let mut erased = 0u64;
+    while let Some(target) = account_info_iter.next() {
+        if target.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let discriminator = account_type::read::<String>(target).unwrap_or_default();
+        let closed = if discriminator == StudentIntroState::DISCRIMINATOR {
+            let mut review_data = account_type::read::<StudentIntroState>(target).unwrap();
+            if review_data.reviewer != *student.key {
+                return Err(reason!(ReviewError::Unauthorized, "Intro {} is not the student's", target.key));
+            }
+            if !redact_intro(&mut review_data) {
+                continue;
+            }
+            // Intros keep their size, so the longer original would linger past the end
+            account_type::overwrite(&review_data, target)?;
+            false
+        } else if discriminator == StudentIntroComment::DISCRIMINATOR {
+            let pda_review = next_account_info(account_info_iter)?;
+            let pda_summary = next_account_info(account_info_iter)?;
+            let mut comment_data = account_type::read::<StudentIntroComment>(target).unwrap();
+            if comment_data.commenter != *student.key || comment_data.anonymous {
+                return Err(reason!(ReviewError::Unauthorized, "Comment {} is not the student's", target.key));
+            }
+            if comment_data.review != *pda_review.key || pda_review.owner != program_id {
+                return Err(reason!(ReviewError::CommentIntroMismatch,
+                    "Comment {} does not belong to this intro", target.key));
+            }
+
+            let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+            // A closing intro closes its comments itself, so they are only redacted
+            if close && !review_data.closing {
+                if comment_data.translations != 0 {
+                    return Err(reason!(ReviewError::NotEnoughAccounts,
+                        "Comment {} has {} translations to close first", target.key, comment_data.translations));
+                }
+                close_account(target, student)?;
+                review_data.closed_comments = review_data.closed_comments
+                    .checked_add(1)
+                    .ok_or(ReviewError::ArithmeticOverflow)?;
+                account_type::write(&review_data, pda_review)?;
+                summary::touch(program_id, ns, pda_review.key, pda_summary, |summary_data| {
+                    summary_data.comment_count = summary_data.comment_count.saturating_sub(1);
+                })?;
+                stats_data.comments = stats_data.comments.saturating_sub(1);
+                true
+            } else {
+                if !redact_comment(&mut comment_data) {
+                    continue;
+                }
+                // Comments shorter than the marker grow at the actor's cost, the others
+                // shrink and refund the student
+                let new_len = StudentIntroComment::get_account_size(
+                    comment_data.comment.clone(), comment_data.link.clone());
+                let payer = if new_len > account_type::body_len(target) { actor } else { student };
+                resize_account(payer, target, system_program, new_len, &rent)?;
+                account_type::write(&comment_data, target)?;
+                false
+            }
+        } else if discriminator == CommentTranslation::DISCRIMINATOR {
+            let pda_comment = next_account_info(account_info_iter)?;
+            let author = next_account_info(account_info_iter)?;
+            match erase_translation(program_id, student, actor, target, pda_comment, author, system_program, close, &rent)? {
+                Some(closed) => closed,
+                None => continue,
+            }
+        } else if discriminator == IntroAttributes::DISCRIMINATOR {
+            let pda_review = next_account_info(account_info_iter)?;
+            let payer = next_account_info(account_info_iter)?;
+            match erase_attributes(program_id, student, target, pda_review, payer, close)? {
+                Some(closed) => closed,
+                None => continue,
+            }
+        } else {
+            return Err(reason!(ReviewError::IncorrectAccountError,
+                "{} is not an intro, comment, translation or attributes account", target.key));
+        };
+
+        events::emit(&IndexEvent::ContentErased {
+            account: *target.key,
+            wallet: *student.key,
+            erased_by: *actor.key,
+            closed,
+        })?;
+        erased += 1;
+    }
+    account_type::write(&stats_data, pda_stats)?;
+    msg!("Erased {} accounts", erased);
+
+    Ok(())
+}
+
+// Returns whether the translation was closed, or None when it was already erased. The
+// commenter's comment may be closed already, in which case only the author can erase it.
+#[allow(clippy::too_many_arguments)]
+fn erase_translation<'a>(
+    program_id: &Pubkey,
+    student: &AccountInfo<'a>,
+    actor: &AccountInfo<'a>,
+    pda_translation: &AccountInfo<'a>,
+    pda_comment: &AccountInfo<'a>,
+    author: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    close: bool,
+    rent: &Rent,
+) -> Result<Option<bool>, ProgramError> {
+    let mut translation_data = account_type::read::<CommentTranslation>(pda_translation).unwrap();
+    if translation_data.comment != *pda_comment.key || translation_data.author != *author.key {
+        return Err(reason!(ReviewError::IncorrectAccountError,
+            "Translation {} must be passed with its comment and author", pda_translation.key));
+    }
+
+    let mut comment_data = if pda_comment.owner == program_id {
+        Some(account_type::read::<StudentIntroComment>(pda_comment).unwrap())
+            .filter(|comment_data| comment_data.discriminator == StudentIntroComment::DISCRIMINATOR)
+    } else {
+        None
+    };
+    let students_comment = matches!(&comment_data,
+        Some(comment_data) if comment_data.commenter == *student.key && !comment_data.anonymous);
+    if translation_data.author != *student.key && !students_comment {
+        return Err(reason!(ReviewError::Unauthorized, "Translation {} is not the student's", pda_translation.key));
+    }
+
+    if close {
+        close_account(pda_translation, author)?;
+        if let Some(comment_data) = comment_data.as_mut() {
+            comment_data.translations = comment_data.translations.saturating_sub(1);
+            account_type::write(comment_data, pda_comment)?;
+        }
+        return Ok(Some(true));
+    }
+
+    if !redact_translation(&mut translation_data) {
+        return Ok(None);
+    }
+    let new_len = CommentTranslation::get_account_size(&translation_data.language, &translation_data.text);
+    let payer = if new_len > account_type::body_len(pda_translation) { actor } else { author };
+    resize_account(payer, pda_translation, system_program, new_len, rent)?;
+    account_type::write(&translation_data, pda_translation)?;
+    Ok(Some(false))
+}
+
+// Same for the attributes of one of the student's intros
+fn erase_attributes<'a>(
+    program_id: &Pubkey,
+    student: &AccountInfo<'a>,
+    pda_attributes: &AccountInfo<'a>,
+    pda_review: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    close: bool,
+) -> Result<Option<bool>, ProgramError> {
+    let mut attributes_data = attributes::load_attributes(program_id, pda_attributes)?;
+    if attributes_data.intro != *pda_review.key || attributes_data.payer != *payer.key || pda_review.owner != program_id {
+        return Err(reason!(ReviewError::IncorrectAccountError,
+            "Attributes {} must be passed with their intro and payer", pda_attributes.key));
+    }
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if review_data.reviewer != *student.key {
+        return Err(reason!(ReviewError::Unauthorized, "Intro {} is not the student's", pda_review.key));
+    }
+
+    if close {
+        close_account(pda_attributes, payer)?;
+        return Ok(Some(true));
+    }
+
+    if attributes_data.entries.is_empty() {
+        return Ok(None);
+    }
+    attributes_data.entries.clear();
+    // The account keeps its size, so the entries would linger past the end
+    account_type::overwrite(&attributes_data, pda_attributes)?;
+    Ok(Some(false))
+}
+
+fn check_erasure_requested(
+    program_id: &Pubkey,
+    ns: &[u8],
+    student: &Pubkey,
+    pda_user: &AccountInfo,
+) -> ProgramResult {
+    let (user_pda, _user_bump) = Pubkey::find_program_address(&[ns, USER_SEED, student.as_ref()], program_id);
+    if user_pda != *pda_user.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for user record PDA"));
+    }
+    if pda_user.owner != program_id {
+        return Err(reason!(ReviewError::ErasureNotRequested, "{} has not requested erasure", student));
+    }
+    let user_data = account_type::read::<UserRecord>(pda_user).unwrap();
+    if !user_data.is_initialized() || user_data.erasure_requested_slot == 0 {
+        return Err(reason!(ReviewError::ErasureNotRequested, "{} has not requested erasure", student));
+    }
+
+    Ok(())
+}
+
+// Both return whether there was anything left to redact. The intro's message hash stays
+// so deleting the intro still releases its content registration.
+fn redact_intro(review_data: &mut StudentIntroState) -> bool {
+    if review_data.message == REDACTION_MARKER
+        && review_data.answers.iter().all(|answer| answer == REDACTION_MARKER)
+    {
+        return false;
+    }
+
+    review_data.message = REDACTION_MARKER.to_string();
+    for answer in review_data.answers.iter_mut() {
+        *answer = REDACTION_MARKER.to_string();
+    }
+    true
+}
+
+fn redact_translation(translation_data: &mut CommentTranslation) -> bool {
+    if translation_data.text == REDACTION_MARKER {
+        return false;
+    }
+
+    translation_data.text = REDACTION_MARKER.to_string();
+    true
+}
+
+fn redact_comment(comment_data: &mut StudentIntroComment) -> bool {
+    if comment_data.comment == REDACTION_MARKER && comment_data.link.is_empty() {
+        return false;
+    }
+
+    comment_data.comment = REDACTION_MARKER.to_string();
+    comment_data.link = String::new();
+    comment_data.content_hash = [0; 32];
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use solana_program::borsh::try_from_slice_unchecked;
+    use crate::state::IntroAttribute;
+
+    #[test]
+    fn test_redaction_replaces_content_once() {
+        let mut review_data = StudentIntroState {
+            name: "Ada".to_string(),
+            message: "I live in Berlin".to_string(),
+            answers: vec!["Rust".to_string(), "Chess".to_string()],
+            ..try_from_slice_unchecked::<StudentIntroState>(&[0u8; StudentIntroState::ACCOUNT_LEN]).unwrap()
+        };
+        assert!(redact_intro(&mut review_data));
+        assert_eq!(review_data.name, "Ada");
+        assert_eq!(review_data.message, REDACTION_MARKER);
+        assert!(review_data.answers.iter().all(|answer| answer == REDACTION_MARKER));
+        assert!(!redact_intro(&mut review_data));
+
+        let size = StudentIntroComment::get_account_size("x".repeat(40), "https://example.com".to_string());
+        let mut comment_data = try_from_slice_unchecked::<StudentIntroComment>(&vec![0u8; size]).unwrap();
+        comment_data.discriminator = StudentIntroComment::DISCRIMINATOR.to_string();
+        comment_data.comment = "x".repeat(40);
+        comment_data.link = "https://example.com".to_string();
+        comment_data.content_hash = [7; 32];
+        assert!(redact_comment(&mut comment_data));
+        assert_eq!((comment_data.comment.as_str(), comment_data.link.as_str()), (REDACTION_MARKER, ""));
+        assert_eq!(comment_data.content_hash, [0; 32]);
+        assert!(!redact_comment(&mut comment_data));
+
+        let new_len = StudentIntroComment::get_account_size(comment_data.comment.clone(), comment_data.link.clone());
+        assert!(new_len < size);
+        assert_eq!(comment_data.try_to_vec().unwrap().len(), new_len);
+    }
+
+    #[test]
+    fn test_translations_and_attributes_are_erased_with_the_content() {
+        let program_id = Pubkey::new_unique();
+        let system = SYSTEM_PROGRAM_ID;
+        let (student_key, instructor_key, outsider_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (comment_key, translation_key, intro_key, attributes_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let rent = Rent::default();
+
+        let size = StudentIntroComment::get_account_size("x".repeat(40), String::new());
+        let mut comment_bytes = vec![0u8; size];
+        let mut comment_data = try_from_slice_unchecked::<StudentIntroComment>(&comment_bytes).unwrap();
+        comment_data.discriminator = StudentIntroComment::DISCRIMINATOR.to_string();
+        comment_data.commenter = student_key;
+        comment_data.comment = "x".repeat(40);
+        comment_data.translations = 2;
+        comment_data.serialize(&mut &mut comment_bytes[..]).unwrap();
+
+        // The instructor translated the student's comment, at the marker's length so
+        // redacting it keeps the account's size
+        let translation = |author: Pubkey| CommentTranslation {
+            discriminator: CommentTranslation::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            comment: comment_key,
+            author,
+            language: "es".to_string(),
+            text: "y".repeat(REDACTION_MARKER.len()),
+        };
+        let mut translation_bytes = translation(instructor_key).try_to_vec().unwrap();
+
+        let (mut student_lamports, mut instructor_lamports, mut comment_lamports, mut translation_lamports) = (1, 1, 1, 50);
+        let (mut student_bytes, mut instructor_bytes) = (vec![], vec![]);
+        let student = AccountInfo::new(&student_key, true, true, &mut student_lamports, &mut student_bytes, &system, false, 0);
+        let instructor = AccountInfo::new(
+            &instructor_key, false, true, &mut instructor_lamports, &mut instructor_bytes, &system, false, 0);
+        let pda_comment = AccountInfo::new(
+            &comment_key, false, true, &mut comment_lamports, &mut comment_bytes, &program_id, false, 0);
+        let pda_translation = AccountInfo::new(
+            &translation_key, false, true, &mut translation_lamports, &mut translation_bytes, &program_id, false, 0);
+
+        assert_eq!(
+            erase_translation(&program_id, &student, &student, &pda_translation, &pda_comment, &instructor, &instructor,
+                false, &rent).unwrap(),
+            Some(false)
+        );
+        let translation_data = account_type::read::<CommentTranslation>(&pda_translation).unwrap();
+        assert_eq!(translation_data.text, REDACTION_MARKER);
+        assert_eq!(
+            erase_translation(&program_id, &student, &student, &pda_translation, &pda_comment, &instructor, &instructor,
+                false, &rent).unwrap(),
+            None
+        );
+
+        // Closing refunds the author and frees the comment's slot, so it can close in turn
+        assert_eq!(
+            erase_translation(&program_id, &student, &student, &pda_translation, &pda_comment, &instructor, &instructor,
+                true, &rent).unwrap(),
+            Some(true)
+        );
+        assert_eq!((pda_translation.lamports(), instructor.lamports()), (0, 51));
+        let comment_data = account_type::read::<StudentIntroComment>(&pda_comment).unwrap();
+        assert_eq!(comment_data.translations, 1);
+
+        // Someone else's translation of someone else's comment is not the student's
+        let mut other_bytes = CommentTranslation { comment: Pubkey::new_unique(), ..translation(outsider_key) }
+            .try_to_vec().unwrap();
+        let (mut other_lamports, mut outsider_lamports, mut outsider_bytes) = (1, 1, vec![]);
+        let other_translation = AccountInfo::new(
+            &translation_key, false, true, &mut other_lamports, &mut other_bytes, &program_id, false, 0);
+        let outsider = AccountInfo::new(&outsider_key, false, true, &mut outsider_lamports, &mut outsider_bytes, &system, false, 0);
+        assert!(erase_translation(&program_id, &student, &student, &other_translation, &pda_comment, &outsider, &outsider,
+            false, &rent).is_err());
+
+        let mut intro_bytes = vec![0u8; StudentIntroState::ACCOUNT_LEN];
+        let mut review_data = try_from_slice_unchecked::<StudentIntroState>(&intro_bytes).unwrap();
+        review_data.reviewer = student_key;
+        review_data.serialize(&mut &mut intro_bytes[..]).unwrap();
+        let mut attributes_bytes = vec![0u8; IntroAttributes::SIZE];
+        IntroAttributes {
+            discriminator: IntroAttributes::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: intro_key,
+            payer: student_key,
+            entries: vec![IntroAttribute { key: "city".to_string(), value: "Berlin".to_string() }],
+        }.serialize(&mut &mut attributes_bytes[..]).unwrap();
+        let (mut intro_lamports, mut attributes_lamports) = (1, 1);
+        let pda_review = AccountInfo::new(&intro_key, false, true, &mut intro_lamports, &mut intro_bytes, &program_id, false, 0);
+        let pda_attributes = AccountInfo::new(
+            &attributes_key, false, true, &mut attributes_lamports, &mut attributes_bytes, &program_id, false, 0);
+
+        assert_eq!(erase_attributes(&program_id, &student, &pda_attributes, &pda_review, &student, false).unwrap(), Some(false));
+        assert!(attributes::load_attributes(&program_id, &pda_attributes).unwrap().entries.is_empty());
+        assert!(!pda_attributes.data.borrow().windows(6).any(|bytes| bytes == b"Berlin"));
+        assert_eq!(erase_attributes(&program_id, &student, &pda_attributes, &pda_review, &student, false).unwrap(), None);
+        assert_eq!(erase_attributes(&program_id, &student, &pda_attributes, &pda_review, &student, true).unwrap(), Some(true));
+    }
+}
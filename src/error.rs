@@ -1,7 +1,9 @@
 use solana_program::{program_error::ProgramError};
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+// Logged as E<nnn> codes, see error_codes. New errors take the next number and are
+// appended to error_codes::ALL too.
+#[derive(Clone, Copy, Debug, Error, PartialEq)]
 pub enum ReviewError{
     // Error 0
     #[error("Account not initialized yet")]
@@ -15,9 +17,117 @@ pub enum ReviewError{
     // Error 3
     #[error("Rating greater than 5 or less than 1")]
     InvalidRating,
-
+    // Error 4
     #[error("Accounts do not match")]
     IncorrectAccountError,
+    // Error 5
+    #[error("Student intro is being closed")]
+    IntroClosing,
+    // Error 6
+    #[error("Comment does not belong to this student intro")]
+    CommentIntroMismatch,
+    // Error 7
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+    // Error 8
+    #[error("Instruction data version is not supported")]
+    UnsupportedInstructionVersion,
+    // Error 9
+    #[error("Signer is not allowed to perform this action")]
+    Unauthorized,
+    // Error 10
+    #[error("Pending rewards were not claimed within the claim window")]
+    RewardsExpired,
+    // Error 11
+    #[error("No pending rewards to claim")]
+    NoPendingRewards,
+    // Error 12
+    #[error("Price account is not a usable Pyth price feed")]
+    InvalidPriceAccount,
+    // Error 13
+    #[error("Price feed is stale")]
+    StalePrice,
+    // Error 14
+    #[error("Price confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    // Error 15
+    #[error("Randomness account is not a valid oracle result for this raffle")]
+    InvalidRandomnessAccount,
+    // Error 16
+    #[error("Raffle is not in the right phase for this action")]
+    InvalidRafflePhase,
+    // Error 17
+    #[error("Treasury does not hold enough unreserved tokens")]
+    InsufficientTreasury,
+    // Error 18
+    #[error("Account was allocated too small for this update and must be reallocated")]
+    AccountTooSmallForUpdate,
+    // Error 19
+    #[error("Comment matches a banned phrase")]
+    BannedPhrase,
+    // Error 20
+    #[error("Comment page is full")]
+    CommentPageFull,
+
+    // Error 21
+    #[error("Accrue-and-claim rewards only support the default cohort")]
+    CohortCannotAccrue,
+
+    // Error 22
+    #[error("Comment link is too long or not https")]
+    InvalidLink,
+
+    // Error 23
+    #[error("Not enough accounts for this instruction")]
+    NotEnoughAccounts,
+
+    // Error 24
+    #[error("Stored content changed since it was read")]
+    StaleUpdate,
+
+    // Error 25
+    #[error("Course is finalized and read-only")]
+    CourseFinalized,
+
+    // Error 26
+    #[error("Not enough admins signed")]
+    AdminQuorumNotMet,
+
+    // Error 27
+    #[error("Intro content is already used by another intro")]
+    DuplicateContent,
+
+    // Error 28
+    #[error("Comment has no room for another translation")]
+    TooManyTranslations,
+
+    // Error 29
+    #[error("Comment can no longer be edited or deleted")]
+    EditWindowClosed,
+
+    // Error 30
+    #[error("Auction is not in the right phase for this action")]
+    InvalidAuctionPhase,
+
+    // Error 31
+    #[error("Bid does not beat the highest bid")]
+    BidTooLow,
+
+    // Error 32
+    #[error("Wallet already reacted to this comment")]
+    AlreadyReacted,
+
+    // Error 33
+    #[error("Change to rewards, caps or fees has to be proposed and applied after the delay")]
+    ConfigChangeTimelocked,
+
+    // Error 34
+    #[error("Wallet posted its anonymous comments for this window")]
+    AnonymousCommentLimit,
+
+    // Error 35
+    #[error("Wallet has not requested erasure")]
+    ErasureNotRequested,
 }
 
 impl From<ReviewError> for ProgramError {
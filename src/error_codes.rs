@@ -0,0 +1,88 @@
+use solana_program::{msg, program_error::ProgramError};
+use std::fmt;
+use crate::error::ReviewError;
+
+// Failed transactions log what went wrong as "E<nnn>: <message>", the code being the
+// ReviewError's discriminant, the same number the transaction fails with as a custom
+// program error. Codes are never reused or renumbered, so support tools can match on the
+// prefix in explorer logs. Failures that aren't a ReviewError, like a missing signature,
+// log their message without a code.
+pub fn code(error: ReviewError) -> String {
+    format!("E{:03}", error as u32)
+}
+
+// The error a code from the logs stands for
+pub fn from_code(code: &str) -> Option<ReviewError> {
+    let index: usize = code.strip_prefix('E')?.parse().ok()?;
+    ALL.get(index).copied()
+}
+
+// Logs the message under the error's code and returns the error, see reason!
+pub fn reason(error: ReviewError, message: fmt::Arguments) -> ProgramError {
+    msg!("{}: {}", code(error), message);
+    error.into()
+}
+
+// `return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for {} PDA", name));`
+#[macro_export]
+macro_rules! reason {
+    ($error:expr, $($arg:tt)+) => {
+        $crate::error_codes::reason($error, format_args!($($arg)+))
+    };
+}
+
+// Every ReviewError in code order
+pub const ALL: &[ReviewError] = &[
+    ReviewError::UninitializedAccount,
+    ReviewError::InvalidPDA,
+    ReviewError::InvalidDataLength,
+    ReviewError::InvalidRating,
+    ReviewError::IncorrectAccountError,
+    ReviewError::IntroClosing,
+    ReviewError::CommentIntroMismatch,
+    ReviewError::ArithmeticOverflow,
+    ReviewError::UnsupportedInstructionVersion,
+    ReviewError::Unauthorized,
+    ReviewError::RewardsExpired,
+    ReviewError::NoPendingRewards,
+    ReviewError::InvalidPriceAccount,
+    ReviewError::StalePrice,
+    ReviewError::PriceConfidenceTooWide,
+    ReviewError::InvalidRandomnessAccount,
+    ReviewError::InvalidRafflePhase,
+    ReviewError::InsufficientTreasury,
+    ReviewError::AccountTooSmallForUpdate,
+    ReviewError::BannedPhrase,
+    ReviewError::CommentPageFull,
+    ReviewError::CohortCannotAccrue,
+    ReviewError::InvalidLink,
+    ReviewError::NotEnoughAccounts,
+    ReviewError::StaleUpdate,
+    ReviewError::CourseFinalized,
+    ReviewError::AdminQuorumNotMet,
+    ReviewError::DuplicateContent,
+    ReviewError::TooManyTranslations,
+    ReviewError::EditWindowClosed,
+    ReviewError::InvalidAuctionPhase,
+    ReviewError::BidTooLow,
+    ReviewError::AlreadyReacted,
+    ReviewError::ConfigChangeTimelocked,
+    ReviewError::AnonymousCommentLimit,
+    ReviewError::ErasureNotRequested,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_follow_discriminants() {
+        for (i, error) in ALL.iter().enumerate() {
+            assert_eq!(*error as usize, i);
+            assert_eq!(from_code(&code(*error)), Some(*error));
+        }
+        assert_eq!(code(ReviewError::InvalidPDA), "E001");
+        assert_eq!(from_code("E999"), None);
+        assert_eq!(from_code("001"), None);
+    }
+}
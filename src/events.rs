@@ -0,0 +1,118 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+use crate::error::ReviewError;
+use crate::state::UserRecord;
+
+// Logged as borsh-encoded program data so indexers can follow state changes from
+// transaction logs instead of polling accounts. New events are appended to keep the
+// variant tags stable.
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+pub enum IndexEvent {
+    // The intro's indexed fields as they are now. Emitted on creation and again whenever
+    // one of them changes, so indexers can upsert on it.
+    IntroIndexed {
+        intro: Pubkey,
+        reviewer: Pubkey,
+        name: String,
+        cohort: String,
+    },
+    // Someone paid a program account's rent shortfall
+    RentToppedUp {
+        account: Pubkey,
+        payer: Pubkey,
+        lamports: u64,
+    },
+    // Totals as the course was wound down, the last event a deployment emits
+    CourseFinalized {
+        intros: u64,
+        comments: u64,
+        tokens_minted: u64,
+        active_wallets: u64,
+        rewards_pending: u64,
+        mint_authority_revoked: bool,
+        slot: u64,
+    },
+    // Normalized hash of an intro's message, on creation and whenever the message changes
+    ContentCommitted {
+        intro: Pubkey,
+        message_hash: [u8; 32],
+    },
+    // Reward tokens burned from the actor's account, with a BURN_* reason code
+    TokensBurned {
+        actor: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        reason: u8,
+    },
+    // An instruction arrived in the unversioned legacy framing, which still works but
+    // is deprecated. Lets staff see which front-ends are left to upgrade.
+    LegacyInstructionUsed {
+        variant: u8,
+    },
+    // Settings now pending at ["pending_config"], which ApplyConfigChange can make live
+    // from apply_after_slot on
+    ConfigChangeProposed {
+        proposer: Pubkey,
+        apply_after_slot: u64,
+    },
+    // The wallet asked for its intros and comments to be erased
+    ErasureRequested {
+        wallet: Pubkey,
+        slot: u64,
+    },
+    // One of the wallet's accounts had its content replaced by the redaction marker,
+    // and was closed too if asked to
+    ContentErased {
+        account: Pubkey,
+        wallet: Pubkey,
+        erased_by: Pubkey,
+        closed: bool,
+    },
+    // The reward mints' supply differs from what the global stats minted less burned
+    RewardSupplyMismatch {
+        accounted: u64,
+        supply: u64,
+        halted: bool,
+    },
+    // The group's manager paid tokens out of one of its vaults
+    GroupVaultWithdrawn {
+        group: Pubkey,
+        vault: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+    },
+}
+
+impl IndexEvent {
+    pub const BURN_DELETE_PENALTY: u8 = 0;
+    pub const BURN_AUCTION_WIN: u8 = 1;
+    pub const BURN_TREASURY_SURPLUS: u8 = 2;
+}
+
+// Logged as a second data field after an event a wallet's own action emitted. Each such
+// event takes the wallet's next action_seq, so an indexer that sees the sequence jump
+// knows how many of the wallet's events it missed and can re-sync just those. Events of
+// admin and permissionless actions have no cursor.
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+pub struct EventCursor {
+    pub wallet: Pubkey,
+    pub action_seq: u64,
+}
+
+pub fn emit(event: &IndexEvent) -> Result<(), ProgramError> {
+    sol_log_data(&[&event.try_to_vec()?]);
+    Ok(())
+}
+
+// Emits the event under the wallet's next action_seq. The caller saves the user record.
+pub fn emit_for(user_data: &mut UserRecord, event: &IndexEvent) -> Result<(), ProgramError> {
+    user_data.action_seq = user_data.action_seq
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    let cursor = EventCursor {
+        wallet: user_data.wallet,
+        action_seq: user_data.action_seq,
+    };
+    sol_log_data(&[&event.try_to_vec()?, &cursor.try_to_vec()?]);
+    Ok(())
+}
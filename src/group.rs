@@ -0,0 +1,433 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::{instruction::transfer, state::Account as TokenAccount, ID as TOKEN_PROGRAM_ID};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::{GROUP_SEED, MEMBER_SEED, TOKEN_MINT_SEED, USER_SEED};
+use crate::error::ReviewError;
+use crate::reason;
+use crate::events::{self, IndexEvent};
+use crate::processor::{check_account_count, check_program_account, close_account, load_or_create_user_record};
+use crate::state::{Group, GroupMembership, UserRecord};
+
+// Basis points a share is expressed in
+pub const MAX_SHARE_BPS: u16 = 10_000;
+
+// Opens a study group at ["group", name]. Its vault is the group PDA's associated token
+// account for each cohort mint, which anyone can create through the ATA program.
+pub fn create_group(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    name: String,
+    reward_share_bps: u16,
+) -> ProgramResult {
+    msg!("Creating study group {:?}...", name);
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let creator = next_account_info(account_info_iter)?;
+    let pda_group = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !creator.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    if name.trim().is_empty() || name.len() > Group::MAX_NAME_LEN {
+        return Err(reason!(ReviewError::InvalidDataLength, "Group name must be 1 to {} bytes", Group::MAX_NAME_LEN));
+    }
+    check_share(reward_share_bps)?;
+
+    let (group_pda, group_bump) = Pubkey::find_program_address(&[ns, GROUP_SEED, name.as_bytes()], program_id);
+    if group_pda != *pda_group.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for group PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            creator.key,
+            pda_group.key,
+            rent.minimum_balance(Group::SIZE + account_type::TAG_LEN),
+            (Group::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[creator.clone(), pda_group.clone(), system_program.clone()],
+        &[&[ns, GROUP_SEED, name.as_bytes(), &[group_bump]]],
+    )?;
+
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED], program_id);
+    let group_data = Group {
+        discriminator: Group::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        name,
+        manager: *creator.key,
+        reward_share_bps,
+        member_count: 0,
+        vault: get_associated_token_address(pda_group.key, &mint_pda),
+        comments: 0,
+        rewards_routed: 0,
+    };
+    account_type::write(&group_data, pda_group)?;
+
+    Ok(())
+}
+
+// The group's manager can lower the share routed to its vault, or hand the group on.
+// Members chose to route at the share they saw, so it can't be raised afterwards.
+pub fn update_group(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    reward_share_bps: u16,
+    manager: Pubkey,
+) -> ProgramResult {
+    msg!("Updating study group...");
+
+    check_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let signer = next_account_info(account_info_iter)?;
+    let pda_group = next_account_info(account_info_iter)?;
+
+    let mut group_data = load_group(program_id, ns, pda_group)?;
+    if !signer.is_signer || group_data.manager != *signer.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the group manager can update the group"));
+    }
+    check_share_lowered(group_data.reward_share_bps, reward_share_bps)?;
+
+    group_data.reward_share_bps = reward_share_bps;
+    if manager != Pubkey::default() {
+        group_data.manager = manager;
+    }
+    account_type::write(&group_data, pda_group)?;
+
+    msg!("Group {:?} routes {} bps of comment rewards", group_data.name, reward_share_bps);
+
+    Ok(())
+}
+
+// Makes the signer a member of the group. A wallet is in at most one group at a time and
+// leaves its current one first.
+pub fn join_group(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Joining study group...");
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let member = next_account_info(account_info_iter)?;
+    let pda_group = next_account_info(account_info_iter)?;
+    let pda_membership = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !member.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    let mut group_data = load_group(program_id, ns, pda_group)?;
+    let rent = Rent::get()?;
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, member, member.key, pda_user, system_program, &rent)?;
+    if user_data.group != Pubkey::default() {
+        msg!("{} is already in group {}", member.key, user_data.group);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (membership_pda, membership_bump) = Pubkey::find_program_address(
+        &[ns, pda_group.key.as_ref(), MEMBER_SEED, member.key.as_ref()], program_id
+    );
+    if membership_pda != *pda_membership.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for membership PDA"));
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            member.key,
+            pda_membership.key,
+            rent.minimum_balance(GroupMembership::SIZE + account_type::TAG_LEN),
+            (GroupMembership::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[member.clone(), pda_membership.clone(), system_program.clone()],
+        &[&[ns, pda_group.key.as_ref(), MEMBER_SEED, member.key.as_ref(), &[membership_bump]]],
+    )?;
+
+    let membership_data = GroupMembership {
+        discriminator: GroupMembership::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        group: *pda_group.key,
+        member: *member.key,
+        joined_slot: Clock::get()?.slot,
+    };
+    account_type::write(&membership_data, pda_membership)?;
+
+    user_data.group = *pda_group.key;
+    account_type::write(&user_data, pda_user)?;
+
+    group_data.member_count = group_data.member_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&group_data, pda_group)?;
+
+    msg!("Group {:?} has {} members", group_data.name, group_data.member_count);
+
+    Ok(())
+}
+
+// Ends the signer's membership, refunding its rent and stopping any reward routing to
+// the group
+pub fn leave_group(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Leaving study group...");
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let member = next_account_info(account_info_iter)?;
+    let pda_group = next_account_info(account_info_iter)?;
+    let pda_membership = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+
+    if !member.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut group_data = load_group(program_id, ns, pda_group)?;
+
+    if pda_membership.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let membership_data = account_type::read::<GroupMembership>(pda_membership).unwrap();
+    if !membership_data.is_initialized()
+        || membership_data.group != *pda_group.key
+        || membership_data.member != *member.key
+    {
+        return Err(reason!(ReviewError::IncorrectAccountError, "{} is not a member of this group", member.key));
+    }
+
+    let (user_pda, _user_bump) = Pubkey::find_program_address(&[ns, USER_SEED, member.key.as_ref()], program_id);
+    if user_pda != *pda_user.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for user record PDA"));
+    }
+    let mut user_data = account_type::read::<UserRecord>(pda_user).unwrap();
+    user_data.group = Pubkey::default();
+    if user_data.reward_group == *pda_group.key {
+        user_data.reward_group = Pubkey::default();
+    }
+    account_type::write(&user_data, pda_user)?;
+
+    close_account(pda_membership, member)?;
+
+    group_data.member_count = group_data.member_count.saturating_sub(1);
+    account_type::write(&group_data, pda_group)?;
+
+    msg!("Group {:?} has {} members", group_data.name, group_data.member_count);
+
+    Ok(())
+}
+
+// Routes a share of the user's comment rewards to a group's vault. The default key stops
+// routing; any other group is passed after the system program and must be the user's own.
+pub fn set_reward_group(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    group: Pubkey,
+) -> ProgramResult {
+    msg!("Setting reward group to {}...", group);
+
+    check_account_count(accounts, 3 + (group != Pubkey::default()) as usize)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let user = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !user.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    if group != Pubkey::default() {
+        let pda_group = next_account_info(account_info_iter)?;
+        if *pda_group.key != group {
+            return Err(reason!(ReviewError::IncorrectAccountError,
+                "Account passed is {}, not {}", pda_group.key, group));
+        }
+        load_group(program_id, ns, pda_group)?;
+    }
+
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, user, user.key, pda_user, system_program, &Rent::get()?)?;
+    if group != Pubkey::default() && user_data.group != group {
+        return Err(reason!(ReviewError::Unauthorized, "Rewards can only be routed to the user's own group"));
+    }
+    user_data.reward_group = group;
+    account_type::write(&user_data, pda_user)?;
+
+    Ok(())
+}
+
+// Pays tokens out of the group's vault for one mint to any token account, the group PDA
+// signing. Only the manager distributes what members routed to the group.
+pub fn withdraw_group_vault(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    msg!("Withdrawing {} from study group vault...", amount);
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let pda_group = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let group_data = load_group(program_id, ns, pda_group)?;
+    if !manager.is_signer || group_data.manager != *manager.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the group manager can withdraw from the vault"));
+    }
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    if *vault.owner != TOKEN_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let vault_data = TokenAccount::unpack(&vault.data.borrow())?;
+    if *vault.key != get_associated_token_address(pda_group.key, &vault_data.mint) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect group vault account"));
+    }
+
+    let (_group_pda, group_bump) = Pubkey::find_program_address(
+        &[ns, GROUP_SEED, group_data.name.as_bytes()], program_id
+    );
+    invoke_signed(
+        &transfer(token_program.key, vault.key, destination.key, pda_group.key, &[], amount)?,
+        &[vault.clone(), destination.clone(), pda_group.clone()],
+        &[&[ns, GROUP_SEED, group_data.name.as_bytes(), &[group_bump]]],
+    )?;
+
+    events::emit(&IndexEvent::GroupVaultWithdrawn {
+        group: *pda_group.key,
+        vault: *vault.key,
+        destination: *destination.key,
+        amount,
+    })
+}
+
+pub fn load_group(program_id: &Pubkey, ns: &[u8], pda_group: &AccountInfo) -> Result<Group, ProgramError> {
+    if pda_group.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let group_data = account_type::read::<Group>(pda_group).unwrap();
+    if !group_data.is_initialized() || group_data.discriminator != Group::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Group not initialized"));
+    }
+
+    let (group_pda, _group_bump) = Pubkey::find_program_address(
+        &[ns, GROUP_SEED, group_data.name.as_bytes()], program_id
+    );
+    if group_pda != *pda_group.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for group PDA"));
+    }
+
+    Ok(group_data)
+}
+
+// The part of a reward that goes to the group, rounded down in the member's favor
+pub fn group_share(reward: u64, reward_share_bps: u16) -> u64 {
+    (reward as u128 * reward_share_bps as u128 / MAX_SHARE_BPS as u128) as u64
+}
+
+fn check_share(reward_share_bps: u16) -> ProgramResult {
+    if reward_share_bps > MAX_SHARE_BPS {
+        msg!("Reward share is at most {} bps", MAX_SHARE_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+fn check_share_lowered(current_bps: u16, reward_share_bps: u16) -> ProgramResult {
+    if reward_share_bps > current_bps {
+        msg!("Reward share can only be lowered from {} bps", current_bps);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_group_share_rounds_down() {
+        assert_eq!(group_share(5_000_000_000, 2_500), 1_250_000_000);
+        assert_eq!(group_share(3, 5_000), 1);
+        assert_eq!(group_share(u64::MAX, MAX_SHARE_BPS), u64::MAX);
+        assert_eq!(group_share(100, 0), 0);
+        assert!(check_share(MAX_SHARE_BPS + 1).is_err());
+        // Members who joined at 10% can't be made to route more
+        assert!(check_share_lowered(1_000, 500).is_ok());
+        assert!(check_share_lowered(1_000, 1_000).is_ok());
+        assert!(check_share_lowered(1_000, MAX_SHARE_BPS).is_err());
+    }
+
+    #[test]
+    fn test_full_group_accounts_fit_their_size() {
+        let group_data = Group {
+            discriminator: Group::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            name: "x".repeat(Group::MAX_NAME_LEN),
+            manager: Pubkey::new_unique(),
+            reward_share_bps: MAX_SHARE_BPS,
+            member_count: u64::MAX,
+            vault: Pubkey::new_unique(),
+            comments: u64::MAX,
+            rewards_routed: u64::MAX,
+        };
+        assert!(group_data.try_to_vec().unwrap().len() <= Group::SIZE);
+
+        let membership_data = GroupMembership {
+            discriminator: GroupMembership::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            group: Pubkey::new_unique(),
+            member: Pubkey::new_unique(),
+            joined_slot: u64::MAX,
+        };
+        assert_eq!(membership_data.try_to_vec().unwrap().len(), GroupMembership::SIZE);
+    }
+}
@@ -0,0 +1,139 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::account_type;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::check_account_count;
+use crate::state::{
+    AccountHealth, Config, GlobalStats, IntroSummaryCache, StudentIntroComment, StudentIntroCommentCounter, StudentIntroState, UserRecord,
+};
+
+// Reports what a program account is and whether it's behind the current layout, for
+// support tooling and cranks. Read only, so it works in simulation and takes any account.
+pub fn check_account_health(
+    program_id: &Pubkey,
+    _ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Checking account health...");
+
+    check_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let target = next_account_info(account_info_iter)?;
+
+    let health = inspect(program_id, target, &Rent::get()?)?;
+    msg!("{} is a {:?} account at version {}, rent exempt: {}, needs migration: {}",
+        target.key, health.account_type, health.version, health.rent_exempt, health.needs_migration);
+    set_return_data(&health.try_to_vec()?);
+
+    Ok(())
+}
+
+pub fn inspect(program_id: &Pubkey, target: &AccountInfo, rent: &Rent) -> Result<AccountHealth, ProgramError> {
+    if target.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let data = target.data.borrow();
+    let tagged = account_type::is_tagged(&data);
+    let body = account_type::body(&data);
+    let account_type = String::deserialize(&mut &body[..]).map_err(|_|
+        reason!(ReviewError::UninitializedAccount, "{} has no account type", target.key))?;
+
+    let (version, needs_migration) = match account_type.as_str() {
+        StudentIntroState::DISCRIMINATOR => {
+            let intro_data = try_from_slice_unchecked::<StudentIntroState>(body).unwrap();
+            // Intros from before the content registry have no message hash, and ones
+            // allocated before the fixed size can't take edits that grow them
+            ((intro_data.message_hash != [0u8; 32]) as u8, body.len() < StudentIntroState::ACCOUNT_LEN)
+        }
+        StudentIntroComment::DISCRIMINATOR => {
+            let comment_data = try_from_slice_unchecked::<StudentIntroComment>(body).unwrap();
+            let version = comment_data.layout_version(body.len());
+            (version, version < StudentIntroComment::LAYOUT_VERSION)
+        }
+        StudentIntroCommentCounter::DISCRIMINATOR => {
+            let counter_data = try_from_slice_unchecked::<StudentIntroCommentCounter>(body).unwrap();
+            (counter_data.seed_version, counter_data.seed_version < StudentIntroCommentCounter::CURRENT_SEED_VERSION)
+        }
+        // Accounts with headroom only fall behind if allocated smaller than they are now
+        UserRecord::DISCRIMINATOR => (0, body.len() < UserRecord::SIZE),
+        Config::DISCRIMINATOR => (0, body.len() < Config::SIZE),
+        GlobalStats::DISCRIMINATOR => (0, body.len() < GlobalStats::SIZE),
+        IntroSummaryCache::DISCRIMINATOR => (0, body.len() < IntroSummaryCache::SIZE),
+        _ => (0, false),
+    };
+
+    let tag = account_type::tag_of(&account_type);
+    Ok(AccountHealth {
+        tag,
+        account_type,
+        version,
+        rent_exempt: rent.is_exempt(target.lamports(), data.len()),
+        rent_shortfall: rent.minimum_balance(data.len()).saturating_sub(target.lamports()),
+        // Accounts from before the tag byte still have to be shifted behind it
+        needs_migration: needs_migration || (!tagged && tag != account_type::UNKNOWN),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_counter_needs_migration() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut data = (StudentIntroCommentCounter::DISCRIMINATOR.to_string(), true, 3u64).try_to_vec().unwrap();
+        let rent = Rent::default();
+        let mut lamports = rent.minimum_balance(data.len());
+        let target = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &program_id, false, 0);
+
+        let health = inspect(&program_id, &target, &rent).unwrap();
+        assert_eq!(health.account_type, StudentIntroCommentCounter::DISCRIMINATOR);
+        assert_eq!(health.tag, account_type::COMMENT_COUNTER);
+        assert_eq!(health.version, StudentIntroCommentCounter::SEED_VERSION_BIG_ENDIAN);
+        assert!(health.rent_exempt);
+        assert!(health.needs_migration);
+
+        assert_eq!(inspect(&Pubkey::new_unique(), &target, &rent).err(), Some(ProgramError::IllegalOwner));
+    }
+
+    #[test]
+    fn test_comment_layout_versions() {
+        let comment_data = StudentIntroComment {
+            discriminator: StudentIntroComment::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            review: Pubkey::new_unique(),
+            commenter: Pubkey::new_unique(),
+            comment: "Nice intro".to_string(),
+            count: 0,
+            link: String::new(),
+            content_hash: [0u8; 32],
+            acknowledged: false,
+            translations: 0,
+            created_slot: 0,
+            visibility: StudentIntroComment::VISIBILITY_PUBLIC,
+            reactions: 0,
+            reaction_rewarded: false,
+            anonymous: false,
+        };
+        let full_len = comment_data.try_to_vec().unwrap().len();
+        assert_eq!(comment_data.layout_version(full_len), StudentIntroComment::LAYOUT_VERSION);
+        assert_eq!(comment_data.layout_version(full_len - 1), 6);
+        assert_eq!(comment_data.layout_version(full_len - 1 - 5), 5);
+        assert_eq!(comment_data.layout_version(full_len - 1 - 5 - 1), 4);
+        assert_eq!(comment_data.layout_version(full_len - 1 - 5 - 1 - 8), 3);
+        assert_eq!(comment_data.layout_version(full_len - 1 - 5 - 1 - 8 - 1 - 1 - 36), 0);
+    }
+}
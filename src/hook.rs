@@ -0,0 +1,86 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    pubkey::Pubkey,
+};
+use crate::error::ReviewError;
+use crate::reason;
+use crate::state::Config;
+
+// Instruction data the registered hook program is called with. Hook programs can depend
+// on this crate for the layout; new events are appended to keep the variant tags stable.
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+pub enum HookEvent {
+    // Accounts: [intro, reviewer], both read-only and neither signing
+    IntroCreated {
+        intro: Pubkey,
+        reviewer: Pubkey,
+    },
+}
+
+// Calls the config's intro_hook_program once an intro is created, passed as the next
+// trailing account. It runs in the same transaction, so a hook that fails fails the
+// intro with it. Without a registered hook there is nothing to pass or call.
+pub fn notify_intro_created<'a>(
+    config_data: &Config,
+    hook_program: Option<&AccountInfo<'a>>,
+    pda_intro: &AccountInfo<'a>,
+    reviewer: &AccountInfo<'a>,
+) -> ProgramResult {
+    if config_data.intro_hook_program == Pubkey::default() {
+        return Ok(());
+    }
+
+    let hook_program = match hook_program {
+        Some(hook_program) if *hook_program.key == config_data.intro_hook_program && hook_program.executable =>
+            hook_program,
+        _ => {
+            return Err(reason!(ReviewError::IncorrectAccountError,
+                "Pass the intro hook program {}", config_data.intro_hook_program));
+        }
+    };
+
+    let event = HookEvent::IntroCreated {
+        intro: *pda_intro.key,
+        reviewer: *reviewer.key,
+    };
+    invoke(
+        &Instruction {
+            program_id: *hook_program.key,
+            accounts: vec![
+                AccountMeta::new_readonly(*pda_intro.key, false),
+                AccountMeta::new_readonly(*reviewer.key, false),
+            ],
+            data: event.try_to_vec()?,
+        },
+        &[pda_intro.clone(), reviewer.clone(), hook_program.clone()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::borsh::try_from_slice_unchecked;
+
+    #[test]
+    fn test_registered_hook_must_be_passed() {
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        let owner = Pubkey::new_unique();
+        let (intro, reviewer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut intro_lamports, mut reviewer_lamports) = (0, 0);
+        let (mut intro_data, mut reviewer_data) = ([0u8; 0], [0u8; 0]);
+        let pda_intro = AccountInfo::new(&intro, false, false, &mut intro_lamports, &mut intro_data, &owner, false, 0);
+        let reviewer_info = AccountInfo::new(&reviewer, true, false, &mut reviewer_lamports, &mut reviewer_data, &owner, false, 0);
+
+        assert!(notify_intro_created(&config_data, None, &pda_intro, &reviewer_info).is_ok());
+
+        config_data.intro_hook_program = Pubkey::new_unique();
+        assert_eq!(notify_intro_created(&config_data, None, &pda_intro, &reviewer_info).err(),
+            Some(ReviewError::IncorrectAccountError.into()));
+        assert_eq!(notify_intro_created(&config_data, Some(&reviewer_info), &pda_intro, &reviewer_info).err(),
+            Some(ReviewError::IncorrectAccountError.into()));
+    }
+}
@@ -1,15 +1,205 @@
-use borsh::BorshDeserialize;
-use solana_program::program_error::ProgramError;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{msg, program_error::ProgramError, pubkey::Pubkey};
+use crate::error::ReviewError;
+use crate::reason;
+
+// Set on the first byte of versioned instruction data; the low bits carry the version
+pub const VERSION_FLAG: u8 = 0x80;
+pub const CURRENT_VERSION: u8 = 1;
+// A namespace is a single PDA seed with its length in front, so it is capped one byte
+// below the seed length limit
+pub const MAX_NAMESPACE_LEN: usize = 31;
 
 pub enum StudentIntroInstruction {
-    AddStudentIntro { name: String, message: String },
-    UpdateStudentIntro { name: String, message: String },
-    AddComment { comment: String },
+    AddStudentIntro { name: String, message: String, dry_run: bool },
+    UpdateStudentIntro { name: String, message: String, expected_hash: Option<[u8; 32]>, dry_run: bool },
+    AddComment { comment: String, link: String, content_hash: [u8; 32], visibility: u8 },
     InitializeMint,
+    DeleteStudentIntro,
+    InitializeGlobalStats,
+    ExportPage { cursor: u64 },
+    InitializeConfig { params: ConfigParams },
+    UpdateConfig { params: ConfigParams },
+    TransferReward { to: Pubkey, amount: u64 },
+    InitializeTreasury,
+    ClaimRewards,
+    ExpireRewards,
+    CreateRaffle { id: u64, start_slot: u64, end_slot: u64, prize: u64 },
+    CommitRaffleRandomness,
+    DrawRaffle,
+    CleanupIntro,
+    InitializeThread { task: u8, interval_slots: u64 },
+    IncrementViews { count: u64 },
+    InitializeBannedPhrases,
+    UpdateBannedPhrases { add: Vec<[u8; 32]>, remove: Vec<[u8; 32]> },
+    InitializeCohort { id: String, intro_reward: u64, comment_reward: u64 },
+    ReassignCohort,
+    ResyncCounter,
+    AcknowledgeComment,
+    PublishTemplate { id: u64, prompts: Vec<String>, tags: Vec<String> },
+    CloneIntroTemplate { name: String, message: String, answers: Vec<String> },
+    AddKeyedComment { comment: String, link: String, content_hash: [u8; 32], visibility: u8 },
+    UpdateComment { comment: String, link: String, content_hash: [u8; 32], expected_hash: Option<[u8; 32]> },
+    TopUpRent { account: Pubkey },
+    FinalizeCourse { revoke_mint_authority: bool },
+    SetRewardDestination { destination: Pubkey },
+    SetAdmins { co_admins: Vec<Pubkey>, threshold: u8 },
+    CheckDuplicateContent { message: String },
+    MigrateCommentSeeds,
+    IndexIntroTags,
+    AddTranslation { language: String, text: String },
+    DeleteComment,
+    CheckAccountHealth,
+    CreateAuction { id: u64, start_slot: u64, end_slot: u64, feature_end_slot: u64 },
+    PlaceBid { amount: u64 },
+    SettleAuction,
+    ClaimBidRefund,
+    CreateGroup { name: String, reward_share_bps: u16 },
+    UpdateGroup { reward_share_bps: u16, manager: Pubkey },
+    SetRewardGroup { group: Pubkey },
+    JoinGroup,
+    LeaveGroup,
+    Bootstrap { params: ConfigParams },
+    BurnTreasurySurplus { amount: u64 },
+    ReactToComment,
+    SyncIntroSummary,
+    ProposeConfigChange { params: ConfigParams },
+    ApplyConfigChange,
+    MigrateBatch,
+    FundMigrationBounty { lamports: u64 },
+    GrantEditor { editor: Pubkey },
+    RevokeEditor { editor: Pubkey },
+    AddAnonymousComment { comment: String, link: String, content_hash: [u8; 32], commitment: [u8; 32] },
+    SetRewardsHalted { halted: bool },
+    SetAttribute { key: String, value: String },
+    RequestErasure,
+    ExecuteErasure { close: bool },
+    RecordMentions { mentions: Vec<Pubkey> },
+    ReconcileRewards { cohorts: Vec<String> },
+    WithdrawGroupVault { amount: u64 },
+    CloseCommentIndex,
+}
+
+// Admin-tunable settings, written to the config account as a whole
+#[derive(BorshDeserialize, BorshSerialize, Clone, Default)]
+pub struct ConfigParams {
+    pub delete_penalty_window_slots: u64,
+    pub unique_commenter_bonus: u64,
+    pub accrue_rewards: bool,
+    pub claim_window_slots: u64,
+    pub creation_fee_lamports: u64,
+    pub fee_receiver: Pubkey,
+    pub price_feed: Pubkey,
+    pub creation_fee_usd_cents: u64,
+    pub max_price_staleness_slots: u64,
+    pub max_price_confidence_bps: u64,
+    pub randomness_program: Pubkey,
+    pub indexer: Pubkey,
+    pub min_reward_comment_chars: u64,
+    pub acknowledge_reward: u64,
+    pub edit_window_slots: u64,
+    pub reaction_reward_threshold: u16,
+    pub reaction_reward: u64,
+    pub bonus_supply_cap: u64,
+    pub config_change_delay_slots: u64,
+    pub migration_bounty_lamports: u64,
+    pub anonymous_window_slots: u64,
+    pub anonymous_comments_per_window: u16,
+    pub intro_hook_program: Pubkey,
+    pub halt_on_supply_mismatch: bool,
 }
 
 impl StudentIntroInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::unpack_namespaced(input).map(|(_namespace, instruction)| instruction)
+    }
+
+    // Versioned data is [VERSION_FLAG | version, variant, payload..], and from v1 on
+    // [VERSION_FLAG | 1, namespace_len, namespace.., variant, payload..]. Data without the
+    // flag comes from clients that predate versioning and is upgraded to the envelope.
+    // Anything before v1 targets the empty namespace, whose PDAs are the same as before
+    // namespaces existed.
+    pub fn unpack_namespaced(input: &[u8]) -> Result<(Vec<u8>, Self), ProgramError> {
+        let (&first, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        if first & VERSION_FLAG == 0 {
+            return Self::unpack_namespaced(&Self::upgrade_legacy(input)?);
+        }
+
+        match first & !VERSION_FLAG {
+            0 => Ok((Vec::new(), Self::unpack_v0(rest)?)),
+            1 => {
+                let (&namespace_len, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                let namespace_len = namespace_len as usize;
+                if namespace_len > MAX_NAMESPACE_LEN || rest.len() < namespace_len {
+                    msg!("Invalid namespace length {}", namespace_len);
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let (namespace, rest) = rest.split_at(namespace_len);
+                Ok((namespace.to_vec(), Self::unpack_v0(rest)?))
+            }
+            version => {
+                Err(reason!(ReviewError::UnsupportedInstructionVersion, "Unsupported instruction version {}", version))
+            }
+        }
+    }
+
+    // The variant a transaction sent with the unversioned legacy framing, [variant,
+    // payload..], if it used that framing. Legacy tags only reach up to 0x7f, so variants
+    // from 0x80 on can only be sent in the envelope.
+    pub fn legacy_variant(input: &[u8]) -> Option<u8> {
+        match input.first() {
+            Some(&variant) if variant & VERSION_FLAG == 0 => Some(variant),
+            _ => None,
+        }
+    }
+
+    // Rewrites legacy data as the current envelope for the empty namespace, which is what
+    // front-ends from before versioning always targeted
+    pub fn upgrade_legacy(input: &[u8]) -> Result<Vec<u8>, ProgramError> {
+        let variant = Self::legacy_variant(input).ok_or(ProgramError::InvalidInstructionData)?;
+        Self::pack_namespaced(&[], variant, &input[1..])
+    }
+
+    // Frames a variant's borsh payload the way unpack_namespaced reads it back, for
+    // clients and programs that build instructions for this one
+    pub fn pack_namespaced(namespace: &[u8], variant: u8, payload: &[u8]) -> Result<Vec<u8>, ProgramError> {
+        if namespace.len() > MAX_NAMESPACE_LEN {
+            msg!("Invalid namespace length {}", namespace.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut data = Vec::with_capacity(3 + namespace.len() + payload.len());
+        data.push(VERSION_FLAG | CURRENT_VERSION);
+        data.push(namespace.len() as u8);
+        data.extend_from_slice(namespace);
+        data.push(variant);
+        data.extend_from_slice(payload);
+
+        Ok(data)
+    }
+
+    // The seed PDAs of a namespace start with. Seeds are hashed back to back, so a bare
+    // namespace followed by the next seed could hash the same as a longer namespace; the
+    // leading length byte keeps every namespace's PDAs apart. The empty namespace seeds
+    // nothing, which keeps the original deployment's PDAs.
+    pub fn namespace_seed(namespace: &[u8]) -> Vec<u8> {
+        match namespace {
+            [] => Vec::new(),
+            _ => [&[namespace.len() as u8][..], namespace].concat(),
+        }
+    }
+
+    // The namespace a seed from namespace_seed was made from
+    pub fn seed_namespace(seed: &[u8]) -> &[u8] {
+        seed.get(1..).unwrap_or_default()
+    }
+
+    fn unpack_v0(input: &[u8]) -> Result<Self, ProgramError> {
         let (&variant, rest) = input
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
@@ -17,33 +207,299 @@ impl StudentIntroInstruction {
         Ok(match variant {
             0 => 
             {
-                let payload = StudentIntroPayload::try_from_slice(rest).unwrap();
+                let mut rest = rest;
+                let payload = StudentIntroPayload::deserialize(&mut rest).map_err(invalid_payload)?;
                 Self::AddStudentIntro {
                 name: payload.name,
                 message: payload.message,
+                dry_run: unpack_dry_run(rest)?,
                 }
             },
             1 =>
             {
-                let payload = StudentIntroPayload::try_from_slice(rest).unwrap();
+                let mut rest = rest;
+                let payload = StudentIntroPayload::deserialize(&mut rest).map_err(invalid_payload)?;
+                // The concurrency guard came later, so data that ends after the message has none.
+                // Data that goes on always carries the guard's Option tag before the dry-run
+                // byte, so a dry run without a guard is [payload, 0, 1].
+                let expected_hash = if rest.is_empty() {
+                    None
+                } else {
+                    Option::<[u8; 32]>::deserialize(&mut rest).map_err(invalid_payload)?
+                };
                 Self::UpdateStudentIntro {
                 name:payload.name,
                 message:payload.message,
+                expected_hash,
+                dry_run: unpack_dry_run(rest)?,
                 }
             },
             2 => 
             {
-                let payload = StudentIntroCommentPayload::try_from_slice(rest).unwrap(); 
+                let mut rest = rest;
+                let payload = StudentIntroCommentPayload::deserialize(&mut rest).map_err(invalid_payload)?;
+                // Attachments came later, so data that ends after the comment has none, and
+                // visibility later still
+                let attachment = if rest.is_empty() {
+                    CommentAttachmentPayload::default()
+                } else {
+                    CommentAttachmentPayload::deserialize(&mut rest).map_err(invalid_payload)?
+                };
                 Self::AddComment {
                     comment: payload.comment,
+                    link: attachment.link,
+                    content_hash: attachment.content_hash,
+                    visibility: unpack_visibility(rest)?,
                 }  
             },
             3 => Self::InitializeMint,
+            4 => Self::DeleteStudentIntro,
+            5 => Self::InitializeGlobalStats,
+            6 => {
+                let payload = ExportPagePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::ExportPage {
+                    cursor: payload.cursor,
+                }
+            },
+            7 => Self::InitializeConfig {
+                params: ConfigParams::try_from_slice(rest).map_err(invalid_payload)?,
+            },
+            8 => Self::UpdateConfig {
+                params: ConfigParams::try_from_slice(rest).map_err(invalid_payload)?,
+            },
+            9 => {
+                let payload = TransferRewardPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::TransferReward {
+                    to: payload.to,
+                    amount: payload.amount,
+                }
+            },
+            10 => Self::InitializeTreasury,
+            11 => Self::ClaimRewards,
+            12 => Self::ExpireRewards,
+            13 => {
+                let payload = CreateRafflePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::CreateRaffle {
+                    id: payload.id,
+                    start_slot: payload.start_slot,
+                    end_slot: payload.end_slot,
+                    prize: payload.prize,
+                }
+            },
+            14 => Self::CommitRaffleRandomness,
+            15 => Self::DrawRaffle,
+            16 => Self::CleanupIntro,
+            17 => {
+                let payload = InitializeThreadPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::InitializeThread {
+                    task: payload.task,
+                    interval_slots: payload.interval_slots,
+                }
+            },
+            18 => {
+                let payload = IncrementViewsPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::IncrementViews { count: payload.count }
+            },
+            19 => Self::InitializeBannedPhrases,
+            20 => {
+                let payload = UpdateBannedPhrasesPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::UpdateBannedPhrases {
+                    add: payload.add,
+                    remove: payload.remove,
+                }
+            },
+            21 => {
+                let payload = InitializeCohortPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::InitializeCohort {
+                    id: payload.id,
+                    intro_reward: payload.intro_reward,
+                    comment_reward: payload.comment_reward,
+                }
+            },
+            22 => Self::ReassignCohort,
+            23 => Self::ResyncCounter,
+            24 => Self::AcknowledgeComment,
+            25 => {
+                let payload = PublishTemplatePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::PublishTemplate {
+                    id: payload.id,
+                    prompts: payload.prompts,
+                    tags: payload.tags,
+                }
+            },
+            26 => {
+                let payload = CloneIntroTemplatePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::CloneIntroTemplate {
+                    name: payload.name,
+                    message: payload.message,
+                    answers: payload.answers,
+                }
+            },
+            27 => {
+                let mut rest = rest;
+                let payload = KeyedCommentPayload::deserialize(&mut rest).map_err(invalid_payload)?;
+                Self::AddKeyedComment {
+                    comment: payload.comment,
+                    link: payload.link,
+                    content_hash: payload.content_hash,
+                    visibility: unpack_visibility(rest)?,
+                }
+            },
+            28 => {
+                let payload = UpdateCommentPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::UpdateComment {
+                    comment: payload.comment,
+                    link: payload.link,
+                    content_hash: payload.content_hash,
+                    expected_hash: payload.expected_hash,
+                }
+            },
+            29 => {
+                let payload = TopUpRentPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::TopUpRent { account: payload.account }
+            },
+            30 => {
+                let payload = FinalizeCoursePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::FinalizeCourse { revoke_mint_authority: payload.revoke_mint_authority }
+            },
+            31 => {
+                let payload = SetRewardDestinationPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::SetRewardDestination { destination: payload.destination }
+            },
+            32 => {
+                let payload = SetAdminsPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::SetAdmins { co_admins: payload.co_admins, threshold: payload.threshold }
+            },
+            33 => {
+                let payload = CheckDuplicateContentPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::CheckDuplicateContent { message: payload.message }
+            },
+            34 => Self::MigrateCommentSeeds,
+            35 => Self::IndexIntroTags,
+            36 => {
+                let payload = AddTranslationPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::AddTranslation { language: payload.language, text: payload.text }
+            },
+            37 => Self::DeleteComment,
+            38 => Self::CheckAccountHealth,
+            39 => {
+                let payload = CreateAuctionPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::CreateAuction {
+                    id: payload.id,
+                    start_slot: payload.start_slot,
+                    end_slot: payload.end_slot,
+                    feature_end_slot: payload.feature_end_slot,
+                }
+            },
+            40 => {
+                let payload = PlaceBidPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::PlaceBid { amount: payload.amount }
+            },
+            41 => Self::SettleAuction,
+            42 => Self::ClaimBidRefund,
+            43 => {
+                let payload = CreateGroupPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::CreateGroup { name: payload.name, reward_share_bps: payload.reward_share_bps }
+            },
+            44 => {
+                let payload = UpdateGroupPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::UpdateGroup { reward_share_bps: payload.reward_share_bps, manager: payload.manager }
+            },
+            45 => {
+                let payload = SetRewardGroupPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::SetRewardGroup { group: payload.group }
+            },
+            46 => Self::JoinGroup,
+            47 => Self::LeaveGroup,
+            48 => Self::Bootstrap {
+                params: ConfigParams::try_from_slice(rest).map_err(invalid_payload)?,
+            },
+            49 => {
+                let payload = BurnTreasurySurplusPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::BurnTreasurySurplus { amount: payload.amount }
+            },
+            50 => Self::ReactToComment,
+            51 => Self::SyncIntroSummary,
+            52 => Self::ProposeConfigChange {
+                params: ConfigParams::try_from_slice(rest).map_err(invalid_payload)?,
+            },
+            53 => Self::ApplyConfigChange,
+            54 => Self::MigrateBatch,
+            55 => {
+                let payload = FundMigrationBountyPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::FundMigrationBounty { lamports: payload.lamports }
+            },
+            56 => {
+                let payload = EditorPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::GrantEditor { editor: payload.editor }
+            },
+            57 => {
+                let payload = EditorPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::RevokeEditor { editor: payload.editor }
+            },
+            58 => {
+                let payload = AnonymousCommentPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::AddAnonymousComment {
+                    comment: payload.comment,
+                    link: payload.link,
+                    content_hash: payload.content_hash,
+                    commitment: payload.commitment,
+                }
+            },
+            59 => {
+                let payload = SetRewardsHaltedPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::SetRewardsHalted { halted: payload.halted }
+            },
+            60 => {
+                let payload = SetAttributePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::SetAttribute { key: payload.key, value: payload.value }
+            },
+            61 => Self::RequestErasure,
+            62 => {
+                let payload = ExecuteErasurePayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::ExecuteErasure { close: payload.close }
+            },
+            63 => {
+                let payload = RecordMentionsPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::RecordMentions { mentions: payload.mentions }
+            },
+            64 => {
+                let payload = ReconcileRewardsPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::ReconcileRewards { cohorts: payload.cohorts }
+            },
+            65 => {
+                let payload = WithdrawGroupVaultPayload::try_from_slice(rest).map_err(invalid_payload)?;
+                Self::WithdrawGroupVault { amount: payload.amount }
+            },
+            66 => Self::CloseCommentIndex,
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
 }
 
+// Truncated or mis-framed payloads are rejected like any other malformed data
+fn invalid_payload(_error: std::io::Error) -> ProgramError {
+    ProgramError::InvalidInstructionData
+}
+
+// The dry-run flag trails the payload and is left off by clients that don't simulate
+fn unpack_dry_run(rest: &[u8]) -> Result<bool, ProgramError> {
+    match rest {
+        [] | [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+// Comments without the trailing visibility byte are public
+fn unpack_visibility(rest: &[u8]) -> Result<u8, ProgramError> {
+    match rest {
+        [] => Ok(0),
+        [visibility] => Ok(*visibility),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
 #[derive(BorshDeserialize)]
 struct StudentIntroPayload {
     name: String,
@@ -54,3 +510,333 @@ struct StudentIntroPayload {
 struct StudentIntroCommentPayload {
     comment: String,
 }
+
+#[derive(BorshDeserialize)]
+struct KeyedCommentPayload {
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct AnonymousCommentPayload {
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    commitment: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct UpdateCommentPayload {
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    expected_hash: Option<[u8; 32]>,
+}
+
+#[derive(BorshDeserialize)]
+struct TopUpRentPayload {
+    account: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct BurnTreasurySurplusPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct FundMigrationBountyPayload {
+    lamports: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct EditorPayload {
+    editor: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct SetRewardsHaltedPayload {
+    halted: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetAttributePayload {
+    key: String,
+    value: String,
+}
+
+#[derive(BorshDeserialize)]
+struct ExecuteErasurePayload {
+    close: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct RecordMentionsPayload {
+    mentions: Vec<Pubkey>,
+}
+
+#[derive(BorshDeserialize)]
+struct ReconcileRewardsPayload {
+    cohorts: Vec<String>,
+}
+
+#[derive(BorshDeserialize)]
+struct WithdrawGroupVaultPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct FinalizeCoursePayload {
+    revoke_mint_authority: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetRewardDestinationPayload {
+    destination: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct SetAdminsPayload {
+    co_admins: Vec<Pubkey>,
+    threshold: u8,
+}
+
+#[derive(BorshDeserialize)]
+struct CheckDuplicateContentPayload {
+    message: String,
+}
+
+#[derive(BorshDeserialize)]
+struct AddTranslationPayload {
+    language: String,
+    text: String,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateAuctionPayload {
+    id: u64,
+    start_slot: u64,
+    end_slot: u64,
+    feature_end_slot: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct PlaceBidPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateGroupPayload {
+    name: String,
+    reward_share_bps: u16,
+}
+
+#[derive(BorshDeserialize)]
+struct UpdateGroupPayload {
+    reward_share_bps: u16,
+    manager: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct SetRewardGroupPayload {
+    group: Pubkey,
+}
+
+#[derive(BorshDeserialize, Default)]
+struct CommentAttachmentPayload {
+    link: String,
+    content_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct ExportPagePayload {
+    cursor: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct TransferRewardPayload {
+    to: Pubkey,
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateRafflePayload {
+    id: u64,
+    start_slot: u64,
+    end_slot: u64,
+    prize: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeThreadPayload {
+    task: u8,
+    interval_slots: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct IncrementViewsPayload {
+    count: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct UpdateBannedPhrasesPayload {
+    add: Vec<[u8; 32]>,
+    remove: Vec<[u8; 32]>,
+}
+
+#[derive(BorshDeserialize)]
+struct PublishTemplatePayload {
+    id: u64,
+    prompts: Vec<String>,
+    tags: Vec<String>,
+}
+
+#[derive(BorshDeserialize)]
+struct CloneIntroTemplatePayload {
+    name: String,
+    message: String,
+    answers: Vec<String>,
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeCohortPayload {
+    id: String,
+    intro_reward: u64,
+    comment_reward: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_accepts_unversioned_and_v0_data() {
+        let cursor = 7u64.to_le_bytes();
+
+        let legacy = [&[6u8][..], &cursor].concat();
+        let versioned = [&[VERSION_FLAG, 6u8][..], &cursor].concat();
+
+        for data in [legacy, versioned] {
+            assert!(matches!(
+                StudentIntroInstruction::unpack(&data),
+                Ok(StudentIntroInstruction::ExportPage { cursor: 7 })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_legacy_tags_map_to_the_empty_namespace_envelope() {
+        let cursor = 7u64.to_le_bytes();
+        let legacy = [&[6u8][..], &cursor].concat();
+
+        assert_eq!(StudentIntroInstruction::legacy_variant(&legacy), Some(6));
+        assert_eq!(
+            StudentIntroInstruction::upgrade_legacy(&legacy).unwrap(),
+            StudentIntroInstruction::pack_namespaced(&[], 6, &cursor).unwrap()
+        );
+
+        let versioned = StudentIntroInstruction::pack_namespaced(b"cs50", 6, &cursor).unwrap();
+        assert_eq!(StudentIntroInstruction::legacy_variant(&versioned), None);
+        assert!(StudentIntroInstruction::upgrade_legacy(&versioned).is_err());
+        assert_eq!(StudentIntroInstruction::legacy_variant(&[]), None);
+    }
+
+    #[test]
+    fn test_unpack_reads_trailing_dry_run_flag() {
+        let payload = ("Ada".to_string(), "Hi".to_string()).try_to_vec().unwrap();
+
+        let data = [&[0u8][..], &payload].concat();
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&data),
+            Ok(StudentIntroInstruction::AddStudentIntro { dry_run: false, .. })
+        ));
+        let data = [&[0u8][..], &payload, &[1]].concat();
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&data),
+            Ok(StudentIntroInstruction::AddStudentIntro { dry_run: true, .. })
+        ));
+        let data = [&[1u8][..], &payload, &Some([7u8; 32]).try_to_vec().unwrap(), &[1]].concat();
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&data),
+            Ok(StudentIntroInstruction::UpdateStudentIntro { expected_hash: Some(_), dry_run: true, .. })
+        ));
+        let data = [&[0u8][..], &payload, &[2]].concat();
+        assert!(StudentIntroInstruction::unpack(&data).is_err());
+
+        // A dry run without a guard writes the None tag first
+        let data = [&[1u8][..], &payload, &[0, 1]].concat();
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&data),
+            Ok(StudentIntroInstruction::UpdateStudentIntro { expected_hash: None, dry_run: true, .. })
+        ));
+        let data = [&[1u8][..], &payload, &[0]].concat();
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&data),
+            Ok(StudentIntroInstruction::UpdateStudentIntro { expected_hash: None, dry_run: false, .. })
+        ));
+        // A lone dry-run byte reads as a guard with its hash missing, and is rejected
+        let data = [&[1u8][..], &payload, &[1]].concat();
+        assert!(matches!(StudentIntroInstruction::unpack(&data), Err(ProgramError::InvalidInstructionData)));
+    }
+
+    #[test]
+    fn test_unpack_rejects_unknown_version() {
+        let data = [VERSION_FLAG | 2, 3];
+        assert!(matches!(
+            StudentIntroInstruction::unpack(&data),
+            Err(ProgramError::Custom(code)) if code == ReviewError::UnsupportedInstructionVersion as u32
+        ));
+    }
+
+    #[test]
+    fn test_unpack_rejects_truncated_payloads() {
+        // An ExportPage cursor cut short, then an intro whose message length runs past the data
+        let truncated_cursor = StudentIntroInstruction::pack_namespaced(b"", 6, &[7u8; 3]).unwrap();
+        let truncated_intro = StudentIntroInstruction::pack_namespaced(b"", 0, &[2, 0, 0, 0, b'h']).unwrap();
+        for data in [truncated_cursor, truncated_intro] {
+            assert!(matches!(
+                StudentIntroInstruction::unpack(&data),
+                Err(ProgramError::InvalidInstructionData)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unpack_reads_namespace() {
+        let cursor = 7u64.to_le_bytes();
+        let data = [&[VERSION_FLAG | CURRENT_VERSION, 4][..], b"cs50", &[6u8], &cursor].concat();
+
+        let (namespace, instruction) = StudentIntroInstruction::unpack_namespaced(&data).unwrap();
+        assert_eq!(namespace, b"cs50");
+        assert!(matches!(instruction, StudentIntroInstruction::ExportPage { cursor: 7 }));
+
+        let too_long = [&[VERSION_FLAG | CURRENT_VERSION, 33][..], &[0u8; 40]].concat();
+        assert!(StudentIntroInstruction::unpack_namespaced(&too_long).is_err());
+    }
+
+    #[test]
+    fn test_pack_round_trips_through_unpack() {
+        let data = StudentIntroInstruction::pack_namespaced(b"cs50", 6, &7u64.to_le_bytes()).unwrap();
+
+        let (namespace, instruction) = StudentIntroInstruction::unpack_namespaced(&data).unwrap();
+        assert_eq!(namespace, b"cs50");
+        assert!(matches!(instruction, StudentIntroInstruction::ExportPage { cursor: 7 }));
+        assert!(StudentIntroInstruction::pack_namespaced(&[0u8; 33], 6, &[]).is_err());
+    }
+
+    #[test]
+    fn test_namespace_seeds_are_length_delimited() {
+        // The legacy deployment keeps its PDAs
+        assert!(StudentIntroInstruction::namespace_seed(b"").is_empty());
+
+        // "cs5" followed by a seed starting with "0" no longer hashes like "cs50"
+        let short = [StudentIntroInstruction::namespace_seed(b"cs5"), b"0config".to_vec()].concat();
+        let long = [StudentIntroInstruction::namespace_seed(b"cs50"), b"config".to_vec()].concat();
+        assert_ne!(short, long);
+
+        let seed = StudentIntroInstruction::namespace_seed(&[b'a'; MAX_NAMESPACE_LEN]);
+        assert_eq!(seed.len(), solana_program::pubkey::MAX_SEED_LEN);
+        assert_eq!(StudentIntroInstruction::seed_namespace(&seed), &[b'a'; MAX_NAMESPACE_LEN]);
+        assert_eq!(StudentIntroInstruction::seed_namespace(b""), b"");
+    }
+}
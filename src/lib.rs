@@ -1,5 +1,33 @@
 pub mod processor;
 pub mod instruction;
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 pub mod state;
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod error_codes;
+pub mod constants;
+pub mod pyth;
+pub mod raffle;
+pub mod crank;
+pub mod moderation;
+pub mod cohort;
+pub mod events;
+pub mod audit;
+pub mod template;
+pub mod content;
+pub mod tags;
+pub mod translation;
+pub mod cpi;
+pub mod health;
+pub mod auction;
+pub mod group;
+pub mod account_type;
+pub mod summary;
+pub mod migration;
+pub mod editor;
+pub mod snapshot;
+pub mod hook;
+pub mod attributes;
+pub mod erasure;
+pub mod mention;
+pub mod reconcile;
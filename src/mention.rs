@@ -0,0 +1,150 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_program_account, load_writable_config};
+use crate::state::{IntroMention, StudentIntroState};
+
+// Records which intros the reviewer's intro mentions, as edges at [from_intro, to_intro]
+// that graph tools can fetch without parsing messages. The accounts after the first four
+// are each mentioned intro followed by its edge, in the order of mentions. Edges already
+// recorded are skipped; the reviewer pays for the others and gets their rent back when
+// the intro is deleted. Edges to an intro deleted later stay and are skipped by clients.
+pub fn record_mentions(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    mentions: Vec<Pubkey>,
+) -> ProgramResult {
+    msg!("Recording {} mentions...", mentions.len());
+
+    if mentions.len() > IntroMention::MAX_MENTIONS {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "An intro records at most {} mentions at a time", IntroMention::MAX_MENTIONS));
+    }
+    check_account_count(accounts, 4 + 2 * mentions.len())?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    load_writable_config(program_id, ns, pda_config)?;
+
+    let review_data = load_open_intro(program_id, pda_review)?;
+    if review_data.reviewer != *reviewer.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the reviewer can record their intro's mentions"));
+    }
+
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
+    for mentioned in &mentions {
+        let pda_mentioned = next_account_info(account_info_iter)?;
+        let pda_mention = next_account_info(account_info_iter)?;
+
+        if pda_mentioned.key != mentioned {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Expected mentioned intro {}", mentioned));
+        }
+        if mentioned == pda_review.key {
+            msg!("An intro can't mention itself");
+            return Err(ProgramError::InvalidArgument);
+        }
+        load_open_intro(program_id, pda_mentioned)?;
+
+        let (mention_pda, mention_bump) = Pubkey::find_program_address(
+            &[ns, pda_review.key.as_ref(), mentioned.as_ref()], program_id);
+        if mention_pda != *pda_mention.key {
+            return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for intro mention PDA"));
+        }
+        if !pda_mention.data_is_empty() {
+            msg!("{} is already mentioned", mentioned);
+            continue;
+        }
+
+        invoke_signed(
+            &system_instruction::create_account(
+                reviewer.key,
+                pda_mention.key,
+                rent.minimum_balance(IntroMention::SIZE + account_type::TAG_LEN),
+                (IntroMention::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[reviewer.clone(), pda_mention.clone(), system_program.clone()],
+            &[&[ns, pda_review.key.as_ref(), mentioned.as_ref(), &[mention_bump]]],
+        )?;
+
+        let mention_data = IntroMention {
+            discriminator: IntroMention::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            from: *pda_review.key,
+            to: *mentioned,
+            created_slot: slot,
+        };
+        account_type::write(&mention_data, pda_mention)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn load_mention(program_id: &Pubkey, pda_mention: &AccountInfo) -> Result<IntroMention, ProgramError> {
+    if pda_mention.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mention_data = account_type::read::<IntroMention>(pda_mention).unwrap();
+    if !mention_data.is_initialized() || mention_data.discriminator != IntroMention::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Intro mention is not initialized"));
+    }
+
+    Ok(mention_data)
+}
+
+fn load_open_intro(program_id: &Pubkey, pda_review: &AccountInfo) -> Result<StudentIntroState, ProgramError> {
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "{} is not a student intro", pda_review.key));
+    }
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro {} is being closed", pda_review.key));
+    }
+
+    Ok(review_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_mention_fits_its_size() {
+        let mention_data = IntroMention {
+            discriminator: IntroMention::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            from: Pubkey::new_unique(),
+            to: Pubkey::new_unique(),
+            created_slot: u64::MAX,
+        };
+        assert_eq!(mention_data.try_to_vec().unwrap().len(), IntroMention::SIZE);
+    }
+}
@@ -0,0 +1,187 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, Sysvar},
+};
+use crate::account_type;
+use crate::constants::MIGRATION_BOUNTY_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::health;
+use crate::processor::{check_account_count, check_program_account, load_config, resize_account};
+use crate::state::{Config, GlobalStats, IntroSummaryCache, StudentIntroComment, StudentIntroState, UserRecord};
+
+// Upgrades any number of accounts to their current layout, tag byte included, taken after
+// the first four accounts. Anyone can crank it: the cranker pays whatever extra rent an
+// account needs to grow and is paid migration_bounty_lamports from the bounty vault for
+// each account it migrated. Accounts already current are skipped, so racing crankers don't fail.
+pub fn migrate_batch(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Migrating batch...");
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let cranker = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_bounty = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !cranker.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    let config_data = load_config(program_id, ns, pda_config)?;
+    check_bounty_vault(program_id, ns, pda_bounty)?;
+
+    let rent = Rent::get()?;
+    let mut migrated = 0u64;
+    for target in account_info_iter {
+        let health = health::inspect(program_id, target, &rent)?;
+        if !health.needs_migration {
+            continue;
+        }
+        // Accounts from before the tag grow by its byte while still read untagged, then
+        // their data moves behind it
+        let untagged = !account_type::is_tagged(&target.data.borrow());
+        if untagged {
+            resize_account(cranker, target, system_program, target.data_len() + account_type::TAG_LEN, &rent)?;
+            account_type::shift_behind_tag(&mut target.data.borrow_mut())?;
+        }
+        let new_len = current_len(&health.account_type, account_type::body(&target.data.borrow()));
+        match new_len {
+            Some(new_len) => resize_account(cranker, target, system_program, new_len, &rent)?,
+            None if !untagged => {
+                msg!("{} {:?} accounts need their own migration", target.key, health.account_type);
+                continue;
+            }
+            None => {}
+        }
+        if new_len.is_some() && health::inspect(program_id, target, &rent)?.needs_migration {
+            msg!("{} still needs migration after resizing", target.key);
+            continue;
+        }
+        migrated += 1;
+    }
+
+    let bounty = bounty_for(&config_data, migrated, pda_bounty.lamports(), rent.minimum_balance(0));
+    if bounty > 0 {
+        **pda_bounty.lamports.borrow_mut() -= bounty;
+        **cranker.lamports.borrow_mut() = cranker.lamports()
+            .checked_add(bounty)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+    msg!("Migrated {} accounts for a bounty of {} lamports", migrated, bounty);
+
+    Ok(())
+}
+
+// Tops up the bounty vault at ["migration_bounty"], creating it on the first deposit
+pub fn fund_migration_bounty(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    lamports: u64,
+) -> ProgramResult {
+    msg!("Funding migration bounty with {} lamports...", lamports);
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let funder = next_account_info(account_info_iter)?;
+    let pda_bounty = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !funder.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    let bounty_bump = check_bounty_vault(program_id, ns, pda_bounty)?;
+
+    if pda_bounty.lamports() == 0 {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                funder.key,
+                pda_bounty.key,
+                rent.minimum_balance(0).saturating_add(lamports),
+                0,
+                program_id,
+            ),
+            &[funder.clone(), pda_bounty.clone(), system_program.clone()],
+            &[&[ns, MIGRATION_BOUNTY_SEED, &[bounty_bump]]],
+        )?;
+    } else {
+        invoke(
+            &system_instruction::transfer(funder.key, pda_bounty.key, lamports),
+            &[funder.clone(), pda_bounty.clone(), system_program.clone()],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn check_bounty_vault(program_id: &Pubkey, ns: &[u8], pda_bounty: &AccountInfo) -> Result<u8, ProgramError> {
+    let (bounty_pda, bounty_bump) = Pubkey::find_program_address(&[ns, MIGRATION_BOUNTY_SEED], program_id);
+    if bounty_pda != *pda_bounty.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for migration bounty PDA"));
+    }
+    if pda_bounty.lamports() != 0 && pda_bounty.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    Ok(bounty_bump)
+}
+
+// The length an account of the type has in the current layout. Accounts whose migration
+// needs more than room, like comment seed versions, have none here.
+fn current_len(account_type: &str, data: &[u8]) -> Option<usize> {
+    match account_type {
+        StudentIntroState::DISCRIMINATOR => Some(StudentIntroState::ACCOUNT_LEN),
+        StudentIntroComment::DISCRIMINATOR => {
+            let comment_data = try_from_slice_unchecked::<StudentIntroComment>(data).unwrap();
+            Some(StudentIntroComment::get_account_size(comment_data.comment, comment_data.link))
+        }
+        UserRecord::DISCRIMINATOR => Some(UserRecord::SIZE),
+        Config::DISCRIMINATOR => Some(Config::SIZE),
+        GlobalStats::DISCRIMINATOR => Some(GlobalStats::SIZE),
+        IntroSummaryCache::DISCRIMINATOR => Some(IntroSummaryCache::SIZE),
+        _ => None,
+    }
+}
+
+// The vault keeps its own rent, and pays what it can once it runs low
+fn bounty_for(config_data: &Config, migrated: u64, vault_lamports: u64, vault_rent: u64) -> u64 {
+    config_data.migration_bounty_lamports
+        .saturating_mul(migrated)
+        .min(vault_lamports.saturating_sub(vault_rent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounty_is_paid_per_account_out_of_spare_lamports() {
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        assert_eq!(bounty_for(&config_data, 3, 10_000, 890), 0);
+
+        config_data.migration_bounty_lamports = 1_000;
+        assert_eq!(bounty_for(&config_data, 3, 10_000, 890), 3_000);
+        assert_eq!(bounty_for(&config_data, 0, 10_000, 890), 0);
+        assert_eq!(bounty_for(&config_data, 20, 10_000, 890), 9_110);
+        assert_eq!(bounty_for(&config_data, 3, 500, 890), 0);
+    }
+}
@@ -0,0 +1,188 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::BANNED_PHRASES_SEED;
+use crate::audit;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_admin, load_writable_config};
+use crate::state::{AuditEntry, BannedPhrases};
+
+pub fn initialize_banned_phrases(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Initializing banned phrases...");
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_banned = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    let (banned_pda, banned_bump) = Pubkey::find_program_address(&[ns, BANNED_PHRASES_SEED], program_id);
+    if banned_pda != *pda_banned.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_banned.key,
+            rent.minimum_balance(BannedPhrases::SIZE + account_type::TAG_LEN),
+            (BannedPhrases::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_banned.clone(), system_program.clone()],
+        &[&[ns, BANNED_PHRASES_SEED, &[banned_bump]]],
+    )?;
+
+    let banned_data = BannedPhrases {
+        discriminator: BannedPhrases::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        hashes: Vec::new(),
+    };
+    account_type::write(&banned_data, pda_banned)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_INITIALIZE_BANNED_PHRASES)
+}
+
+// Hashes are of normalize_phrase output so the admin never has to publish the phrases
+pub fn update_banned_phrases(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    add: Vec<[u8; 32]>,
+    remove: Vec<[u8; 32]>,
+) -> ProgramResult {
+    msg!("Updating banned phrases...");
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_banned = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    let mut banned_data = load_banned_phrases(program_id, ns, pda_banned)?
+        .ok_or(ReviewError::UninitializedAccount)?;
+
+    banned_data.hashes.retain(|banned| !remove.contains(banned));
+    for phrase_hash in add {
+        if !banned_data.hashes.contains(&phrase_hash) {
+            banned_data.hashes.push(phrase_hash);
+        }
+    }
+
+    if banned_data.hashes.len() > BannedPhrases::MAX_HASHES {
+        return Err(reason!(ReviewError::InvalidDataLength, "At most {} banned phrases fit", BannedPhrases::MAX_HASHES));
+    }
+
+    msg!("{} banned phrases", banned_data.hashes.len());
+    account_type::write(&banned_data, pda_banned)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_UPDATE_BANNED_PHRASES)
+}
+
+// Rejects a comment whose normalized text is on the banned list. An uninitialized list
+// bans nothing.
+pub fn check_comment(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_banned: &AccountInfo,
+    comment: &str,
+) -> ProgramResult {
+    let banned_data = match load_banned_phrases(program_id, ns, pda_banned)? {
+        Some(banned_data) => banned_data,
+        None => return Ok(()),
+    };
+
+    if banned_data.hashes.contains(&phrase_hash(comment)) {
+        return Err(reason!(ReviewError::BannedPhrase, "Comment matches a banned phrase"));
+    }
+
+    Ok(())
+}
+
+// Lowercases, drops punctuation and collapses whitespace so trivial variations of a
+// spam string hash the same
+pub fn normalize_phrase(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn phrase_hash(phrase: &str) -> [u8; 32] {
+    hash(normalize_phrase(phrase).as_bytes()).to_bytes()
+}
+
+fn load_banned_phrases(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_banned: &AccountInfo,
+) -> Result<Option<BannedPhrases>, ProgramError> {
+    let (banned_pda, _banned_bump) = Pubkey::find_program_address(&[ns, BANNED_PHRASES_SEED], program_id);
+    if banned_pda != *pda_banned.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for banned phrases PDA"));
+    }
+    if pda_banned.data_is_empty() {
+        return Ok(None);
+    }
+    if pda_banned.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let banned_data = account_type::read::<BannedPhrases>(pda_banned).unwrap();
+    if !banned_data.is_initialized() {
+        return Ok(None);
+    }
+
+    Ok(Some(banned_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_variants_hash_the_same() {
+        assert_eq!(normalize_phrase("  Free   TOKENS!!! at spam.example "), "free tokens at spamexample");
+        assert_eq!(phrase_hash("free tokens"), phrase_hash("FREE, tokens."));
+        assert_ne!(phrase_hash("free tokens"), phrase_hash("free token"));
+        assert_eq!(normalize_phrase("?! ..."), "");
+    }
+}
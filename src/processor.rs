@@ -3,21 +3,59 @@ use solana_program::{
     borsh::try_from_slice_unchecked,
     entrypoint::ProgramResult,
     msg,
-    program::invoke_signed,
+    hash::hash,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     pubkey::Pubkey,
     system_instruction,
-    sysvar::{rent::Rent, Sysvar, rent::ID as RENT_PROGRAM_ID},
-    program_pack::IsInitialized,
+    sysvar::{clock::Clock, rent::Rent, Sysvar, rent::ID as RENT_PROGRAM_ID},
+    program_pack::{IsInitialized, Pack},
     system_program::ID as SYSTEM_PROGRAM_ID,
-    native_token::LAMPORTS_PER_SOL,
 };
 use borsh::BorshSerialize;
+use crate::constants::{
+    COMMENTER_SEED, COMMENT_PAGE_SEED, COMMENT_SEED, CONFIG_SEED, GLOBAL_STATS_SEED, INTRO_SEED,
+    PENDING_CONFIG_SEED, REACTION_SEED, REWARD_DECIMALS, TOKEN_AUTH_SEED, TOKEN_MINT_SEED, TRANSFER_SEED,
+    TREASURY_SEED, USER_SEED,
+};
 use crate::{error::ReviewError, state::StudentIntroCommentCounter, state::StudentIntroComment};
-use crate::instruction::StudentIntroInstruction;
+use crate::reason;
+use crate::state::{
+    AuditEntry, CommentPage, CommentReaction, CommentTranslation, ContentEntry, CostEstimate, CommenterRecord, Config, ExportPage, GlobalStats, IntroAttributes, IntroIndex, IntroMention, IntroSummary, IntroSummaryCache, MaintenanceThread,
+    PendingConfigChange, TransferReceipt, UserCommentIndex, UserIntroIndex, UserRecord,
+};
+use crate::instruction::{ConfigParams, StudentIntroInstruction};
+use crate::account_type;
+use crate::attributes;
+use crate::cohort::{self, RewardSchedule};
+use crate::content;
+use crate::editor;
+use crate::erasure;
+use crate::audit;
+use crate::auction;
+use crate::crank;
+use crate::events::{self, IndexEvent};
+use crate::group;
+use crate::health;
+use crate::hook;
+use crate::mention;
+use crate::migration;
+use crate::moderation;
+use crate::pyth;
+use crate::raffle;
+use crate::reconcile;
+use crate::snapshot;
+use crate::summary;
+use crate::tags;
+use crate::template;
+use crate::translation;
 use std::convert::TryInto;
 use crate::state::StudentIntroState;
-use spl_token::{ instruction::{ initialize_mint, mint_to }, ID as TOKEN_PROGRAM_ID };
+use spl_token::{
+    instruction::{ burn, initialize_account, initialize_mint, mint_to, transfer, transfer_checked },
+    state::{Account as TokenAccount, Mint},
+    ID as TOKEN_PROGRAM_ID,
+};
 use spl_associated_token_account::get_associated_token_address;
 
 pub fn process_instruction(
@@ -25,178 +63,398 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = StudentIntroInstruction::unpack(instruction_data)?;
+    let (namespace, instruction) = StudentIntroInstruction::unpack_namespaced(instruction_data)?;
+    let namespace_seed = StudentIntroInstruction::namespace_seed(&namespace);
+    let ns = namespace_seed.as_slice();
+    if let Some(variant) = StudentIntroInstruction::legacy_variant(instruction_data) {
+        msg!("Instruction {} uses the deprecated unversioned framing", variant);
+        events::emit(&IndexEvent::LegacyInstructionUsed { variant })?;
+    }
 
     match instruction {
         StudentIntroInstruction::AddStudentIntro { 
             name, 
-            message } => {
-            add_student_intro(program_id, accounts, name, message)
+            message,
+            dry_run } => {
+            add_student_intro(program_id, ns, accounts, name, message, dry_run)
         }
         StudentIntroInstruction::UpdateStudentIntro { 
             name, 
-            message } => {
-            update_student_intro(program_id, accounts, name, message)
+            message,
+            expected_hash,
+            dry_run } => {
+            update_student_intro(program_id, ns, accounts, name, message, expected_hash, dry_run)
         }
-        StudentIntroInstruction::AddComment { comment } => {
-            add_student_intro_comment(program_id, accounts, comment)
+        StudentIntroInstruction::AddComment { comment, link, content_hash, visibility } => {
+            add_student_intro_comment(program_id, ns, accounts, comment, link, content_hash, visibility, false, None)
         }
+        StudentIntroInstruction::AddKeyedComment { comment, link, content_hash, visibility } => {
+            add_student_intro_comment(program_id, ns, accounts, comment, link, content_hash, visibility, true, None)
+        }
+        StudentIntroInstruction::AddAnonymousComment { comment, link, content_hash, commitment } => {
+            add_student_intro_comment(program_id, ns, accounts, comment, link, content_hash,
+                StudentIntroComment::VISIBILITY_PUBLIC, false, Some(commitment))
+        }
+        StudentIntroInstruction::SetRewardsHalted { halted } =>
+            set_rewards_halted(program_id, ns, accounts, halted),
+        StudentIntroInstruction::SetAttribute { key, value } =>
+            attributes::set_attribute(program_id, ns, accounts, key, value),
+        StudentIntroInstruction::RequestErasure =>
+            erasure::request_erasure(program_id, ns, accounts),
+        StudentIntroInstruction::ExecuteErasure { close } =>
+            erasure::execute_erasure(program_id, ns, accounts, close),
+        StudentIntroInstruction::RecordMentions { mentions } =>
+            mention::record_mentions(program_id, ns, accounts, mentions),
+        StudentIntroInstruction::ReconcileRewards { cohorts } =>
+            reconcile::reconcile_rewards(program_id, ns, accounts, cohorts),
+        StudentIntroInstruction::WithdrawGroupVault { amount } =>
+            group::withdraw_group_vault(program_id, ns, accounts, amount),
+        StudentIntroInstruction::CloseCommentIndex =>
+            close_comment_index(program_id, ns, accounts),
+        StudentIntroInstruction::UpdateComment { comment, link, content_hash, expected_hash } => {
+            update_comment(program_id, ns, accounts, comment, link, content_hash, expected_hash)
+        }
+        StudentIntroInstruction::TopUpRent { account } =>
+            top_up_rent(program_id, ns, accounts, account),
+        StudentIntroInstruction::FinalizeCourse { revoke_mint_authority } =>
+            finalize_course(program_id, ns, accounts, revoke_mint_authority),
+        StudentIntroInstruction::SetRewardDestination { destination } =>
+            set_reward_destination(program_id, ns, accounts, destination),
+        StudentIntroInstruction::SetAdmins { co_admins, threshold } =>
+            set_admins(program_id, ns, accounts, co_admins, threshold),
+        StudentIntroInstruction::CheckDuplicateContent { message } =>
+            content::check_duplicate_content(program_id, ns, accounts, message),
+        StudentIntroInstruction::MigrateCommentSeeds =>
+            migrate_comment_seeds(program_id, ns, accounts),
+        StudentIntroInstruction::IndexIntroTags =>
+            tags::index_intro_tags(program_id, ns, accounts),
+        StudentIntroInstruction::AddTranslation { language, text } =>
+            translation::add_translation(program_id, ns, accounts, language, text),
+        StudentIntroInstruction::DeleteComment =>
+            delete_comment(program_id, ns, accounts),
+        StudentIntroInstruction::CheckAccountHealth =>
+            health::check_account_health(program_id, ns, accounts),
+        StudentIntroInstruction::CreateAuction { id, start_slot, end_slot, feature_end_slot } =>
+            auction::create_auction(program_id, ns, accounts, id, start_slot, end_slot, feature_end_slot),
+        StudentIntroInstruction::PlaceBid { amount } =>
+            auction::place_bid(program_id, ns, accounts, amount),
+        StudentIntroInstruction::SettleAuction =>
+            auction::settle_auction(program_id, ns, accounts),
+        StudentIntroInstruction::ClaimBidRefund =>
+            auction::claim_bid_refund(program_id, ns, accounts),
+        StudentIntroInstruction::CreateGroup { name, reward_share_bps } =>
+            group::create_group(program_id, ns, accounts, name, reward_share_bps),
+        StudentIntroInstruction::UpdateGroup { reward_share_bps, manager } =>
+            group::update_group(program_id, ns, accounts, reward_share_bps, manager),
+        StudentIntroInstruction::SetRewardGroup { group } =>
+            group::set_reward_group(program_id, ns, accounts, group),
+        StudentIntroInstruction::JoinGroup =>
+            group::join_group(program_id, ns, accounts),
+        StudentIntroInstruction::LeaveGroup =>
+            group::leave_group(program_id, ns, accounts),
         StudentIntroInstruction::InitializeMint => 
-            initialize_token_mint(program_id, accounts),
-        
+            initialize_token_mint(program_id, ns, accounts),
+        StudentIntroInstruction::DeleteStudentIntro =>
+            delete_student_intro(program_id, ns, accounts),
+        StudentIntroInstruction::InitializeGlobalStats =>
+            initialize_global_stats(program_id, ns, accounts),
+        StudentIntroInstruction::ExportPage { cursor } =>
+            export_page(program_id, ns, accounts, cursor),
+        StudentIntroInstruction::InitializeConfig { params } =>
+            initialize_config(program_id, ns, accounts, params),
+        StudentIntroInstruction::Bootstrap { params } =>
+            bootstrap(program_id, ns, accounts, params),
+        StudentIntroInstruction::BurnTreasurySurplus { amount } =>
+            burn_treasury_surplus(program_id, ns, accounts, amount),
+        StudentIntroInstruction::UpdateConfig { params } =>
+            update_config(program_id, ns, accounts, params),
+        StudentIntroInstruction::TransferReward { to, amount } =>
+            transfer_reward(program_id, ns, accounts, to, amount),
+        StudentIntroInstruction::InitializeTreasury =>
+            initialize_treasury(program_id, ns, accounts),
+        StudentIntroInstruction::ClaimRewards =>
+            claim_rewards(program_id, ns, accounts),
+        StudentIntroInstruction::ExpireRewards =>
+            expire_rewards(program_id, ns, accounts),
+        StudentIntroInstruction::CreateRaffle { id, start_slot, end_slot, prize } =>
+            raffle::create_raffle(program_id, ns, accounts, id, start_slot, end_slot, prize),
+        StudentIntroInstruction::CommitRaffleRandomness =>
+            raffle::commit_raffle_randomness(program_id, ns, accounts),
+        StudentIntroInstruction::DrawRaffle =>
+            raffle::draw_raffle(program_id, ns, accounts),
+        StudentIntroInstruction::CleanupIntro =>
+            cleanup_intro(program_id, ns, accounts),
+        StudentIntroInstruction::InitializeThread { task, interval_slots } =>
+            crank::initialize_thread(program_id, ns, accounts, task, interval_slots),
+        StudentIntroInstruction::IncrementViews { count } =>
+            increment_views(program_id, ns, accounts, count),
+        StudentIntroInstruction::InitializeBannedPhrases =>
+            moderation::initialize_banned_phrases(program_id, ns, accounts),
+        StudentIntroInstruction::UpdateBannedPhrases { add, remove } =>
+            moderation::update_banned_phrases(program_id, ns, accounts, add, remove),
+        StudentIntroInstruction::InitializeCohort { id, intro_reward, comment_reward } =>
+            cohort::initialize_cohort(program_id, ns, accounts, id, intro_reward, comment_reward),
+        StudentIntroInstruction::ReassignCohort =>
+            cohort::reassign_cohort(program_id, ns, accounts),
+        StudentIntroInstruction::ResyncCounter =>
+            resync_counter(program_id, ns, accounts),
+        StudentIntroInstruction::AcknowledgeComment =>
+            acknowledge_comment(program_id, ns, accounts),
+        StudentIntroInstruction::ReactToComment =>
+            react_to_comment(program_id, ns, accounts),
+        StudentIntroInstruction::SyncIntroSummary =>
+            summary::sync_intro_summary(program_id, ns, accounts),
+        StudentIntroInstruction::ProposeConfigChange { params } =>
+            propose_config_change(program_id, ns, accounts, params),
+        StudentIntroInstruction::ApplyConfigChange =>
+            apply_config_change(program_id, ns, accounts),
+        StudentIntroInstruction::MigrateBatch =>
+            migration::migrate_batch(program_id, ns, accounts),
+        StudentIntroInstruction::FundMigrationBounty { lamports } =>
+            migration::fund_migration_bounty(program_id, ns, accounts, lamports),
+        StudentIntroInstruction::GrantEditor { editor } =>
+            editor::grant_editor(program_id, ns, accounts, editor),
+        StudentIntroInstruction::RevokeEditor { editor } =>
+            editor::revoke_editor(program_id, ns, accounts, editor),
+        StudentIntroInstruction::PublishTemplate { id, prompts, tags } =>
+            template::publish_template(program_id, ns, accounts, id, prompts, tags),
+        StudentIntroInstruction::CloneIntroTemplate { name, message, answers } =>
+            clone_intro_template(program_id, ns, accounts, name, message, answers),
     }
 }
 
 pub fn add_student_intro(
     program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    name: String,
+    message: String,
+    dry_run: bool,
+) -> ProgramResult {
+    create_student_intro(program_id, ns, accounts, name, message, None, dry_run)
+}
+
+// Same accounts as AddStudentIntro with the template before the intro hook program. The intro answers the
+// template's prompts and takes its tags; the name and message stay the student's own.
+pub fn clone_intro_template(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    name: String,
+    message: String,
+    answers: Vec<String>,
+) -> ProgramResult {
+    create_student_intro(program_id, ns, accounts, name, message, Some(answers), false)
+}
+
+fn create_student_intro(
+    program_id: &Pubkey,
+    ns: &[u8],
     accounts: &[AccountInfo],
     name: String,
     message: String,
+    answers: Option<Vec<String>>,
+    dry_run: bool,
 ) -> ProgramResult {
 
     msg!("Adding student intro..");
     msg!("Name: {}", name);
     msg!("Message: {}", message);
-    
-   // Get Account iterator
-   let account_info_iter = &mut accounts.iter();
 
-   // Get accounts
-   let initializer = next_account_info(account_info_iter)?;
-   let pda_account = next_account_info(account_info_iter)?;
-   let pda_counter = next_account_info(account_info_iter)?;
+    // Get Account iterator
+    check_account_count(accounts, 19 + answers.is_some() as usize)?;
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
 
     let token_mint = next_account_info(account_info_iter)?;
     let mint_auth = next_account_info(account_info_iter)?;
     let user_ata = next_account_info(account_info_iter)?;
 
-   let system_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
 
-   let token_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
 
-   msg!("Deriving mint authority");
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(
-        &[b"token_mint"], program_id);
-    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(
-        &[b"token_auth"], program_id
-    );
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Mint passed in add mint derived do not match");
-        return Err(ReviewError::InvalidPDA.into());
-    }
-    if *user_ata.key != get_associated_token_address(initializer.key, token_mint.key) {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(ReviewError::IncorrectAccountError.into());
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_user_intro = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_intro_index = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let fee_receiver = next_account_info(account_info_iter)?;
+    let price_feed = next_account_info(account_info_iter)?;
+    // The cohort the intro joins, whose mint and amounts pay its rewards
+    let pda_cohort = next_account_info(account_info_iter)?;
+    // ["content", hash] for the message, which must not be taken by another intro
+    let pda_content = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+
+    let (template, answers, tags) = match answers {
+        Some(answers) => {
+            let pda_template = next_account_info(account_info_iter)?;
+            let template_data = template::load_template(program_id, ns, pda_template)?;
+            template::check_answers(&template_data, &answers)?;
+            (*pda_template.key, answers, template_data.tags)
+        }
+        None => (Pubkey::default(), Vec::new(), Vec::new()),
+    };
+
+    let schedule = cohort::load_cohort(program_id, ns, pda_cohort)?;
+    let plan = validate_add_student_intro(
+        program_id,
+        ns,
+        initializer,
+        pda_account,
+        pda_counter,
+        user_ata,
+        pda_user,
+        pda_user_intro,
+        pda_stats,
+        pda_intro_index,
+        pda_config,
+        system_program,
+        RewardAccounts::check(program_id, ns, schedule, token_program, token_mint, mint_auth, treasury)?,
+        &name,
+        &message,
+        StudentIntroState::template_content_size(&answers, &tags),
+        dry_run,
+    )?;
+    let AddIntroPlan {
+        rent,
+        slot,
+        intro_bump,
+        counter_bump,
+        user_intro_bump,
+        intro_index_bump,
+        new_wallet,
+        rewards,
+        config_data,
+        mut stats_data,
+        mut user_data,
+    } = plan;
+    let pda = *pda_account.key;
+
+    // Account size is fixed so later updates can grow the message in place
+    let account_len: usize = StudentIntroState::ACCOUNT_LEN + account_type::TAG_LEN;
+
+    if dry_run {
+        for account in [pda_account, pda_counter, pda_user_intro, pda_intro_index, pda_summary] {
+            if !account.data_is_empty() {
+                msg!("Account {} already exists", account.key);
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+        }
+        let mut sizes = vec![
+            StudentIntroState::ACCOUNT_LEN, StudentIntroCommentCounter::SIZE, UserIntroIndex::SIZE, IntroIndex::SIZE, IntroSummaryCache::SIZE,
+        ];
+        if new_wallet {
+            sizes.push(UserRecord::SIZE);
+        }
+        if content::needs_entry(program_id, ns, pda_content, &content::message_hash(&message), &pda)? {
+            sizes.push(ContentEntry::SIZE);
+        }
+        let estimate = CostEstimate {
+            rent_lamports: sizes.iter().map(|size| rent.minimum_balance(*size + account_type::TAG_LEN)).sum(),
+            fee_lamports: creation_fee(&config_data, fee_receiver, price_feed, slot)?,
+            accounts_created: sizes.len() as u8,
+            reward: rewards.schedule.intro_reward,
+        };
+        return report_costs(initializer, &estimate);
     }
 
+    // Create the account
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            pda_account.key,
+            rent.minimum_balance(account_len),
+            account_len.try_into().unwrap(),
+            program_id,
+        ),
+        &[
+            initializer.clone(),
+            pda_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            ns,
+            initializer.key.as_ref(),
+            name.as_bytes(),
+            &[intro_bump],
+        ]],
+    )?;
+
+    msg!("PDA created: {}", pda);
 
-    if *token_mint.key != mint_pda {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
-
-
-   if !initializer.is_signer {
-    msg!("Missing required signature");
-    return Err(ProgramError::MissingRequiredSignature);
-    }
-
-   let (pda, bump_seed) = Pubkey::find_program_address(
-       &[initializer.key.as_ref(), name.as_bytes().as_ref()],
-       program_id,
-   );
-
-   if pda != *pda_account.key {
-    msg!("Invalid seeds for PDA");
-    return Err(ProgramError::InvalidArgument);
-   }
-
-   // Calculate account size required
-   // let account_len = 1 + (4 + name.len()) + (4 + message.len());
-   let account_len: usize = 1000;
-
-   // let total_len: usize = 1 + 1 + (4 + name.len()) + (4 + message.len());
-   if StudentIntroState::get_account_size(name.clone(), message.clone()) > 1000 {
-       msg!("Data length is larger than 1000 bytes");
-       return Err(ReviewError::InvalidDataLength.into());
-   }
-
-   // Calculate rent required
-   let rent = Rent::get()?;
-   let rent_lamports = rent.minimum_balance(account_len);
-
-   // Create the account
-   invoke_signed(
-       &system_instruction::create_account(
-           initializer.key,
-           pda_account.key,
-           rent_lamports,
-           account_len.try_into().unwrap(),
-           program_id,
-       ),
-       &[
-           initializer.clone(),
-           pda_account.clone(),
-           system_program.clone(),
-       ],
-       &[&[
-           initializer.key.as_ref(),
-           name.as_bytes().as_ref(),
-           &[bump_seed],
-       ]],
-   )?;
-
-   msg!("PDA created: {}", pda);
-
-   msg!("unpacking state account");
-   let mut account_data =
-       try_from_slice_unchecked::<StudentIntroState>(&pda_account.data.borrow()).unwrap();
-   msg!("borrowed account data");
-
-   account_data.discriminator = StudentIntroState::DISCRIMINATOR.to_string();
-   account_data.reviewer = *initializer.key;
-   account_data.name = name;
-   account_data.message = message;
-   account_data.is_initialized = true;
-
-   msg!("serializing account");
-   account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-   msg!("state account serialized");
-
-   msg!("Creating comment counter");
-   let rent = Rent::get()?;
-   let counter_rent_lamports = rent.minimum_balance(StudentIntroCommentCounter::SIZE);
-
-   let (counter, counter_bump) = Pubkey::find_program_address(
-        &[pda.as_ref(), "comment".as_ref()], program_id
-   );
-   if counter != *pda_counter.key {
-    msg!("Invalid seeds for PDA");
-    return Err(ProgramError::InvalidArgument);
-   }
-
-   invoke_signed(
-    &system_instruction::create_account(
-        initializer.key,
-        pda_counter.key, 
-        counter_rent_lamports, 
-        StudentIntroCommentCounter::SIZE.try_into().unwrap(), 
-        program_id), 
+    msg!("unpacking state account");
+    let mut account_data =
+        account_type::read::<StudentIntroState>(pda_account).unwrap();
+    msg!("borrowed account data");
+
+    account_data.discriminator = StudentIntroState::DISCRIMINATOR.to_string();
+    account_data.reviewer = *initializer.key;
+    account_data.name = name;
+    account_data.message = message;
+    account_data.is_initialized = true;
+    account_data.closing = false;
+    account_data.closed_comments = 0;
+    account_data.created_slot = slot;
+    account_data.cohort = rewards.schedule.cohort.clone();
+    account_data.intro_reward = rewards.schedule.intro_reward;
+    // pay_reward below mints nothing while rewards are halted
+    account_data.reward_minted = Some(if config_data.rewards_halted { 0 } else { rewards.schedule.intro_reward });
+    account_data.template = template;
+    account_data.answers = answers;
+    account_data.tags = tags;
+    account_data.message_hash = content::message_hash(&account_data.message);
+    account_data.group = user_data.group;
+    account_data.comments_added = 0;
+    account_data.comment_seed_version = Some(StudentIntroCommentCounter::CURRENT_SEED_VERSION);
+
+    content::register(program_id, ns, initializer, pda_content, system_program,
+        &account_data.message_hash, &pda, &rent, slot)?;
+
+    msg!("serializing account");
+    account_type::write(&account_data, pda_account)?;
+    msg!("state account serialized");
+
+    summary::create(program_id, ns, initializer, pda_summary, system_program, &pda, &account_data, 0, &rent)?;
+
+    cohort::count_member(pda_cohort, true)?;
+    events::emit_for(&mut user_data, &IndexEvent::IntroIndexed {
+        intro: pda,
+        reviewer: account_data.reviewer,
+        name: account_data.name,
+        cohort: account_data.cohort,
+    })?;
+    events::emit_for(&mut user_data, &IndexEvent::ContentCommitted {
+        intro: pda,
+        message_hash: account_data.message_hash,
+    })?;
+
+    msg!("Creating comment counter");
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            pda_counter.key,
+            rent.minimum_balance(StudentIntroCommentCounter::SIZE + account_type::TAG_LEN),
+            (StudentIntroCommentCounter::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
         &[
             initializer.clone(),
             pda_counter.clone(),
             system_program.clone(),
-        ], 
-    &[&[pda.as_ref(), "comment".as_ref(), &[counter_bump]]],)?;
-    
+        ],
+        &[&[ns, pda.as_ref(), COMMENT_SEED, &[counter_bump]]],
+    )?;
+
     msg!("Comment counter created");
 
-    let mut counter_data = try_from_slice_unchecked::<StudentIntroCommentCounter>(
-        &pda_counter.data.borrow()).unwrap();
+    let mut counter_data = account_type::read::<StudentIntroCommentCounter>(pda_counter).unwrap();
 
     msg!("Checking if counter account is already initialized.");
     if counter_data.is_initialized() {
@@ -207,221 +465,3796 @@ pub fn add_student_intro(
     counter_data.discriminator = StudentIntroCommentCounter::DISCRIMINATOR.to_string();
     counter_data.counter = 0;
     counter_data.is_intialized = true;
+    counter_data.seed_version = StudentIntroCommentCounter::CURRENT_SEED_VERSION;
     msg!("Comment count: {}", counter_data.counter);
-    
-    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+
+    account_type::write(&counter_data, pda_counter)?;
 
     msg!("Comment counter initialized");
 
-    msg!("Minting 10 tokens to User associated token account");
+    msg!("Recording intro in the reviewer's index");
     invoke_signed(
-        // Instruction
-        &mint_to(
-            token_program.key,
-            token_mint.key,
-            user_ata.key,
-            mint_auth.key,
-            &[],
-            10*LAMPORTS_PER_SOL,
-        )?, // ? unwraps and returns the error if there is one
-        // Account_infos
-        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
-        // Seeds
-        &[&[b"token_auth", &[mint_auth_bump]]],
+        &system_instruction::create_account(
+            initializer.key,
+            pda_user_intro.key,
+            rent.minimum_balance(UserIntroIndex::SIZE + account_type::TAG_LEN),
+            (UserIntroIndex::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[initializer.clone(), pda_user_intro.clone(), system_program.clone()],
+        &[&[
+            ns,
+            initializer.key.as_ref(),
+            INTRO_SEED,
+            user_data.intro_count.to_be_bytes().as_ref(),
+            &[user_intro_bump],
+        ]],
+    )?;
+
+    let index_data = UserIntroIndex {
+        discriminator: UserIntroIndex::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        reviewer: *initializer.key,
+        index: user_data.intro_count,
+        review: pda,
+    };
+    account_type::write(&index_data, pda_user_intro)?;
+
+    user_data.intro_count = user_data.intro_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    stats_data.intros = stats_data.intros
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    if new_wallet {
+        stats_data.active_wallets = stats_data.active_wallets
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    msg!("Recording intro in the global index");
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            pda_intro_index.key,
+            rent.minimum_balance(IntroIndex::SIZE + account_type::TAG_LEN),
+            (IntroIndex::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[initializer.clone(), pda_intro_index.clone(), system_program.clone()],
+        &[&[
+            ns,
+            INTRO_SEED,
+            stats_data.intros_created.to_be_bytes().as_ref(),
+            &[intro_index_bump],
+        ]],
+    )?;
+
+    let intro_index_data = IntroIndex {
+        discriminator: IntroIndex::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        index: stats_data.intros_created,
+        review: pda,
+    };
+    account_type::write(&intro_index_data, pda_intro_index)?;
+
+    stats_data.intros_created = stats_data.intros_created
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    msg!("Rewarding {} to User", rewards.schedule.intro_reward);
+    pay_reward(
+        &rewards,
+        &config_data,
+        &mut stats_data,
+        &mut user_data,
+        user_ata,
+        rewards.schedule.intro_reward,
+        slot,
     )?;
 
+    account_type::write(&user_data, pda_user)?;
+    account_type::write(&stats_data, pda_stats)?;
+
+    charge_creation_fee(&config_data, initializer, fee_receiver, price_feed, system_program, slot)?;
+
+    hook::notify_intro_created(&config_data, account_info_iter.next(), pda_account, initializer)
+}
+
+// What add_student_intro's validation pass hands to the rest of the handler. Rent and
+// the clock are read once, and each PDA is derived once so its bump can sign the CPI
+// that creates it.
+struct AddIntroPlan<'a, 'b> {
+    rent: Rent,
+    slot: u64,
+    intro_bump: u8,
+    counter_bump: u8,
+    user_intro_bump: u8,
+    intro_index_bump: u8,
+    new_wallet: bool,
+    rewards: RewardAccounts<'a, 'b>,
+    config_data: Config,
+    stats_data: GlobalStats,
+    user_data: UserRecord,
+}
+
+// Checks every account add_student_intro touches before anything is written. The user
+// record is created here when missing since its intro count seeds the reviewer's index.
+#[allow(clippy::too_many_arguments)]
+fn validate_add_student_intro<'a, 'b>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    initializer: &AccountInfo<'a>,
+    pda_account: &AccountInfo<'a>,
+    pda_counter: &AccountInfo<'a>,
+    user_ata: &AccountInfo<'a>,
+    pda_user: &AccountInfo<'a>,
+    pda_user_intro: &AccountInfo<'a>,
+    pda_stats: &AccountInfo<'a>,
+    pda_intro_index: &AccountInfo<'a>,
+    pda_config: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rewards: RewardAccounts<'a, 'b>,
+    name: &str,
+    message: &str,
+    template_size: usize,
+    dry_run: bool,
+) -> Result<AddIntroPlan<'a, 'b>, ProgramError> {
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_not_executable(&[pda_account, pda_counter, user_ata, pda_user, pda_user_intro, pda_intro_index])?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    let (pda, intro_bump) = Pubkey::find_program_address(
+        &[ns, initializer.key.as_ref(), name.as_bytes()],
+        program_id,
+    );
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if StudentIntroState::get_account_size(name.to_string(), message.to_string()) + template_size
+        > StudentIntroState::ACCOUNT_LEN
+    {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "Data length is larger than {} bytes", StudentIntroState::ACCOUNT_LEN));
+    }
+
+    let (counter, counter_bump) = Pubkey::find_program_address(
+        &[ns, pda.as_ref(), COMMENT_SEED], program_id
+    );
+    if counter != *pda_counter.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+    rewards.check_treasury(program_id, ns, &config_data)?;
+
+    let stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    let (intro_index_pda, intro_index_bump) = Pubkey::find_program_address(
+        &[ns, INTRO_SEED, stats_data.intros_created.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if intro_index_pda != *pda_intro_index.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let new_wallet = pda_user.data_is_empty();
+    let user_data = if dry_run && new_wallet {
+        preview_user_record(program_id, ns, initializer.key, pda_user)?
+    } else {
+        load_or_create_user_record(
+            program_id, ns, initializer, initializer.key, pda_user, system_program, &rent)?
+    };
+
+    if *user_ata.key != get_associated_token_address(&user_data.reward_owner(), rewards.token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+    }
+
+    let (user_intro_pda, user_intro_bump) = Pubkey::find_program_address(
+        &[ns, initializer.key.as_ref(), INTRO_SEED, user_data.intro_count.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if user_intro_pda != *pda_user_intro.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    Ok(AddIntroPlan {
+        rent,
+        slot,
+        intro_bump,
+        counter_bump,
+        user_intro_bump,
+        intro_index_bump,
+        new_wallet,
+        rewards,
+        config_data,
+        stats_data,
+        user_data,
+    })
+}
+
+// Charges the intro creation fee, priced in USD through the configured Pyth feed when
+// one is set and as a flat lamport amount otherwise
+fn charge_creation_fee<'a>(
+    config_data: &Config,
+    payer: &AccountInfo<'a>,
+    fee_receiver: &AccountInfo<'a>,
+    price_feed: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    slot: u64,
+) -> ProgramResult {
+    let fee_lamports = creation_fee(config_data, fee_receiver, price_feed, slot)?;
+    if fee_lamports == 0 {
+        return Ok(());
+    }
+
+    msg!("Charging {} lamports creation fee", fee_lamports);
+    invoke(
+        &system_instruction::transfer(payer.key, fee_receiver.key, fee_lamports),
+        &[payer.clone(), fee_receiver.clone(), system_program.clone()],
+    )
+}
+
+fn creation_fee(
+    config_data: &Config,
+    fee_receiver: &AccountInfo,
+    price_feed: &AccountInfo,
+    slot: u64,
+) -> Result<u64, ProgramError> {
+    let fee_lamports = if config_data.price_feed != Pubkey::default() && config_data.creation_fee_usd_cents > 0 {
+        if *price_feed.key != config_data.price_feed {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect price feed account"));
+        }
+
+        let price = pyth::load_price(price_feed)?;
+        pyth::check_price(
+            &price,
+            slot,
+            config_data.max_price_staleness_slots,
+            config_data.max_price_confidence_bps,
+        )?;
+        pyth::usd_cents_to_lamports(config_data.creation_fee_usd_cents, &price)?
+    } else {
+        config_data.creation_fee_lamports
+    };
+
+    if fee_lamports > 0 && *fee_receiver.key != config_data.fee_receiver {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect fee receiver account"));
+    }
+
+    Ok(fee_lamports)
+}
+
+// Ends a dry run once everything has been validated. The estimate goes back as return
+// data, and a payer who couldn't cover it gets the error the real instruction would hit.
+fn report_costs(payer: &AccountInfo, estimate: &CostEstimate) -> ProgramResult {
+    let total = estimate.rent_lamports
+        .checked_add(estimate.fee_lamports)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    if payer.lamports() < total {
+        msg!("Payer has {} lamports but needs {}", payer.lamports(), total);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    msg!("Dry run: {} lamports rent, {} lamports fee", estimate.rent_lamports, estimate.fee_lamports);
+    set_return_data(&estimate.try_to_vec()?);
 
-   Ok(())
+    Ok(())
 }
 
 pub fn update_student_intro(
     program_id: &Pubkey,
+    ns: &[u8],
     accounts: &[AccountInfo],
     name: String,
     message: String,
+    expected_hash: Option<[u8; 32]>,
+    dry_run: bool,
 ) -> ProgramResult {
     msg!("Updating student intro...");
 
+    check_account_count(accounts, 8)?;
     let account_info_iter = &mut accounts.iter();
 
     let initializer = next_account_info(account_info_iter)?;
     let pda_account = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    // Registry entries of the current and the new message. The current one is closed
+    // when the message changes; intros from before the registry have none to close.
+    let pda_old_content = next_account_info(account_info_iter)?;
+    let pda_new_content = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    // The signer's user record, whose action_seq the event takes
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+    // An editor signing for the reviewer passes their grant
+    let pda_editor = next_account_info(account_info_iter).ok();
 
     if pda_account.owner != program_id {
         return  Err(ProgramError::IllegalOwner);
     }
+    load_writable_config(program_id, ns, pda_config)?;
 
     if !initializer.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    msg!("Unpacking state student");
-    let mut account_data = try_from_slice_unchecked::<StudentIntroState>(&pda_account.data.borrow()).unwrap();
-    msg!("borrowed account data");
+    msg!("Unpacking state student");
+    let mut account_data = account_type::read::<StudentIntroState>(pda_account).unwrap();
+    msg!("borrowed account data");
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(&[ns,
+        account_data.reviewer.as_ref(),
+        account_data.name.as_bytes(),
+    ], program_id);
+
+    if pda != *pda_account.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    if !account_data.is_initialized {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    editor::check_editor(program_id, ns, initializer, pda_account.key, &account_data, pda_editor)?;
+
+    if account_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    check_expected_hash(
+        expected_hash, intro_content_hash(&account_data.name, &account_data.message)?)?;
+
+    let total_len: usize = StudentIntroState::get_account_size(name.clone(), message.clone())
+        + StudentIntroState::template_content_size(&account_data.answers, &account_data.tags);
+    if total_len > StudentIntroState::ACCOUNT_LEN {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "Data length is larger than {} bytes", StudentIntroState::ACCOUNT_LEN));
+    }
+
+    // Intros created before accounts were allocated with headroom can be smaller than the
+    // limit, so an edit that fits the limit may still not fit the account
+    if total_len > account_type::body_len(pda_account) {
+        return Err(reason!(ReviewError::AccountTooSmallForUpdate,
+            "Update needs {} bytes but {} only has {}, realloc it to at least {} bytes first",
+            total_len, pda_account.key, account_type::body_len(pda_account),
+            total_len + account_type::tag_len(&pda_account.data.borrow())));
+    }
+
+    let new_hash = content::message_hash(&message);
+    if dry_run {
+        let mut estimate = CostEstimate::default();
+        if new_hash != account_data.message_hash
+            && content::needs_entry(program_id, ns, pda_new_content, &new_hash, pda_account.key)?
+        {
+            estimate.rent_lamports = Rent::get()?.minimum_balance(ContentEntry::SIZE + account_type::TAG_LEN);
+            estimate.accounts_created = 1;
+        }
+        return report_costs(initializer, &estimate);
+    }
+    // Whoever signs, reviewer or editor, pays for the new entry and gets the old one's
+    // rent back, and entries are all the same size
+    if new_hash != account_data.message_hash {
+        check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+        content::register(program_id, ns, initializer, pda_new_content, system_program,
+            &new_hash, pda_account.key, &Rent::get()?, Clock::get()?.slot)?;
+        if account_data.message_hash != [0u8; 32] {
+            content::release(program_id, ns, pda_old_content, &account_data.message_hash,
+                pda_account.key, initializer)?;
+        }
+    }
+
+    account_data.name = name;
+    account_data.message = message;
+    account_data.message_hash = new_hash;
+
+    account_type::write(&account_data, pda_account)?;
+    summary::touch(program_id, ns, pda_account.key, pda_summary, |summary_data| {
+        summary_data.name_hash = hash(account_data.name.as_bytes()).to_bytes();
+    })?;
+
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, initializer, initializer.key, pda_user, system_program, &Rent::get()?)?;
+    events::emit_for(&mut user_data, &IndexEvent::ContentCommitted {
+        intro: *pda_account.key,
+        message_hash: new_hash,
+    })?;
+    account_type::write(&user_data, pda_user)?;
+
+    Ok(())
+}
+
+// Edits made from two devices can cross, so updates may carry the hash of the content
+// the edit was based on and are rejected if what's stored has changed since
+fn check_expected_hash(expected_hash: Option<[u8; 32]>, stored_hash: [u8; 32]) -> ProgramResult {
+    match expected_hash {
+        Some(expected_hash) if expected_hash != stored_hash => {
+            Err(reason!(ReviewError::StaleUpdate, "Content was changed by another update, reload it and retry"))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Hash of the borsh-encoded (name, message), what UpdateStudentIntro's guard compares
+pub fn intro_content_hash(name: &str, message: &str) -> Result<[u8; 32], ProgramError> {
+    Ok(hash(&(name, message).try_to_vec()?).to_bytes())
+}
+
+// Hash of the borsh-encoded (comment, link), what UpdateComment's guard compares
+pub fn comment_content_hash(comment: &str, link: &str) -> Result<[u8; 32], ProgramError> {
+    Ok(hash(&(comment, link).try_to_vec()?).to_bytes())
+}
+
+// Lets a commenter rewrite their comment. The account is resized to the new content,
+// with the commenter covering any extra rent and getting back what's no longer needed.
+pub fn update_comment(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    expected_hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    msg!("Updating comment...");
+
+    check_account_count(accounts, 6)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let pda_banned = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+
+    if !commenter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    if pda_review.owner != program_id || pda_comment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    let mut comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+    if !comment_data.is_initialized() || comment_data.discriminator != StudentIntroComment::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment account is not initialized"));
+    }
+    if comment_data.review != *pda_review.key {
+        return Err(reason!(ReviewError::CommentIntroMismatch,
+            "Comment {} does not belong to this intro", pda_comment.key));
+    }
+    if comment_data.commenter != *commenter.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the commenter can edit a comment"));
+    }
+    check_edit_window(&config_data, commenter.key, &comment_data, Clock::get()?.slot)?;
+
+    check_expected_hash(expected_hash, comment_content_hash(&comment_data.comment, &comment_data.link)?)?;
+    moderation::check_comment(program_id, ns, pda_banned, &comment)?;
+    check_comment_link(&link)?;
+
+    let new_len = StudentIntroComment::get_account_size(comment.clone(), link.clone());
+    resize_account(commenter, pda_comment, system_program, new_len, &Rent::get()?)?;
+
+    comment_data.comment = comment;
+    comment_data.link = link;
+    comment_data.content_hash = content_hash;
+    account_type::write(&comment_data, pda_comment)?;
+
+    Ok(())
+}
+
+// Lets a commenter take back their comment while the edit window is open, or the admin
+// remove one at any time. The rent goes back to the commenter, and the comment counts
+// as closed so deleting the intro later doesn't wait for it. Each of its translations is
+// passed after the summary as a (translation, author) pair and closed with it.
+pub fn delete_comment(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Deleting comment...");
+
+    check_account_count(accounts, 7)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let actor = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let commenter = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+    let translation_pairs = account_info_iter.as_slice().chunks_exact(2);
+
+    if !actor.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pda_review.owner != program_id || pda_comment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config_data = load_config(program_id, ns, pda_config)?;
+
+    let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing,
+            "Student intro is being closed, its comments are closed with it"));
+    }
+
+    let comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+    if !comment_data.is_initialized() || comment_data.discriminator != StudentIntroComment::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment account is not initialized"));
+    }
+    if comment_data.review != *pda_review.key {
+        return Err(reason!(ReviewError::CommentIntroMismatch,
+            "Comment {} does not belong to this intro", pda_comment.key));
+    }
+    if comment_data.commenter != *actor.key && config_data.admin != *actor.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the commenter or the admin can delete a comment"));
+    }
+    if rent_receiver(&comment_data, actor.key) != *commenter.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Rent refund must go to the commenter"));
+    }
+    check_edit_window(&config_data, actor.key, &comment_data, Clock::get()?.slot)?;
+
+    if !translation_pairs.remainder().is_empty() || translation_pairs.len() != comment_data.translations as usize {
+        return Err(reason!(ReviewError::NotEnoughAccounts,
+            "Pass the comment's {} translations as (translation, author) pairs", comment_data.translations));
+    }
+    for pair in translation_pairs {
+        translation::close_translation(program_id, pda_comment.key, &pair[0], &pair[1])?;
+    }
+    close_account(pda_comment, commenter)?;
+
+    review_data.closed_comments = review_data.closed_comments
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&review_data, pda_review)?;
+    summary::touch(program_id, ns, pda_review.key, pda_summary, |summary_data| {
+        summary_data.comment_count = summary_data.comment_count.saturating_sub(1);
+    })?;
+
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    // Stats may have been initialized after this comment was created
+    stats_data.comments = stats_data.comments.saturating_sub(1);
+    account_type::write(&stats_data, pda_stats)?;
+
+    msg!("Comment {} deleted", pda_comment.key);
+
+    Ok(())
+}
+
+// Closes a commenter's index entry once the comment it points at is gone, however it was
+// closed. Anyone can send it; the rent goes back to the commenter. Profile pages walking
+// 0..comment_count still find holes where entries were closed, and skip them.
+pub fn close_comment_index(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Closing comment index entry...");
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?;
+    let pda_user_comment = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let commenter = next_account_info(account_info_iter)?;
+
+    if !caller.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pda_user_comment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let index_data = account_type::read::<UserCommentIndex>(pda_user_comment).unwrap();
+    if !index_data.is_initialized() || index_data.discriminator != UserCommentIndex::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment index entry is not initialized"));
+    }
+    let (user_comment_pda, _user_comment_bump) = Pubkey::find_program_address(
+        &[ns, index_data.commenter.as_ref(), COMMENT_SEED, index_data.index.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if user_comment_pda != *pda_user_comment.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+    if index_data.comment != *pda_comment.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Expected comment {}", index_data.comment));
+    }
+    if index_data.commenter != *commenter.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Rent refund must go to the commenter"));
+    }
+    if is_live_comment(program_id, pda_comment) {
+        return Err(reason!(ReviewError::Unauthorized,
+            "Comment {} is still open, its index entry stays", pda_comment.key));
+    }
+
+    close_account(pda_user_comment, commenter)
+}
+
+// Closed comments are left zeroed until the runtime reclaims them
+fn is_live_comment(program_id: &Pubkey, pda_comment: &AccountInfo) -> bool {
+    pda_comment.owner == program_id
+        && pda_comment.lamports() > 0
+        && matches!(
+            account_type::read::<StudentIntroComment>(pda_comment),
+            Ok(comment_data) if comment_data.is_initialized()
+                && comment_data.discriminator == StudentIntroComment::DISCRIMINATOR
+        )
+}
+
+fn check_translations_closed(comment: Option<Pubkey>, open_translations: u8) -> ProgramResult {
+    match comment {
+        Some(comment) if open_translations > 0 => Err(reason!(ReviewError::NotEnoughAccounts,
+            "Comment {} has {} translations to pass after it", comment, open_translations)),
+        _ => Ok(()),
+    }
+}
+
+// Comments from before their slot was recorded count as outside any window
+fn check_edit_window(
+    config_data: &Config,
+    actor: &Pubkey,
+    comment_data: &StudentIntroComment,
+    slot: u64,
+) -> ProgramResult {
+    if config_data.edit_window_slots == 0 || config_data.admin == *actor {
+        return Ok(());
+    }
+
+    if comment_data.created_slot == 0
+        || slot > comment_data.created_slot.saturating_add(config_data.edit_window_slots)
+    {
+        return Err(reason!(ReviewError::EditWindowClosed,
+            "Comments can only be changed within {} slots of posting", config_data.edit_window_slots));
+    }
+
+    Ok(())
+}
+
+// Resizes a program account so new_len bytes of data fit behind its tag. The payer tops
+// up the rent when it grows and gets back the lamports above the new minimum when it
+// shrinks.
+pub(crate) fn resize_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_len: usize,
+    rent: &Rent,
+) -> ProgramResult {
+    let new_len = new_len + account_type::tag_len(&account.data.borrow());
+    if new_len == account.data_len() {
+        return Ok(());
+    }
+
+    let min_balance = rent.minimum_balance(new_len);
+    if new_len > account.data_len() {
+        let extra_rent = min_balance.saturating_sub(account.lamports());
+        if extra_rent > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, extra_rent),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+    } else {
+        let refund = account.lamports().saturating_sub(min_balance);
+        **account.lamports.borrow_mut() -= refund;
+        **payer.lamports.borrow_mut() = payer.lamports()
+            .checked_add(refund)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    account.realloc(new_len, false)
+}
+
+// Comments don't touch the intro's counter: they are counted on the intro and numbered by
+// their page slot, or for keyed comments by the commenter's own index. The counter's
+// place in the accounts is only read, for the seed version of intros that don't record it
+// yet, and can be any account otherwise. Every comment still writes the intro itself, its
+// summary and the global stats, so comments land one at a time per intro.
+#[allow(clippy::too_many_arguments)]
+pub fn add_student_intro_comment(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    comment: String,
+    link: String,
+    content_hash: [u8; 32],
+    visibility: u8,
+    keyed: bool,
+    // Set for anonymous comments, which store it in place of the commenter. The hash of
+    // the commenter's key and a salt only they know lets them prove authorship later.
+    commitment: Option<[u8; 32]>,
+) -> ProgramResult {
+    msg!("Adding comment ...");
+    msg!("Comment: {}",comment);
+
+    check_account_count(accounts, 21)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+
+    let system_program = next_account_info(account_info_iter)?;
+
+    let token_program = next_account_info(account_info_iter)?;
+
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_user_comment = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_commenter = next_account_info(account_info_iter)?;
+    let reviewer_ata = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let pda_reviewer_user = next_account_info(account_info_iter)?;
+    let pda_banned = next_account_info(account_info_iter)?;
+    let pda_page = next_account_info(account_info_iter)?;
+    // The intro's cohort, whose mint and amounts pay the comment's rewards
+    let pda_cohort = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+    // Optional: the commenter's group and its vault when they are in one, then the
+    // running raffle and the ticket this comment would earn
+    let mut trailing = account_info_iter.as_slice().iter();
+
+    check_not_executable(&[
+        pda_review, pda_comment, user_ata, pda_user, pda_user_comment, pda_commenter, reviewer_ata,
+    ])?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Student intro is not initialized"));
+    }
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    moderation::check_comment(program_id, ns, pda_banned, &comment)?;
+
+    check_comment_link(&link)?;
+    let account_len = StudentIntroComment::get_account_size(comment.clone(), link.clone()) + account_type::TAG_LEN;
+    let comment_chars = comment.chars().count() as u64;
+    
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(account_len);
+
+    let new_wallet = pda_user.data_is_empty();
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, commenter, commenter.key, pda_user, system_program, &rent)?;
+
+    // Anonymous comments leave out everything that ties them to the signer: the user's
+    // comment index, their commenter record, group counts and rewards. The signer's
+    // user record still rate-limits them.
+    let anonymous = commitment.is_some();
+    let mut member_group = if anonymous || user_data.group == Pubkey::default() {
+        None
+    } else {
+        let pda_group = next_account_info(&mut trailing)?;
+        let group_vault = next_account_info(&mut trailing)?;
+        if *pda_group.key != user_data.group {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Commenter is in group {}", user_data.group));
+        }
+        Some((group::load_group(program_id, ns, pda_group)?, pda_group, group_vault))
+    };
+    let pda_raffle = trailing.next();
+    let pda_ticket = trailing.next();
+
+    // Paged comments take the next slot of one of the intro's pages. Keyed comments live
+    // at [intro, commenter, the commenter's comment index] instead, which a client can
+    // derive from its own user record without racing other commenters for a page slot.
+    // The page account is unused then.
+    let mut page_data = if keyed {
+        None
+    } else {
+        check_not_executable(&[pda_page])?;
+        let page_data = load_or_open_comment_page(
+            program_id, ns, commenter, pda_review, &mut review_data, pda_page, system_program, &rent)?;
+        if page_data.count >= CommentPage::CAPACITY {
+            return Err(reason!(ReviewError::CommentPageFull, "Comment page {} is full", page_data.page_index));
+        }
+        Some(page_data)
+    };
+
+    let slot_in_page = match page_data.as_ref() {
+        Some(page_data) => StudentIntroCommentCounter::versioned_slot_seed(
+            comment_seed_version(program_id, ns, pda_review.key, &review_data, pda_counter)?, page_data.count),
+        None => [0u8; 8],
+    };
+    let comment_number = match page_data.as_ref() {
+        Some(page_data) => page_data.page_index
+            .checked_mul(CommentPage::CAPACITY)
+            .and_then(|first| first.checked_add(page_data.count))
+            .ok_or(ReviewError::ArithmeticOverflow)?,
+        None => user_data.comment_count,
+    };
+    let comment_index = user_data.comment_count.to_be_bytes();
+    let mut comment_seeds: Vec<&[u8]> = if keyed {
+        vec![ns, pda_review.key.as_ref(), commenter.key.as_ref(), &comment_index]
+    } else {
+        vec![ns, pda_page.key.as_ref(), &slot_in_page]
+    };
+    let (pda, bump_seed) = Pubkey::find_program_address(&comment_seeds, program_id);
+    if pda != *pda_comment.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+    let bump = [bump_seed];
+    comment_seeds.push(&bump);
+
+    invoke_signed(
+        &system_instruction::create_account(
+        commenter.key, 
+        pda_comment.key, 
+        rent_lamports, 
+        account_len.try_into().unwrap(), 
+        program_id,
+        ),
+        &[commenter.clone(),
+        pda_comment.clone(),
+        system_program.clone()],
+    &[&comment_seeds],
+    )?;
+
+    match page_data.as_mut() {
+        Some(page_data) => {
+            msg!("Created comment {} on page {}", page_data.count, page_data.page_index);
+            page_data.count += 1;
+            account_type::write(page_data, pda_page)?;
+        }
+        None => msg!("Created comment {} keyed by the commenter", user_data.comment_count),
+    }
+
+    let mut comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+
+    msg!("Checking if comment account is already initialized.");
+    if comment_data.is_initialized() {
+        msg!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    comment_data.discriminator = StudentIntroComment::DISCRIMINATOR.to_string();
+    comment_data.review = *pda_review.key;
+    comment_data.commenter = commitment.map_or(*commenter.key, Pubkey::new_from_array);
+    comment_data.anonymous = anonymous;
+    comment_data.comment = comment;
+    comment_data.count = comment_number;
+    comment_data.link = link;
+    comment_data.content_hash = content_hash;
+    comment_data.created_slot = Clock::get()?.slot;
+    comment_data.visibility = visibility;
+    comment_data.is_initialized = true;
+    account_type::write(&comment_data, pda_comment)?;
+
+    msg!("Comment number: {}", comment_number);
+
+    review_data.comments_added = review_data.comments_added
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    summary::touch(program_id, ns, pda_review.key, pda_summary, |summary_data| {
+        summary_data.comment_count = summary_data.comment_count.saturating_add(1);
+    })?;
+
+    if !anonymous {
+        msg!("Recording comment in the commenter's index");
+
+        let (user_comment_pda, user_comment_bump) = Pubkey::find_program_address(
+            &[ns, commenter.key.as_ref(), COMMENT_SEED, user_data.comment_count.to_be_bytes().as_ref()],
+            program_id,
+        );
+        if user_comment_pda != *pda_user_comment.key {
+            return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+        }
+
+        invoke_signed(
+            &system_instruction::create_account(
+                commenter.key,
+                pda_user_comment.key,
+                rent.minimum_balance(UserCommentIndex::SIZE + account_type::TAG_LEN),
+                (UserCommentIndex::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[commenter.clone(), pda_user_comment.clone(), system_program.clone()],
+            &[&[
+                ns,
+                commenter.key.as_ref(),
+                COMMENT_SEED,
+                user_data.comment_count.to_be_bytes().as_ref(),
+                &[user_comment_bump],
+            ]],
+        )?;
+
+        let index_data = UserCommentIndex {
+            discriminator: UserCommentIndex::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            commenter: *commenter.key,
+            index: user_data.comment_count,
+            review: *pda_review.key,
+            comment: *pda_comment.key,
+        };
+        account_type::write(&index_data, pda_user_comment)?;
+
+        user_data.comment_count = user_data.comment_count
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_visibility(&config_data, commenter.key, visibility)?;
+    if anonymous {
+        check_anonymous_rate(&config_data, &mut user_data, Clock::get()?.slot)?;
+    }
+
+    // Short comments are accepted but don't earn tokens, tickets or count as a distinct
+    // commenter for the intro owner's bonus
+    let earns_reward = !anonymous && comment_chars >= config_data.min_reward_comment_chars;
+    let unique_commenter_bonus = if earns_reward {
+        record_commenter(
+            program_id,
+            ns,
+            commenter,
+            pda_review,
+            &mut review_data,
+            pda_commenter,
+            system_program,
+            config_data.unique_commenter_bonus,
+        )?
+    } else {
+        0
+    };
+    account_type::write(&review_data, pda_review)?;
+
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    stats_data.comments = stats_data.comments
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    if new_wallet {
+        stats_data.active_wallets = stats_data.active_wallets
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    // Mint tokens here
+    let schedule = cohort::load_cohort(program_id, ns, pda_cohort)?;
+    if schedule.cohort != review_data.cohort {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Intro belongs to cohort {:?}", review_data.cohort));
+    }
+    let rewards = RewardAccounts::check(program_id, ns, schedule, token_program, token_mint, mint_auth, treasury)?;
+    rewards.check_treasury(program_id, ns, &config_data)?;
+
+    if *user_ata.key != get_associated_token_address(&user_data.reward_owner(), token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+    }
+
+    let slot = Clock::get()?.slot;
+
+    let reward = if earns_reward && !config_data.rewards_halted { rewards.schedule.comment_reward } else { 0 };
+    if reward > 0 {
+        // The group's share is minted straight to its vault, the rest pays out as usual
+        let share = match member_group.as_mut() {
+            Some((group_data, pda_group, group_vault)) if user_data.reward_group == *pda_group.key => {
+                let share = group::group_share(reward, group_data.reward_share_bps);
+                if share > 0 {
+                    msg!("Routing {} to group {:?}", share, group_data.name);
+                    pay_group_share(&rewards, &config_data, &mut stats_data, group_vault, pda_group.key, share)?;
+                    group_data.rewards_routed = group_data.rewards_routed
+                        .checked_add(share)
+                        .ok_or(ReviewError::ArithmeticOverflow)?;
+                }
+                share
+            }
+            _ => 0,
+        };
+        msg!("Rewarding {} to User", reward - share);
+        if reward > share {
+            pay_reward(
+                &rewards,
+                &config_data,
+                &mut stats_data,
+                &mut user_data,
+                user_ata,
+                reward - share,
+                slot,
+            )?;
+        }
+    } else if anonymous {
+        msg!("Anonymous comments earn no reward");
+    } else if config_data.rewards_halted {
+        msg!("Reward minting is halted, no reward");
+    } else {
+        msg!("Comment is shorter than {} characters, no reward", config_data.min_reward_comment_chars);
+    }
+    account_type::write(&user_data, pda_user)?;
+
+    if unique_commenter_bonus > 0 {
+        // Read after the comment reward was minted, which the cap counts too
+        let supply = Mint::unpack(&token_mint.data.borrow())?.supply;
+        if within_bonus_supply_cap(&config_data, supply, unique_commenter_bonus) {
+            let mut reviewer_data = load_or_create_user_record(
+                program_id, ns, commenter, &review_data.reviewer, pda_reviewer_user, system_program, &rent)?;
+
+            if *reviewer_ata.key != get_associated_token_address(&reviewer_data.reward_owner(), token_mint.key) {
+                return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect reviewer token account"));
+            }
+
+            msg!("Rewarding {} bonus to the intro owner for {} distinct commenters",
+                unique_commenter_bonus, review_data.unique_commenters);
+            pay_reward(
+                &rewards,
+                &config_data,
+                &mut stats_data,
+                &mut reviewer_data,
+                reviewer_ata,
+                unique_commenter_bonus,
+                slot,
+            )?;
+            account_type::write(&reviewer_data, pda_reviewer_user)?;
+        } else {
+            msg!("Unique commenter bonus would pass the supply cap of {}", config_data.bonus_supply_cap);
+        }
+    }
+
+    if let Some((mut group_data, pda_group, _group_vault)) = member_group {
+        group_data.comments = group_data.comments
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        account_type::write(&group_data, pda_group)?;
+    }
+
+    if let (true, Some(pda_raffle), Some(pda_ticket)) = (earns_reward, pda_raffle, pda_ticket) {
+        raffle::issue_ticket(program_id, ns, commenter, pda_raffle, pda_ticket, system_program)?;
+    }
+
+    account_type::write(&stats_data, pda_stats)?;
+
+    // Tells the client what the comment actually earned
+    set_return_data(&reward.try_to_vec()?);
+
+    Ok(())
+}
+
+// Anonymous comments have no wallet to refund, so their rent goes to whoever closes them
+fn rent_receiver(comment_data: &StudentIntroComment, closer: &Pubkey) -> Pubkey {
+    if comment_data.anonymous {
+        *closer
+    } else {
+        comment_data.commenter
+    }
+}
+
+// Anonymous comments still come from a wallet, which can only post so many of them per
+// window, so the mode can't be used to flood an intro
+fn check_anonymous_rate(config_data: &Config, user_data: &mut UserRecord, slot: u64) -> ProgramResult {
+    if config_data.anonymous_window_slots == 0 || config_data.anonymous_comments_per_window == 0 {
+        return Err(reason!(ReviewError::Unauthorized, "Anonymous comments are turned off"));
+    }
+
+    if slot >= user_data.anonymous_window_start_slot.saturating_add(config_data.anonymous_window_slots) {
+        user_data.anonymous_window_start_slot = slot;
+        user_data.anonymous_comments_in_window = 0;
+    }
+    if user_data.anonymous_comments_in_window >= config_data.anonymous_comments_per_window {
+        return Err(reason!(ReviewError::AnonymousCommentLimit, "At most {} anonymous comments every {} slots",
+            config_data.anonymous_comments_per_window, config_data.anonymous_window_slots));
+    }
+    user_data.anonymous_comments_in_window += 1;
+
+    Ok(())
+}
+
+// Private feedback is for graders, so only the admin set can leave non-public comments
+fn check_visibility(config_data: &Config, commenter: &Pubkey, visibility: u8) -> ProgramResult {
+    match visibility {
+        StudentIntroComment::VISIBILITY_PUBLIC => Ok(()),
+        StudentIntroComment::VISIBILITY_OWNER | StudentIntroComment::VISIBILITY_STAFF => {
+            if config_data.admin != *commenter && !config_data.co_admins.contains(commenter) {
+                return Err(reason!(ReviewError::Unauthorized, "Only staff can leave non-public feedback"));
+            }
+            Ok(())
+        }
+        _ => {
+            msg!("Unknown visibility level {}", visibility);
+            Err(ProgramError::InvalidArgument)
+        }
+    }
+}
+
+// Links are optional, but one that is given must be a bounded https URL
+fn check_comment_link(link: &str) -> ProgramResult {
+    if link.is_empty() {
+        return Ok(());
+    }
+
+    if link.len() > StudentIntroComment::MAX_LINK_LEN || !link.starts_with(StudentIntroComment::LINK_SCHEME)
+        || link.len() == StudentIntroComment::LINK_SCHEME.len()
+    {
+        return Err(reason!(ReviewError::InvalidLink, "Comment links must be {} URLs of at most {} bytes",
+            StudentIntroComment::LINK_SCHEME, StudentIntroComment::MAX_LINK_LEN));
+    }
+
+    Ok(())
+}
+
+// Loads the comment page passed in, opening it when it is the intro's next page. Pages
+// already open but not full can all take comments, so busy intros spread out writes.
+// Loads the counter at [intro, "comment"]. Its seed version picks the comment slot seeds,
+// so any other program account passed in its place is rejected before it is trusted.
+pub(crate) fn load_comment_counter(
+    program_id: &Pubkey,
+    ns: &[u8],
+    intro: &Pubkey,
+    pda_counter: &AccountInfo,
+) -> Result<StudentIntroCommentCounter, ProgramError> {
+    if pda_counter.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let (counter_pda, _counter_bump) = Pubkey::find_program_address(&[ns, intro.as_ref(), COMMENT_SEED], program_id);
+    if counter_pda != *pda_counter.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for comment counter PDA"));
+    }
+
+    let counter_data = account_type::read::<StudentIntroCommentCounter>(pda_counter)
+        .map_err(|_| reason!(ReviewError::UninitializedAccount, "Comment counter is not initialized"))?;
+    if !counter_data.is_initialized() || counter_data.discriminator != StudentIntroCommentCounter::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment counter is not initialized"));
+    }
+
+    Ok(counter_data)
+}
+
+// The seed version of the intro's comment page slots, from its counter for intros that
+// don't record it yet
+fn comment_seed_version(
+    program_id: &Pubkey,
+    ns: &[u8],
+    intro: &Pubkey,
+    review_data: &StudentIntroState,
+    pda_counter: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    match review_data.comment_seed_version {
+        Some(seed_version) => Ok(seed_version),
+        None => Ok(load_comment_counter(program_id, ns, intro, pda_counter)?.seed_version),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_or_open_comment_page<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    commenter: &AccountInfo<'a>,
+    pda_review: &AccountInfo<'a>,
+    review_data: &mut StudentIntroState,
+    pda_page: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> Result<CommentPage, ProgramError> {
+    if !pda_page.data_is_empty() {
+        if pda_page.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let page_data = account_type::read::<CommentPage>(pda_page).unwrap();
+        let (page_pda, _page_bump) = Pubkey::find_program_address(
+            &[ns, pda_review.key.as_ref(), COMMENT_PAGE_SEED, page_data.page_index.to_be_bytes().as_ref()],
+            program_id,
+        );
+        if !page_data.is_initialized() || page_pda != *pda_page.key {
+            return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for comment page PDA"));
+        }
+
+        return Ok(page_data);
+    }
+
+    let page_index = review_data.comment_pages;
+    let (page_pda, page_bump) = Pubkey::find_program_address(
+        &[ns, pda_review.key.as_ref(), COMMENT_PAGE_SEED, page_index.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if page_pda != *pda_page.key {
+        return Err(reason!(ReviewError::InvalidPDA, "New comments open page {}", page_index));
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            commenter.key,
+            pda_page.key,
+            rent.minimum_balance(CommentPage::SIZE + account_type::TAG_LEN),
+            (CommentPage::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[commenter.clone(), pda_page.clone(), system_program.clone()],
+        &[&[
+            ns,
+            pda_review.key.as_ref(),
+            COMMENT_PAGE_SEED,
+            page_index.to_be_bytes().as_ref(),
+            &[page_bump],
+        ]],
+    )?;
+
+    review_data.comment_pages = review_data.comment_pages
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    msg!("Opened comment page {}", page_index);
+
+    Ok(CommentPage {
+        discriminator: CommentPage::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        review: *pda_review.key,
+        page_index,
+        count: 0,
+        payer: *commenter.key,
+    })
+}
+
+// Counts the commenter on the intro and returns the owner's bonus if this is their
+// first comment there. The owner commenting on their own intro earns nothing.
+#[allow(clippy::too_many_arguments)]
+fn record_commenter<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    commenter: &AccountInfo<'a>,
+    pda_review: &AccountInfo<'a>,
+    review_data: &mut StudentIntroState,
+    pda_commenter: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    base_bonus: u64,
+) -> Result<u64, ProgramError> {
+    let (commenter_pda, commenter_bump) = Pubkey::find_program_address(
+        &[ns, pda_review.key.as_ref(), COMMENTER_SEED, commenter.key.as_ref()],
+        program_id,
+    );
+    if commenter_pda != *pda_commenter.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for commenter record PDA"));
+    }
+
+    let first_comment = pda_commenter.data_is_empty();
+    if first_comment {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                commenter.key,
+                pda_commenter.key,
+                rent.minimum_balance(CommenterRecord::SIZE + account_type::TAG_LEN),
+                (CommenterRecord::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[commenter.clone(), pda_commenter.clone(), system_program.clone()],
+            &[&[
+                ns,
+                pda_review.key.as_ref(),
+                COMMENTER_SEED,
+                commenter.key.as_ref(),
+                &[commenter_bump],
+            ]],
+        )?;
+    } else if pda_commenter.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut commenter_data = account_type::read::<CommenterRecord>(pda_commenter).unwrap();
+    if first_comment {
+        commenter_data.discriminator = CommenterRecord::DISCRIMINATOR.to_string();
+        commenter_data.is_initialized = true;
+        commenter_data.review = *pda_review.key;
+        commenter_data.commenter = *commenter.key;
+    }
+    commenter_data.comment_count = commenter_data.comment_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&commenter_data, pda_commenter)?;
+
+    if !first_comment || *commenter.key == review_data.reviewer {
+        return Ok(0);
+    }
+
+    review_data.unique_commenters = review_data.unique_commenters
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    base_bonus
+        .checked_mul(integer_sqrt(review_data.unique_commenters))
+        .ok_or_else(|| ReviewError::ArithmeticOverflow.into())
+}
+
+fn integer_sqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method, starting from an overestimate that can't overflow
+    let mut root = value / 2 + 1;
+    let mut next = (root + value / root) / 2;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+
+    root
+}
+
+// Lets the intro owner mark a comment on their intro as read, paying the commenter the
+// configured thank-you reward the first time. Acknowledging again changes nothing.
+pub fn acknowledge_comment(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Acknowledging comment...");
+
+    check_account_count(accounts, 13)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_cohort = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let commenter_ata = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_commenter_user = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_not_executable(&[pda_review, pda_comment, commenter_ata, pda_commenter_user])?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    if pda_review.owner != program_id || pda_comment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    let (pda, _bump_seed) = Pubkey::find_program_address(
+        &[ns, reviewer.key.as_ref(), review_data.name.as_bytes()],
+        program_id,
+    );
+    if pda != *pda_review.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Only the intro owner can acknowledge its comments"));
+    }
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    let mut comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+    if !comment_data.is_initialized() || comment_data.discriminator != StudentIntroComment::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment account is not initialized"));
+    }
+    if comment_data.review != *pda_review.key {
+        return Err(reason!(ReviewError::CommentIntroMismatch,
+            "Comment {} does not belong to this intro", pda_comment.key));
+    }
+    if comment_data.acknowledged {
+        msg!("Comment already acknowledged");
+        return Ok(());
+    }
+    comment_data.acknowledged = true;
+
+    // Comments are sized to their contents, so ones from before acknowledgements need
+    // room for the flag. The reviewer covers the extra rent.
+    let rent = Rent::get()?;
+    let needed_len = StudentIntroComment::get_account_size(
+        comment_data.comment.clone(), comment_data.link.clone());
+    if account_type::body_len(pda_comment) < needed_len {
+        resize_account(reviewer, pda_comment, system_program, needed_len, &rent)?;
+    }
+    account_type::write(&comment_data, pda_comment)?;
+
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+    if config_data.acknowledge_reward == 0 || comment_data.commenter == *reviewer.key || comment_data.anonymous {
+        return Ok(());
+    }
+
+    let schedule = cohort::load_cohort(program_id, ns, pda_cohort)?;
+    if schedule.cohort != review_data.cohort {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Intro belongs to cohort {:?}", review_data.cohort));
+    }
+    let rewards = RewardAccounts::check(program_id, ns, schedule, token_program, token_mint, mint_auth, treasury)?;
+    rewards.check_treasury(program_id, ns, &config_data)?;
+
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    let mut commenter_data = load_or_create_user_record(
+        program_id, ns, reviewer, &comment_data.commenter, pda_commenter_user, system_program, &rent)?;
+
+    if *commenter_ata.key != get_associated_token_address(&commenter_data.reward_owner(), token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect commenter token account"));
+    }
+
+    let supply = Mint::unpack(&token_mint.data.borrow())?.supply;
+    if !within_bonus_supply_cap(&config_data, supply, config_data.acknowledge_reward) {
+        msg!("Acknowledge reward would pass the supply cap of {}", config_data.bonus_supply_cap);
+        return Ok(());
+    }
+
+    msg!("Rewarding {} to the commenter as thanks", config_data.acknowledge_reward);
+    pay_reward(
+        &rewards,
+        &config_data,
+        &mut stats_data,
+        &mut commenter_data,
+        commenter_ata,
+        config_data.acknowledge_reward,
+        Clock::get()?.slot,
+    )?;
+    account_type::write(&commenter_data, pda_commenter_user)?;
+    account_type::write(&stats_data, pda_stats)?;
+
+    Ok(())
+}
+
+// Records the reactor's one reaction to a comment. Once the comment has reactions from
+// reaction_reward_threshold distinct wallets the commenter is minted the reaction bonus,
+// a single time. Commenters can't react to their own comments.
+pub fn react_to_comment(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Reacting to comment...");
+
+    check_account_count(accounts, 15)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reactor = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let pda_reaction = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_cohort = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let commenter_ata = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_commenter_user = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+
+    if !reactor.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_not_executable(&[pda_review, pda_comment, commenter_ata, pda_commenter_user])?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    if pda_review.owner != program_id || pda_comment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    let mut comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+    if !comment_data.is_initialized() || comment_data.discriminator != StudentIntroComment::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment account is not initialized"));
+    }
+    if comment_data.review != *pda_review.key {
+        return Err(reason!(ReviewError::CommentIntroMismatch,
+            "Comment {} does not belong to this intro", pda_comment.key));
+    }
+    if comment_data.commenter == *reactor.key {
+        return Err(reason!(ReviewError::Unauthorized, "Commenters can't react to their own comments"));
+    }
+
+    let (reaction_pda, reaction_bump) = Pubkey::find_program_address(
+        &[ns, pda_comment.key.as_ref(), REACTION_SEED, reactor.key.as_ref()], program_id);
+    if reaction_pda != *pda_reaction.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for reaction PDA"));
+    }
+    if !pda_reaction.data_is_empty() {
+        return Err(reason!(ReviewError::AlreadyReacted, "{} already reacted to {}", reactor.key, pda_comment.key));
+    }
+
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
+    invoke_signed(
+        &system_instruction::create_account(
+            reactor.key,
+            pda_reaction.key,
+            rent.minimum_balance(CommentReaction::SIZE + account_type::TAG_LEN),
+            (CommentReaction::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[reactor.clone(), pda_reaction.clone(), system_program.clone()],
+        &[&[ns, pda_comment.key.as_ref(), REACTION_SEED, reactor.key.as_ref(), &[reaction_bump]]],
+    )?;
+    let reaction_data = CommentReaction {
+        discriminator: CommentReaction::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        comment: *pda_comment.key,
+        reactor: *reactor.key,
+        slot,
+    };
+    account_type::write(&reaction_data, pda_reaction)?;
+
+    comment_data.reactions = comment_data.reactions
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    stats_data.reactions = stats_data.reactions
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    summary::touch(program_id, ns, pda_review.key, pda_summary, |summary_data| {
+        summary_data.like_count = summary_data.like_count.saturating_add(1);
+    })?;
+
+    if reaction_bonus_due(&config_data, &comment_data) {
+        let schedule = cohort::load_cohort(program_id, ns, pda_cohort)?;
+        if schedule.cohort != review_data.cohort {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Intro belongs to cohort {:?}", review_data.cohort));
+        }
+        let rewards = RewardAccounts::check(program_id, ns, schedule, token_program, token_mint, mint_auth, treasury)?;
+        rewards.check_treasury(program_id, ns, &config_data)?;
+
+        let supply = Mint::unpack(&token_mint.data.borrow())?.supply;
+        if within_bonus_supply_cap(&config_data, supply, config_data.reaction_reward) {
+            let mut commenter_data = load_or_create_user_record(
+                program_id, ns, reactor, &comment_data.commenter, pda_commenter_user, system_program, &rent)?;
+            if *commenter_ata.key != get_associated_token_address(&commenter_data.reward_owner(), token_mint.key) {
+                return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect commenter token account"));
+            }
+
+            msg!("Comment reached {} reactions, rewarding {} to the commenter",
+                comment_data.reactions, config_data.reaction_reward);
+            pay_reward(
+                &rewards,
+                &config_data,
+                &mut stats_data,
+                &mut commenter_data,
+                commenter_ata,
+                config_data.reaction_reward,
+                slot,
+            )?;
+            account_type::write(&commenter_data, pda_commenter_user)?;
+            comment_data.reaction_rewarded = true;
+        } else {
+            msg!("Reaction bonus would pass the supply cap of {}", config_data.bonus_supply_cap);
+        }
+    }
+
+    // Comments from before reactions need room for the count. The reactor covers the
+    // extra rent.
+    let needed_len = StudentIntroComment::get_account_size(
+        comment_data.comment.clone(), comment_data.link.clone());
+    if account_type::body_len(pda_comment) < needed_len {
+        resize_account(reactor, pda_comment, system_program, needed_len, &rent)?;
+    }
+    account_type::write(&comment_data, pda_comment)?;
+    account_type::write(&stats_data, pda_stats)?;
+
+    Ok(())
+}
+
+fn reaction_bonus_due(config_data: &Config, comment_data: &StudentIntroComment) -> bool {
+    config_data.reaction_reward != 0
+        && config_data.reaction_reward_threshold != 0
+        && !config_data.rewards_halted
+        && !comment_data.anonymous
+        && !comment_data.reaction_rewarded
+        && comment_data.reactions >= config_data.reaction_reward_threshold as u32
+}
+
+fn within_bonus_supply_cap(config_data: &Config, supply: u64, amount: u64) -> bool {
+    config_data.bonus_supply_cap == 0
+        || supply.checked_add(amount).map(|total| total <= config_data.bonus_supply_cap).unwrap_or(false)
+}
+
+pub fn delete_student_intro(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Deleting student intro...");
+
+    check_account_count(accounts, 10)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    // Only read when the reward is burned, which an intro young enough always has
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if pda_review.owner != program_id || pda_counter.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut account_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+
+    if !account_data.is_initialized() {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(
+        &[ns, reviewer.key.as_ref(), account_data.name.as_bytes()],
+        program_id,
+    );
+    if pda != *pda_review.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    if !account_data.closing {
+        let config_data = load_config(program_id, ns, pda_config)?;
+        let clock = Clock::get()?;
+
+        if account_data.created_slot != 0
+            && clock.slot < account_data.created_slot.saturating_add(config_data.delete_penalty_window_slots)
+        {
+            let (user_pda, _user_bump) = Pubkey::find_program_address(
+                &[ns, USER_SEED, reviewer.key.as_ref()], program_id);
+            if user_pda != *pda_user.key {
+                return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for user record PDA"));
+            }
+            if pda_user.owner != program_id {
+                return Err(ProgramError::IllegalOwner);
+            }
+            let mut user_data = account_type::read::<UserRecord>(pda_user).unwrap();
+
+            // Rewards still pending in the treasury are cancelled before anything is burned.
+            // Only the default cohort accrues; the cancelled tokens stay in the treasury as
+            // surplus for BurnTreasurySurplus.
+            let mut burn_amount = account_data.minted_reward();
+            if account_data.minted_cohort().is_empty() {
+                let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+                expire_pending_rewards(&config_data, &mut stats_data, &mut user_data, clock.slot)?;
+                let cancelled = burn_amount.min(user_data.pending_rewards);
+                user_data.pending_rewards -= cancelled;
+                stats_data.rewards_pending = stats_data.rewards_pending.saturating_sub(cancelled);
+                account_type::write(&stats_data, pda_stats)?;
+                burn_amount -= cancelled;
+                if cancelled > 0 {
+                    msg!("Intro deleted early, cancelled {} pending rewards", cancelled);
+                }
+            }
+
+            if burn_amount > 0 {
+                // Rewards minted directly sit with the reward destination, which then signs
+                // the burn as the account after the summary
+                let holder = if user_data.reward_owner() == *reviewer.key {
+                    reviewer
+                } else {
+                    next_account_info(account_info_iter)?
+                };
+                if *holder.key != user_data.reward_owner() {
+                    return Err(reason!(ReviewError::IncorrectAccountError,
+                        "Expected reward destination {}", user_data.reward_owner()));
+                }
+                if !holder.is_signer {
+                    msg!("Missing required signature");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                let (mint_pda, _mint_bump) = Pubkey::find_program_address(
+                    &[ns, TOKEN_MINT_SEED, account_data.minted_cohort().as_bytes()], program_id);
+                if *token_mint.key != mint_pda {
+                    return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+                }
+                if *user_ata.key != get_associated_token_address(holder.key, token_mint.key) {
+                    return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect user token account"));
+                }
+                check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+                msg!("Intro deleted within {} slots of creation, burning the {} reward",
+                    config_data.delete_penalty_window_slots, burn_amount);
+                burn_reward_tokens(program_id, ns, token_program, user_ata, token_mint, holder, pda_stats,
+                    &mut user_data, burn_amount, IndexEvent::BURN_DELETE_PENALTY)?;
+            }
+            account_type::write(&user_data, pda_user)?;
+        }
+    }
+
+    // The rest of the accounts are (comment, commenter) pairs, each followed by its
+    // (translation, author) pairs, and (page, payer), (attributes, payer) and (mention,
+    // reviewer) pairs to close in this batch
+    let comment_accounts = account_info_iter.as_slice();
+
+    // The dashboard stops listing the intro as soon as it starts closing
+    summary::close(program_id, ns, pda_review.key, pda_summary, reviewer)?;
+    account_data.closing = true;
+
+    close_intro_batch(program_id, ns, reviewer, pda_review, pda_counter, pda_stats, account_data, comment_accounts)
+}
+
+// Every burn of reward tokens goes through here or burn_pda_tokens, so burned_total and
+// the TokensBurned events account for all of the token's sinks. This one burns from a
+// holder's own account, which the holder signs for.
+#[allow(clippy::too_many_arguments)]
+fn burn_reward_tokens<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    token_program: &AccountInfo<'a>,
+    holder_ata: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    holder: &AccountInfo<'a>,
+    pda_stats: &AccountInfo<'a>,
+    holder_data: &mut UserRecord,
+    amount: u64,
+    reason: u8,
+) -> ProgramResult {
+    invoke(
+        &burn(
+            token_program.key,
+            holder_ata.key,
+            token_mint.key,
+            holder.key,
+            &[],
+            amount,
+        )?,
+        &[holder_ata.clone(), token_mint.clone(), holder.clone()],
+    )?;
+    count_burned(program_id, ns, pda_stats, amount)?;
+
+    events::emit_for(holder_data, &IndexEvent::TokensBurned {
+        actor: *holder.key,
+        mint: *token_mint.key,
+        amount,
+        reason,
+    })
+}
+
+// Burns from a token account the default mint's authority holds, like the treasury or
+// an auction vault, with the authority PDA signing. The actor is who the burn is
+// attributed to.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn burn_pda_tokens<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    token_program: &AccountInfo<'a>,
+    holder_ata: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    mint_auth: &AccountInfo<'a>,
+    mint_auth_bump: u8,
+    pda_stats: &AccountInfo<'a>,
+    actor: &Pubkey,
+    amount: u64,
+    reason: u8,
+) -> ProgramResult {
+    invoke_signed(
+        &burn(token_program.key, holder_ata.key, token_mint.key, mint_auth.key, &[], amount)?,
+        &[holder_ata.clone(), token_mint.clone(), mint_auth.clone()],
+        &[&[ns, TOKEN_AUTH_SEED, &[mint_auth_bump]]],
+    )?;
+    count_burned(program_id, ns, pda_stats, amount)?;
+
+    events::emit(&IndexEvent::TokensBurned {
+        actor: *actor,
+        mint: *token_mint.key,
+        amount,
+        reason,
+    })
+}
+
+fn count_burned(program_id: &Pubkey, ns: &[u8], pda_stats: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    stats_data.burned_total = stats_data.burned_total
+        .checked_add(amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&stats_data, pda_stats)?;
+    Ok(())
+}
+
+// Anyone can carry on closing an intro its owner already started deleting. Rent only ever
+// goes back to each commenter and the reviewer, and replaying a batch is harmless.
+pub fn cleanup_intro(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Cleaning up student intro...");
+
+    let (accounts, pda_thread) = crank::split_thread(
+        program_id, ns, MaintenanceThread::TASK_CLEANUP_INTROS, accounts);
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+
+    // The rest of the accounts are (comment, commenter) pairs, each followed by its
+    // (translation, author) pairs, and (page, payer), (attributes, payer) and (mention,
+    // reviewer) pairs to close in this batch
+    let comment_accounts = account_info_iter.as_slice();
+
+    if let Some(pda_thread) = pda_thread {
+        if !crank::start_run(program_id, pda_thread, Clock::get()?.slot)? {
+            return Ok(());
+        }
+    }
+
+    if pda_review.owner != program_id && pda_review.lamports() == 0 {
+        msg!("Student intro is already closed");
+        return Ok(());
+    }
+    if pda_review.owner != program_id || pda_counter.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let account_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+
+    if !account_data.is_initialized() {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+    if !account_data.closing {
+        return Err(reason!(ReviewError::Unauthorized, "Only the owner can start deleting a student intro"));
+    }
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(
+        &[ns, reviewer.key.as_ref(), account_data.name.as_bytes()],
+        program_id,
+    );
+    if pda != *pda_review.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    close_intro_batch(program_id, ns, reviewer, pda_review, pda_counter, pda_stats, account_data, comment_accounts)
+}
+
+// Closes a batch of (comment, commenter) pairs of an intro in the closing state, then the
+// intro and its counter once every comment is gone. Comments closed by an earlier batch
+// are skipped, though translations passed after them are still closed.
+#[allow(clippy::too_many_arguments)]
+fn close_intro_batch<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    reviewer: &AccountInfo<'a>,
+    pda_review: &AccountInfo<'a>,
+    pda_counter: &AccountInfo<'a>,
+    pda_stats: &AccountInfo<'a>,
+    mut account_data: StudentIntroState,
+    comment_accounts: &[AccountInfo<'a>],
+) -> ProgramResult {
+    let pda = *pda_review.key;
+
+    let (counter, _counter_bump) = Pubkey::find_program_address(
+        &[ns, pda.as_ref(), COMMENT_SEED], program_id
+    );
+    if counter != *pda_counter.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let counter_data = account_type::read::<StudentIntroCommentCounter>(pda_counter).unwrap();
+
+    let comment_pairs = comment_accounts.chunks_exact(2);
+    if !comment_pairs.remainder().is_empty() {
+        msg!("Comment accounts must be passed as (comment, commenter), (translation, author), (page, payer), \
+            (attributes, payer) or (mention, reviewer) pairs");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+
+    // A comment's translations follow it as (translation, author) pairs and close with it
+    let mut last_comment = None;
+    let mut open_translations = 0u8;
+    for pair in comment_pairs {
+        let (pda_comment, commenter) = (&pair[0], &pair[1]);
+
+        let discriminator = account_type::read::<String>(pda_comment).unwrap_or_default();
+        if discriminator == CommentTranslation::DISCRIMINATOR {
+            let comment = last_comment.ok_or_else(|| reason!(ReviewError::IncorrectAccountError,
+                "Translation {} must follow its comment", pda_comment.key))?;
+            translation::close_translation(program_id, &comment, pda_comment, commenter)?;
+            open_translations = open_translations.saturating_sub(1);
+            continue;
+        }
+        check_translations_closed(last_comment, open_translations)?;
+        last_comment = Some(*pda_comment.key);
+        open_translations = 0;
+
+        if pda_comment.lamports() == 0 {
+            msg!("Comment {} is already closed", pda_comment.key);
+            continue;
+        }
+        if pda_comment.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        // Comment pages ride along in the same batches as (page, payer) pairs
+        if discriminator == CommentPage::DISCRIMINATOR {
+            close_comment_page(pda_comment, commenter, &pda, &mut account_data)?;
+            continue;
+        }
+        // So do the intro's attributes
+        if discriminator == IntroAttributes::DISCRIMINATOR {
+            let attributes_data = attributes::load_attributes(program_id, pda_comment)?;
+            if attributes_data.intro != pda || attributes_data.payer != *commenter.key {
+                return Err(reason!(ReviewError::IncorrectAccountError,
+                    "Attributes {} must be closed to their payer along with their intro", pda_comment.key));
+            }
+            close_account(pda_comment, commenter)?;
+            continue;
+        }
+        // And the mentions it recorded, as (mention, reviewer) pairs
+        if discriminator == IntroMention::DISCRIMINATOR {
+            let mention_data = mention::load_mention(program_id, pda_comment)?;
+            if mention_data.from != pda || commenter.key != reviewer.key {
+                return Err(reason!(ReviewError::IncorrectAccountError,
+                    "Mention {} must be closed to the reviewer along with its intro", pda_comment.key));
+            }
+            close_account(pda_comment, commenter)?;
+            continue;
+        }
+
+        let comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+
+        if !comment_data.is_initialized()
+            || comment_data.discriminator != StudentIntroComment::DISCRIMINATOR
+        {
+            return Err(reason!(ReviewError::UninitializedAccount, "Comment account is not initialized"));
+        }
+        if comment_data.review != pda {
+            return Err(reason!(ReviewError::CommentIntroMismatch,
+                "Comment {} does not belong to this intro", pda_comment.key));
+        }
+        if rent_receiver(&comment_data, reviewer.key) != *commenter.key {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Rent refund must go to the commenter"));
+        }
+
+        close_account(pda_comment, commenter)?;
+        open_translations = comment_data.translations;
+
+        account_data.closed_comments = account_data.closed_comments
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        // Stats may have been initialized after this comment was created
+        stats_data.comments = stats_data.comments.saturating_sub(1);
+    }
+    check_translations_closed(last_comment, open_translations)?;
+
+    let comment_total = account_data.comment_total(&counter_data);
+    msg!("Closed {} of {} comments and {} of {} pages", account_data.closed_comments, comment_total,
+        account_data.closed_pages, account_data.comment_pages);
+
+    if account_data.closed_comments < comment_total || account_data.closed_pages < account_data.comment_pages {
+        // More comments remain, keep the intro around in the closing state
+        account_type::write(&account_data, pda_review)?;
+        account_type::write(&stats_data, pda_stats)?;
+        return Ok(());
+    }
+
+    stats_data.intros = stats_data.intros.saturating_sub(1);
+    account_type::write(&stats_data, pda_stats)?;
+
+    close_account(pda_counter, reviewer)?;
+    close_account(pda_review, reviewer)?;
+
+    msg!("Student intro closed");
+
+    Ok(())
+}
+
+fn close_comment_page(
+    pda_page: &AccountInfo,
+    payer: &AccountInfo,
+    review: &Pubkey,
+    account_data: &mut StudentIntroState,
+) -> ProgramResult {
+    let page_data = account_type::read::<CommentPage>(pda_page).unwrap();
+    if page_data.review != *review {
+        return Err(reason!(ReviewError::CommentIntroMismatch,
+            "Comment page {} does not belong to this intro", pda_page.key));
+    }
+    if page_data.payer != *payer.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Rent refund must go to the page payer"));
+    }
+
+    close_account(pda_page, payer)?;
+
+    account_data.closed_pages = account_data.closed_pages
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+// Views are counted off-chain and reported in batches by the configured indexer key
+pub fn increment_views(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    count: u64,
+) -> ProgramResult {
+    msg!("Adding {} views...", count);
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let indexer = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+    if !indexer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if config_data.indexer == Pubkey::default() || config_data.indexer != *indexer.key {
+        return Err(reason!(ReviewError::Unauthorized, "{} is not the configured indexer", indexer.key));
+    }
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.discriminator != StudentIntroState::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Account is not initialized"));
+    }
+
+    review_data.views = review_data.views
+        .checked_add(count)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    msg!("{} now has {} views", pda_review.key, review_data.views);
+
+    account_type::write(&review_data, pda_review)?;
+
+    Ok(())
+}
+
+// Comment slots already taken make AddComment fail on creating the comment PDA, whether
+// an old bug left the counters behind or someone funded the address out of band. Each
+// account passed must be the page's next slot and already exist; the page is moved past
+// it, and the intro's counter too when it holds a comment on this intro.
+pub fn resync_counter(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Resyncing comment counter...");
+
+    check_account_count(accounts, 7)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_page = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // The rest of the accounts are the taken comment slots, in order
+    let taken_slots = account_info_iter.as_slice();
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    if pda_review.owner != program_id || pda_counter.owner != program_id || pda_page.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (counter, _counter_bump) = Pubkey::find_program_address(
+        &[ns, pda_review.key.as_ref(), COMMENT_SEED], program_id
+    );
+    if counter != *pda_counter.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+    let mut counter_data = account_type::read::<StudentIntroCommentCounter>(pda_counter).unwrap();
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    let seed_version = review_data.comment_seed_version.unwrap_or(counter_data.seed_version);
+
+    let mut page_data = account_type::read::<CommentPage>(pda_page).unwrap();
+    let (page_pda, _page_bump) = Pubkey::find_program_address(
+        &[ns, pda_review.key.as_ref(), COMMENT_PAGE_SEED, page_data.page_index.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if !page_data.is_initialized() || page_pda != *pda_page.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for comment page PDA"));
+    }
+
+    for pda_comment in taken_slots {
+        if page_data.count >= CommentPage::CAPACITY {
+            return Err(reason!(ReviewError::CommentPageFull, "Comment page {} is full", page_data.page_index));
+        }
+
+        let (comment_pda, _comment_bump) = Pubkey::find_program_address(
+            &[
+                ns,
+                pda_page.key.as_ref(),
+                StudentIntroCommentCounter::versioned_slot_seed(seed_version, page_data.count).as_ref(),
+            ],
+            program_id,
+        );
+        if comment_pda != *pda_comment.key {
+            return Err(reason!(ReviewError::InvalidPDA,
+                "Expected comment slot {} at {}", page_data.count, comment_pda));
+        }
+        if pda_comment.lamports() == 0 {
+            msg!("Comment slot {} is free", page_data.count);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let comment_data = if pda_comment.owner == program_id {
+            account_type::read::<StudentIntroComment>(pda_comment).ok()
+        } else {
+            None
+        };
+        let holds_comment = match comment_data {
+            Some(comment_data) => comment_data.is_initialized()
+                && comment_data.discriminator == StudentIntroComment::DISCRIMINATOR
+                && comment_data.review == *pda_review.key,
+            None => false,
+        };
+        if holds_comment {
+            counter_data.counter = counter_data.counter
+                .checked_add(1)
+                .ok_or(ReviewError::ArithmeticOverflow)?;
+        }
+
+        msg!("Skipping taken comment slot {} on page {}", page_data.count, page_data.page_index);
+        page_data.count += 1;
+    }
+
+    msg!("Page {} now at {}, comment count {}", page_data.page_index, page_data.count, counter_data.counter);
+    account_type::write(&page_data, pda_page)?;
+    account_type::write(&counter_data, pda_counter)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_RESYNC_COUNTER)
+}
+
+// Moves an intro's comment slots over to the current seed scheme. Comments already made
+// stay where they are; only slots taken from now on are derived the new way, and the old
+// and new encodings of different slots never collide. The version is recorded on the intro
+// too, so its comments no longer read the counter for it.
+pub fn migrate_comment_seeds(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Migrating comment seeds...");
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.reviewer != *reviewer.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the intro owner can migrate its comment seeds"));
+    }
+
+    let mut counter_data = load_comment_counter(program_id, ns, pda_review.key, pda_counter)?;
+    let current = Some(StudentIntroCommentCounter::CURRENT_SEED_VERSION);
+    if counter_data.seed_version == StudentIntroCommentCounter::CURRENT_SEED_VERSION
+        && review_data.comment_seed_version == current
+    {
+        msg!("Comment seeds are already at version {}", counter_data.seed_version);
+        return Ok(());
+    }
+
+    resize_account(reviewer, pda_counter, system_program, StudentIntroCommentCounter::SIZE, &Rent::get()?)?;
+    msg!("Comment seeds moved from version {} to {}",
+        counter_data.seed_version, StudentIntroCommentCounter::CURRENT_SEED_VERSION);
+    counter_data.seed_version = StudentIntroCommentCounter::CURRENT_SEED_VERSION;
+    account_type::write(&counter_data, pda_counter)?;
+    review_data.comment_seed_version = current;
+    account_type::write(&review_data, pda_review)?;
+
+    Ok(())
+}
+
+// Accounts can fall below rent exemption after a realloc or a runtime rent change.
+// Anyone can pay the shortfall of a program account; the account is named in the
+// instruction data so a misordered account list can't send the lamports elsewhere.
+pub fn top_up_rent(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    account: Pubkey,
+) -> ProgramResult {
+    msg!("Topping up rent for {}...", account);
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let target = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *target.key != account {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Account passed is {}, not {}", target.key, account));
+    }
+    if target.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    let shortfall = Rent::get()?
+        .minimum_balance(target.data_len())
+        .saturating_sub(target.lamports());
+    if shortfall == 0 {
+        msg!("{} is already rent exempt", account);
+        return Ok(());
+    }
+
+    invoke(
+        &system_instruction::transfer(payer.key, target.key, shortfall),
+        &[payer.clone(), target.clone(), system_program.clone()],
+    )?;
+    msg!("Paid {} lamports of rent for {}", shortfall, account);
+
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    stats_data.rent_topped_up = stats_data.rent_topped_up
+        .checked_add(shortfall)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&stats_data, pda_stats)?;
+
+    events::emit(&IndexEvent::RentToppedUp {
+        account,
+        payer: *payer.key,
+        lamports: shortfall,
+    })
+}
+
+// Winds a deployment down: optionally drops the authority of the default mint and of
+// every cohort's, passed as (cohort, mint, mint_auth) before the co-signers, so no more
+// tokens can ever be minted, then marks the config read-only. Claims, deletes and rent
+// top-ups keep working so students can still collect what they're owed, and the surplus
+// left in the treasury can still be burned.
+pub fn finalize_course(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    revoke_mint_authority: bool,
+) -> ProgramResult {
+    msg!("Finalizing course...");
+
+    check_account_count(accounts, 8)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    // Each cohort's (cohort, mint, mint_auth) comes before the co-signers
+    let (cohort_mints, co_signers) = cohort::split_cohort_mints(program_id, account_info_iter.as_slice());
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin_quorum(&config_data, admin, co_signers)?;
+    let stats_data = load_global_stats(program_id, ns, pda_stats)?;
+
+    if revoke_mint_authority {
+        cohort::revoke_mint_authority(program_id, ns, "", token_mint, mint_auth, token_program)?;
+
+        // A cohort passed twice fails on its second revoke, so each counts once
+        for triple in cohort_mints.chunks_exact(3) {
+            let schedule = cohort::load_cohort(program_id, ns, &triple[0])?;
+            cohort::revoke_mint_authority(program_id, ns, &schedule.cohort, &triple[1], &triple[2], token_program)?;
+        }
+        let cohorts_revoked = (cohort_mints.len() / 3) as u64;
+        if cohorts_revoked < config_data.cohorts_created {
+            return Err(reason!(ReviewError::NotEnoughAccounts,
+                "Pass the mint of each of the {} cohorts to revoke, got {}", config_data.cohorts_created, cohorts_revoked));
+        }
+    }
+
+    config_data.finalized = true;
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_FINALIZE_COURSE)?;
+
+    events::emit(&IndexEvent::CourseFinalized {
+        intros: stats_data.intros,
+        comments: stats_data.comments,
+        tokens_minted: stats_data.tokens_minted,
+        active_wallets: stats_data.active_wallets,
+        rewards_pending: stats_data.rewards_pending,
+        mint_authority_revoked: revoke_mint_authority,
+        slot: Clock::get()?.slot,
+    })
+}
+
+// Burns tokens the treasury holds beyond what students can still claim, so leftover
+// supply goes away instead of to a staff wallet. Takes the admin quorum, like finalizing,
+// and keeps working once the course is finalized, which is when the surplus is known.
+pub fn burn_treasury_surplus(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    msg!("Burning {} surplus treasury tokens...", amount);
+
+    check_account_count(accounts, 9)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, ns, pda_config)?;
+    check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+    let stats_data = load_global_stats(program_id, ns, pda_stats)?;
+
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[ns, TREASURY_SEED], program_id);
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED], program_id);
+    if treasury_pda != *treasury.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect treasury account"));
+    }
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+    }
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Mint passed in and mint derived do not match"));
+    }
+
+    if amount == 0 {
+        msg!("Nothing to burn");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // Tokens that accrued to students are not surplus
+    let treasury_balance = TokenAccount::unpack(&treasury.data.borrow())?.amount;
+    if treasury_balance.saturating_sub(stats_data.rewards_pending) < amount {
+        return Err(reason!(ReviewError::InsufficientTreasury, "Treasury holds {} with {} reserved, cannot burn {}",
+            treasury_balance, stats_data.rewards_pending, amount));
+    }
+
+    burn_pda_tokens(program_id, ns, token_program, treasury, token_mint, mint_auth, mint_auth_bump, pda_stats,
+        admin.key, amount, IndexEvent::BURN_TREASURY_SURPLUS)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_BURN_TREASURY)
+}
+
+pub fn initialize_global_stats(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Initializing global stats...");
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (stats_pda, stats_bump) = Pubkey::find_program_address(&[ns, GLOBAL_STATS_SEED], program_id);
+    if stats_pda != *pda_stats.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            pda_stats.key,
+            rent.minimum_balance(GlobalStats::SIZE + account_type::TAG_LEN),
+            (GlobalStats::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[initializer.clone(), pda_stats.clone(), system_program.clone()],
+        &[&[ns, GLOBAL_STATS_SEED, &[stats_bump]]],
+    )?;
+
+    let mut stats_data = account_type::read::<GlobalStats>(pda_stats).unwrap();
+    if stats_data.is_initialized() {
+        msg!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    stats_data.discriminator = GlobalStats::DISCRIMINATOR.to_string();
+    stats_data.is_initialized = true;
+    account_type::write(&stats_data, pda_stats)?;
+
+    msg!("Global stats initialized");
+
+    Ok(())
+}
+
+pub fn export_page(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    cursor: u64,
+) -> ProgramResult {
+    msg!("Exporting intros from cursor {}", cursor);
+
+    check_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_stats = next_account_info(account_info_iter)?;
+
+    // The rest of the accounts are (intro index, intro, counter) triples starting at the cursor
+    let page_accounts = account_info_iter.as_slice();
+
+    let stats_data = load_global_stats(program_id, ns, pda_stats)?;
+
+    let page_triples = page_accounts.chunks_exact(3);
+    if !page_triples.remainder().is_empty() {
+        msg!("Page accounts must be passed as (intro index, intro, counter) triples");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if page_triples.len() > ExportPage::MAX_INTROS {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "At most {} intros fit in one page", ExportPage::MAX_INTROS));
+    }
+
+    let mut page = ExportPage {
+        next_cursor: cursor,
+        intros: Vec::with_capacity(page_triples.len()),
+    };
+
+    for triple in page_triples {
+        let (pda_intro_index, pda_review, pda_counter) = (&triple[0], &triple[1], &triple[2]);
+
+        if page.next_cursor >= stats_data.intros_created {
+            break;
+        }
+
+        let (intro_index_pda, _intro_index_bump) = Pubkey::find_program_address(
+            &[ns, INTRO_SEED, page.next_cursor.to_be_bytes().as_ref()],
+            program_id,
+        );
+        if intro_index_pda != *pda_intro_index.key {
+            return Err(reason!(ReviewError::InvalidPDA,
+                "Intro index {} is not at cursor {}", pda_intro_index.key, page.next_cursor));
+        }
+        if pda_intro_index.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let intro_index_data = account_type::read::<IntroIndex>(pda_intro_index).unwrap();
+        if intro_index_data.review != *pda_review.key {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Intro does not match index {}", page.next_cursor));
+        }
+
+        page.next_cursor += 1;
+
+        // Deleted intros keep their index slot, skip them
+        if pda_review.data_is_empty() || pda_review.owner != program_id {
+            continue;
+        }
+
+        let (counter, _counter_bump) = Pubkey::find_program_address(
+            &[ns, pda_review.key.as_ref(), COMMENT_SEED], program_id
+        );
+        if counter != *pda_counter.key {
+            return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+        }
+
+        let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+        let counter_data = account_type::read::<StudentIntroCommentCounter>(pda_counter).unwrap();
+
+        page.intros.push(IntroSummary {
+            review: *pda_review.key,
+            reviewer: review_data.reviewer,
+            name_hash: hash(review_data.name.as_bytes()).to_bytes(),
+            comment_count: review_data.comment_total(&counter_data).saturating_sub(review_data.closed_comments),
+        });
+    }
+
+    msg!("Exported {} intros, next cursor {}", page.intros.len(), page.next_cursor);
+    set_return_data(&page.try_to_vec()?);
+
+    Ok(())
+}
+
+// Sends the sender's reward tokens of one cohort to another wallet and records a receipt.
+// The cohort whose token is sent trails the accounts; without it the default cohort's is.
+pub fn transfer_reward(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    to: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    msg!("Transferring {} reward tokens to {}", amount, to);
+
+    check_account_count(accounts, 8)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let sender = next_account_info(account_info_iter)?;
+    let sender_ata = next_account_info(account_info_iter)?;
+    let recipient_ata = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let pda_receipt = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let pda_cohort = account_info_iter.next();
+
+    if !sender.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if amount == 0 {
+        msg!("Transfer amount must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    check_transfer_mint(program_id, ns, pda_cohort, token_mint)?;
+    if *sender_ata.key != get_associated_token_address(sender.key, token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect sender token account"));
+    }
+    if *recipient_ata.key != get_associated_token_address(&to, token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect recipient token account"));
+    }
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    invoke(
+        &transfer_checked(
+            token_program.key,
+            sender_ata.key,
+            token_mint.key,
+            recipient_ata.key,
+            sender.key,
+            &[],
+            amount,
+            REWARD_DECIMALS,
+        )?,
+        &[sender_ata.clone(), token_mint.clone(), recipient_ata.clone(), sender.clone()],
+    )?;
+
+    msg!("Writing transfer receipt");
+    let rent = Rent::get()?;
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, sender, sender.key, pda_user, system_program, &rent)?;
+
+    let (receipt_pda, receipt_bump) = Pubkey::find_program_address(
+        &[ns, sender.key.as_ref(), TRANSFER_SEED, user_data.transfer_count.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if receipt_pda != *pda_receipt.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            sender.key,
+            pda_receipt.key,
+            rent.minimum_balance(TransferReceipt::SIZE + account_type::TAG_LEN),
+            (TransferReceipt::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[sender.clone(), pda_receipt.clone(), system_program.clone()],
+        &[&[
+            ns,
+            sender.key.as_ref(),
+            TRANSFER_SEED,
+            user_data.transfer_count.to_be_bytes().as_ref(),
+            &[receipt_bump],
+        ]],
+    )?;
+
+    let receipt_data = TransferReceipt {
+        discriminator: TransferReceipt::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        from: *sender.key,
+        to,
+        index: user_data.transfer_count,
+        amount,
+        slot: Clock::get()?.slot,
+    };
+    account_type::write(&receipt_data, pda_receipt)?;
+
+    user_data.transfer_count = user_data.transfer_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&user_data, pda_user)?;
+
+    Ok(())
+}
+
+fn check_transfer_mint(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_cohort: Option<&AccountInfo>,
+    token_mint: &AccountInfo,
+) -> ProgramResult {
+    let cohort = match pda_cohort {
+        Some(pda_cohort) => cohort::load_cohort(program_id, ns, pda_cohort)?.cohort,
+        None => String::new(),
+    };
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(
+        &[ns, TOKEN_MINT_SEED, cohort.as_bytes()], program_id);
+    if *token_mint.key != mint_pda {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint for cohort {:?}", cohort));
+    }
+
+    Ok(())
+}
+
+pub fn initialize_treasury(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Initializing treasury...");
+
+    check_account_count(accounts, 7)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[ns, TREASURY_SEED], program_id);
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED], program_id);
+    let (mint_auth_pda, _mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED], program_id);
+
+    if treasury_pda != *treasury.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect treasury account"));
+    }
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint account"));
+    }
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect mint auth account"));
+    }
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+    if *sysvar_rent.key != RENT_PROGRAM_ID {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect rent program"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            treasury.key,
+            rent.minimum_balance(TokenAccount::LEN),
+            TokenAccount::LEN.try_into().unwrap(),
+            token_program.key,
+        ),
+        &[initializer.clone(), treasury.clone(), system_program.clone()],
+        &[&[ns, TREASURY_SEED, &[treasury_bump]]],
+    )?;
+
+    // The mint authority PDA owns the treasury so the program can pay out of it
+    invoke(
+        &initialize_account(
+            token_program.key,
+            treasury.key,
+            token_mint.key,
+            mint_auth.key,
+        )?,
+        &[treasury.clone(), token_mint.clone(), mint_auth.clone(), sysvar_rent.clone()],
+    )?;
+
+    msg!("Treasury initialized");
+
+    Ok(())
+}
+
+// Points a user's rewards at another wallet, e.g. a cold wallet, so the hot wallet that
+// pays fees doesn't have to hold tokens. The default key sends them back to the wallet.
+pub fn set_reward_destination(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    destination: Pubkey,
+) -> ProgramResult {
+    msg!("Setting reward destination to {}...", destination);
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let user = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !user.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    let mut user_data = load_or_create_user_record(
+        program_id, ns, user, user.key, pda_user, system_program, &Rent::get()?)?;
+    user_data.reward_destination = destination;
+    account_type::write(&user_data, pda_user)?;
+
+    msg!("Rewards for {} now go to {}", user.key, user_data.reward_owner());
+
+    Ok(())
+}
+
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Claiming rewards...");
+
+    check_account_count(accounts, 9)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let user = next_account_info(account_info_iter)?;
+    let pda_user = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+
+    if !user.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (user_pda, _user_bump) = Pubkey::find_program_address(&[ns, USER_SEED, user.key.as_ref()], program_id);
+    if user_pda != *pda_user.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for user record PDA"));
+    }
+    if pda_user.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[ns, TREASURY_SEED], program_id);
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED], program_id);
+
+    if treasury_pda != *treasury.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect treasury account"));
+    }
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+    }
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Mint passed in and mint derived do not match"));
+    }
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    let config_data = load_config(program_id, ns, pda_config)?;
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    let mut user_data = account_type::read::<UserRecord>(pda_user).unwrap();
+
+    if *user_ata.key != get_associated_token_address(&user_data.reward_owner(), token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect user token account"));
+    }
+
+    if user_data.pending_rewards == 0 {
+        return Err(reason!(ReviewError::NoPendingRewards, "Nothing to claim"));
+    }
+    if expire_pending_rewards(&config_data, &mut stats_data, &mut user_data, Clock::get()?.slot)? > 0 {
+        return Err(ReviewError::RewardsExpired.into());
+    }
+
+    let amount = user_data.pending_rewards;
+    msg!("Paying out {} pending rewards", amount);
+    invoke_signed(
+        &transfer(
+            token_program.key,
+            treasury.key,
+            user_ata.key,
+            mint_auth.key,
+            &[],
+            amount,
+        )?,
+        &[treasury.clone(), user_ata.clone(), mint_auth.clone()],
+        &[&[ns, TOKEN_AUTH_SEED, &[mint_auth_bump]]],
+    )?;
+
+    user_data.pending_rewards = 0;
+    stats_data.rewards_pending = stats_data.rewards_pending.saturating_sub(amount);
+
+    account_type::write(&user_data, pda_user)?;
+    account_type::write(&stats_data, pda_stats)?;
+
+    Ok(())
+}
+
+pub fn expire_rewards(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Expiring unclaimed rewards...");
+
+    let (accounts, pda_thread) = crank::split_thread(
+        program_id, ns, MaintenanceThread::TASK_EXPIRE_REWARDS, accounts);
+    check_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+
+    // The rest of the accounts are user records to check
+    let user_accounts = account_info_iter.as_slice();
+
+    let config_data = load_config(program_id, ns, pda_config)?;
+    let mut stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    let slot = Clock::get()?.slot;
+
+    if let Some(pda_thread) = pda_thread {
+        if !crank::start_run(program_id, pda_thread, slot)? {
+            return Ok(());
+        }
+    }
+
+    let mut total_expired: u64 = 0;
+    for pda_user in user_accounts {
+        if pda_user.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let mut user_data = account_type::read::<UserRecord>(pda_user).unwrap();
+        if user_data.discriminator != UserRecord::DISCRIMINATOR {
+            return Err(reason!(ReviewError::IncorrectAccountError, "{} is not a user record", pda_user.key));
+        }
+
+        let expired = expire_pending_rewards(&config_data, &mut stats_data, &mut user_data, slot)?;
+        if expired > 0 {
+            account_type::write(&user_data, pda_user)?;
+            total_expired = total_expired
+                .checked_add(expired)
+                .ok_or(ReviewError::ArithmeticOverflow)?;
+        }
+    }
+
+    msg!("Expired {} rewards across {} user records", total_expired, user_accounts.len());
+    account_type::write(&stats_data, pda_stats)?;
+
+    Ok(())
+}
+
+// Sets a deployment up in one transaction: config, the default cohort's mint, the
+// treasury, the banned phrase registry and the global stats, in the order they depend on
+// each other. Every account has to be new, so a half set up deployment isn't patched over.
+pub fn bootstrap(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    params: ConfigParams,
+) -> ProgramResult {
+    msg!("Bootstrapping deployment...");
+
+    check_account_count(accounts, 11)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let pda_banned = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    for account in [pda_config, pda_stats, token_mint, treasury, pda_banned, pda_audit] {
+        if !account.data_is_empty() || account.lamports() > 0 {
+            msg!("{} already exists, this deployment is already set up", account.key);
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+    }
+
+    initialize_config(program_id, ns, &[admin.clone(), pda_config.clone(), system_program.clone()], params)?;
+    initialize_token_mint(program_id, ns, &[
+        admin.clone(), token_mint.clone(), mint_auth.clone(), system_program.clone(), token_program.clone(),
+        sysvar_rent.clone(),
+    ])?;
+    initialize_treasury(program_id, ns, &[
+        admin.clone(), treasury.clone(), token_mint.clone(), mint_auth.clone(), system_program.clone(),
+        token_program.clone(), sysvar_rent.clone(),
+    ])?;
+    moderation::initialize_banned_phrases(program_id, ns, &[
+        admin.clone(), pda_config.clone(), pda_banned.clone(), system_program.clone(), pda_audit.clone(),
+    ])?;
+    initialize_global_stats(program_id, ns, &[admin.clone(), pda_stats.clone(), system_program.clone()])?;
+
+    msg!("Deployment bootstrapped");
+
+    Ok(())
+}
+
+pub fn initialize_config(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    params: ConfigParams,
+) -> ProgramResult {
+    msg!("Initializing config...");
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[ns, CONFIG_SEED], program_id);
+    if config_pda != *pda_config.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_config.key,
+            rent.minimum_balance(Config::SIZE + account_type::TAG_LEN),
+            (Config::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_config.clone(), system_program.clone()],
+        &[&[ns, CONFIG_SEED, &[config_bump]]],
+    )?;
+
+    let mut config_data = account_type::read::<Config>(pda_config).unwrap();
+    if config_data.is_initialized() {
+        msg!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    config_data.discriminator = Config::DISCRIMINATOR.to_string();
+    config_data.is_initialized = true;
+    config_data.admin = *admin.key;
+    config_data.namespace = std::str::from_utf8(StudentIntroInstruction::seed_namespace(ns))
+        .map_err(|_| ProgramError::InvalidInstructionData)?
+        .to_string();
+    apply_config_params(&mut config_data, params);
+    account_type::write(&config_data, pda_config)?;
+
+    msg!("Config initialized for namespace {:?}, admin: {}", config_data.namespace, admin.key);
+
+    Ok(())
+}
+
+pub fn update_config(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    params: ConfigParams,
+) -> ProgramResult {
+    msg!("Updating config...");
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+
+    if config_data.config_change_delay_slots != 0 && changes_economics(&config_data, &params) {
+        return Err(reason!(ReviewError::ConfigChangeTimelocked,
+            "Propose the change instead, it can apply {} slots later", config_data.config_change_delay_slots));
+    }
+
+    apply_config_params(&mut config_data, params);
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_UPDATE_CONFIG)
+}
+
+// Announces a full set of settings that ApplyConfigChange can make live once the current
+// config_change_delay_slots have passed. Proposing again replaces the pending settings
+// and restarts the delay. Proposals made before the account was grown to its current
+// size are grown on the next proposal, at the admin's cost.
+pub fn propose_config_change(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    params: ConfigParams,
+) -> ProgramResult {
+    msg!("Proposing config change...");
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_pending = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+
+    let (pending_pda, pending_bump) = Pubkey::find_program_address(&[ns, PENDING_CONFIG_SEED], program_id);
+    if pending_pda != *pda_pending.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for pending config PDA"));
+    }
+    if pda_pending.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                admin.key,
+                pda_pending.key,
+                rent.minimum_balance(PendingConfigChange::SIZE + account_type::TAG_LEN),
+                (PendingConfigChange::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[admin.clone(), pda_pending.clone(), system_program.clone()],
+            &[&[ns, PENDING_CONFIG_SEED, &[pending_bump]]],
+        )?;
+    } else if pda_pending.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    } else if account_type::body_len(pda_pending) < PendingConfigChange::SIZE {
+        resize_account(admin, pda_pending, system_program, PendingConfigChange::SIZE, &Rent::get()?)?;
+    }
+
+    let slot = Clock::get()?.slot;
+    let pending_data = PendingConfigChange {
+        discriminator: PendingConfigChange::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        proposer: *admin.key,
+        params,
+        proposed_slot: slot,
+        apply_after_slot: slot.saturating_add(config_data.config_change_delay_slots),
+        base: config_params(&config_data),
+    };
+    account_type::write(&pending_data, pda_pending)?;
+    msg!("Config change can apply from slot {}", pending_data.apply_after_slot);
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_PROPOSE_CONFIG)?;
+
+    events::emit(&IndexEvent::ConfigChangeProposed {
+        proposer: *admin.key,
+        apply_after_slot: pending_data.apply_after_slot,
+    })
+}
+
+// Makes the pending settings live once their delay has passed and closes the proposal,
+// its rent going to the admin applying it. Only the settings the proposal changes are
+// applied, so direct updates made while it waited aren't reverted.
+pub fn apply_config_change(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Applying config change...");
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_pending = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+
+    let (pending_pda, _pending_bump) = Pubkey::find_program_address(&[ns, PENDING_CONFIG_SEED], program_id);
+    if pending_pda != *pda_pending.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for pending config PDA"));
+    }
+    if pda_pending.owner != program_id {
+        return Err(reason!(ReviewError::UninitializedAccount, "No config change is pending"));
+    }
+    let pending_data = account_type::read::<PendingConfigChange>(pda_pending).unwrap();
+    if !pending_data.is_initialized || pending_data.discriminator != PendingConfigChange::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "No config change is pending"));
+    }
+    check_apply_slot(&pending_data, Clock::get()?.slot)?;
+
+    let params = merge_proposal(config_params(&config_data), &pending_data.base, pending_data.params);
+    apply_config_params(&mut config_data, params);
+    close_account(pda_pending, admin)?;
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_APPLY_CONFIG)
+}
+
+fn check_apply_slot(pending_data: &PendingConfigChange, slot: u64) -> ProgramResult {
+    if slot < pending_data.apply_after_slot {
+        return Err(reason!(ReviewError::ConfigChangeTimelocked,
+            "Config change can apply from slot {}, it is slot {}", pending_data.apply_after_slot, slot));
+    }
+
+    Ok(())
+}
+
+// Whether the params touch how rewards are paid, their amounts and caps, the penalty
+// taking them back, fees and where they go, or the delay guarding all of these
+fn changes_economics(config_data: &Config, params: &ConfigParams) -> bool {
+    config_data.delete_penalty_window_slots != params.delete_penalty_window_slots
+        || config_data.accrue_rewards != params.accrue_rewards
+        || config_data.claim_window_slots != params.claim_window_slots
+        || config_data.unique_commenter_bonus != params.unique_commenter_bonus
+        || config_data.acknowledge_reward != params.acknowledge_reward
+        || config_data.min_reward_comment_chars != params.min_reward_comment_chars
+        || config_data.reaction_reward_threshold != params.reaction_reward_threshold
+        || config_data.reaction_reward != params.reaction_reward
+        || config_data.bonus_supply_cap != params.bonus_supply_cap
+        || config_data.creation_fee_lamports != params.creation_fee_lamports
+        || config_data.creation_fee_usd_cents != params.creation_fee_usd_cents
+        || config_data.fee_receiver != params.fee_receiver
+        || config_data.price_feed != params.price_feed
+        || config_data.max_price_staleness_slots != params.max_price_staleness_slots
+        || config_data.max_price_confidence_bps != params.max_price_confidence_bps
+        || config_data.config_change_delay_slots != params.config_change_delay_slots
+        || config_data.migration_bounty_lamports != params.migration_bounty_lamports
+}
+
+// Stops or restarts every reward mint. Halting takes only the admin, so emission can be
+// stopped the moment an exploit is found; restarting takes the admin quorum.
+pub fn set_rewards_halted(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    halted: bool,
+) -> ProgramResult {
+    msg!("Setting reward minting halted to {}...", halted);
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    if halted {
+        check_admin(&config_data, admin)?;
+    } else {
+        check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+    }
+
+    config_data.rewards_halted = halted;
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_SET_REWARDS_HALTED)
+}
+
+// Replaces the co-admins and the threshold. This is itself a high-value action, so the
+// current set has to meet its current threshold to change it.
+pub fn set_admins(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    co_admins: Vec<Pubkey>,
+    threshold: u8,
+) -> ProgramResult {
+    msg!("Setting {} co-admins with a threshold of {}...", co_admins.len(), threshold);
+
+    check_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+
+    if co_admins.len() > Config::MAX_CO_ADMINS {
+        return Err(reason!(ReviewError::InvalidDataLength, "At most {} co-admins fit", Config::MAX_CO_ADMINS));
+    }
+    for (i, co_admin) in co_admins.iter().enumerate() {
+        if *co_admin == config_data.admin || co_admins[..i].contains(co_admin) {
+            msg!("{} is listed more than once in the admin set", co_admin);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    if threshold as usize > co_admins.len() + 1 {
+        msg!("Threshold {} is more than the {} admins in the set", threshold, co_admins.len() + 1);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.co_admins = co_admins;
+    config_data.admin_threshold = threshold;
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_SET_ADMINS)
+}
+
+// The config's current settings, as UpdateConfig would take them
+fn config_params(config_data: &Config) -> ConfigParams {
+    ConfigParams {
+        delete_penalty_window_slots: config_data.delete_penalty_window_slots,
+        unique_commenter_bonus: config_data.unique_commenter_bonus,
+        accrue_rewards: config_data.accrue_rewards,
+        claim_window_slots: config_data.claim_window_slots,
+        creation_fee_lamports: config_data.creation_fee_lamports,
+        fee_receiver: config_data.fee_receiver,
+        price_feed: config_data.price_feed,
+        creation_fee_usd_cents: config_data.creation_fee_usd_cents,
+        max_price_staleness_slots: config_data.max_price_staleness_slots,
+        max_price_confidence_bps: config_data.max_price_confidence_bps,
+        randomness_program: config_data.randomness_program,
+        indexer: config_data.indexer,
+        min_reward_comment_chars: config_data.min_reward_comment_chars,
+        acknowledge_reward: config_data.acknowledge_reward,
+        edit_window_slots: config_data.edit_window_slots,
+        reaction_reward_threshold: config_data.reaction_reward_threshold,
+        reaction_reward: config_data.reaction_reward,
+        bonus_supply_cap: config_data.bonus_supply_cap,
+        config_change_delay_slots: config_data.config_change_delay_slots,
+        migration_bounty_lamports: config_data.migration_bounty_lamports,
+        anonymous_window_slots: config_data.anonymous_window_slots,
+        anonymous_comments_per_window: config_data.anonymous_comments_per_window,
+        intro_hook_program: config_data.intro_hook_program,
+        halt_on_supply_mismatch: config_data.halt_on_supply_mismatch,
+    }
+}
+
+// Takes the settings a proposal changed from its base and keeps the current ones for
+// the rest
+fn merge_proposal(current: ConfigParams, base: &ConfigParams, proposed: ConfigParams) -> ConfigParams {
+    let mut merged = current;
+    macro_rules! take_changed {
+        ($($field:ident),* $(,)?) => {
+            $(if proposed.$field != base.$field {
+                merged.$field = proposed.$field;
+            })*
+        };
+    }
+    take_changed!(
+        delete_penalty_window_slots, unique_commenter_bonus, accrue_rewards, claim_window_slots,
+        creation_fee_lamports, fee_receiver, price_feed, creation_fee_usd_cents, max_price_staleness_slots,
+        max_price_confidence_bps, randomness_program, indexer, min_reward_comment_chars, acknowledge_reward,
+        edit_window_slots, reaction_reward_threshold, reaction_reward, bonus_supply_cap,
+        config_change_delay_slots, migration_bounty_lamports, anonymous_window_slots,
+        anonymous_comments_per_window, intro_hook_program, halt_on_supply_mismatch,
+    );
+    merged
+}
+
+fn apply_config_params(config_data: &mut Config, params: ConfigParams) {
+    config_data.delete_penalty_window_slots = params.delete_penalty_window_slots;
+    config_data.unique_commenter_bonus = params.unique_commenter_bonus;
+    config_data.accrue_rewards = params.accrue_rewards;
+    config_data.claim_window_slots = params.claim_window_slots;
+    config_data.creation_fee_lamports = params.creation_fee_lamports;
+    config_data.fee_receiver = params.fee_receiver;
+    config_data.price_feed = params.price_feed;
+    config_data.creation_fee_usd_cents = params.creation_fee_usd_cents;
+    config_data.max_price_staleness_slots = params.max_price_staleness_slots;
+    config_data.max_price_confidence_bps = params.max_price_confidence_bps;
+    config_data.randomness_program = params.randomness_program;
+    config_data.indexer = params.indexer;
+    config_data.min_reward_comment_chars = params.min_reward_comment_chars;
+    config_data.acknowledge_reward = params.acknowledge_reward;
+    config_data.edit_window_slots = params.edit_window_slots;
+    config_data.reaction_reward_threshold = params.reaction_reward_threshold;
+    config_data.reaction_reward = params.reaction_reward;
+    config_data.bonus_supply_cap = params.bonus_supply_cap;
+    config_data.config_change_delay_slots = params.config_change_delay_slots;
+    config_data.migration_bounty_lamports = params.migration_bounty_lamports;
+    config_data.anonymous_window_slots = params.anonymous_window_slots;
+    config_data.anonymous_comments_per_window = params.anonymous_comments_per_window;
+    config_data.intro_hook_program = params.intro_hook_program;
+    config_data.halt_on_supply_mismatch = params.halt_on_supply_mismatch;
+}
+
+pub(crate) fn load_config(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_config: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[ns, CONFIG_SEED], program_id);
+    if config_pda != *pda_config.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for config PDA"));
+    }
+    if pda_config.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let config_data = account_type::read::<Config>(pda_config).unwrap();
+    if !config_data.is_initialized() {
+        return Err(reason!(ReviewError::UninitializedAccount, "Config not initialized"));
+    }
+
+    Ok(config_data)
+}
+
+// Config for a handler that changes content or settings, which a finalized course no
+// longer allows
+pub(crate) fn load_writable_config(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_config: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    let config_data = load_config(program_id, ns, pda_config)?;
+    if config_data.finalized {
+        return Err(reason!(ReviewError::CourseFinalized, "Course is finalized, nothing can be changed"));
+    }
+
+    Ok(config_data)
+}
+
+// Handlers check this before reading any account, so a short account list fails with
+// the count the instruction needs instead of NotEnoughAccountKeys from whichever lookup
+// ran out. Optional and trailing accounts aren't counted.
+pub(crate) fn check_account_count(accounts: &[AccountInfo], expected: usize) -> ProgramResult {
+    if accounts.len() < expected {
+        return Err(reason!(ReviewError::NotEnoughAccounts,
+            "Instruction needs at least {} accounts but got {}", expected, accounts.len()));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_admin(config_data: &Config, admin: &AccountInfo) -> ProgramResult {
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if config_data.admin != *admin.key {
+        return Err(reason!(ReviewError::Unauthorized, "{} is not the config admin", admin.key));
+    }
+
+    Ok(())
+}
+
+// High-value actions take the other admins' signatures as trailing accounts. The admin
+// still signs as the actor, and each signer of the admin set counts once toward the
+// threshold.
+pub(crate) fn check_admin_quorum(
+    config_data: &Config,
+    admin: &AccountInfo,
+    co_signers: &[AccountInfo],
+) -> ProgramResult {
+    check_admin(config_data, admin)?;
+
+    let threshold = config_data.admin_threshold.max(1) as usize;
+    let mut signed = vec![*admin.key];
+    for co_signer in co_signers {
+        if co_signer.is_signer
+            && config_data.co_admins.contains(co_signer.key)
+            && !signed.contains(co_signer.key)
+        {
+            signed.push(*co_signer.key);
+        }
+    }
+
+    if signed.len() < threshold {
+        return Err(reason!(ReviewError::AdminQuorumNotMet, "{} of {} required admins signed", signed.len(), threshold));
+    }
 
-    let (pda, _bump_seed) = Pubkey::find_program_address(&[
-        initializer.key.as_ref(),
-        account_data.name.as_bytes().as_ref(),
-    ], program_id);
+    Ok(())
+}
 
-    if pda != *pda_account.key {
-        msg!("Invalid seeds for PDA");
-        return Err(ReviewError::InvalidPDA.into());
+pub(crate) fn load_global_stats(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_stats: &AccountInfo,
+) -> Result<GlobalStats, ProgramError> {
+    let (stats_pda, _stats_bump) = Pubkey::find_program_address(&[ns, GLOBAL_STATS_SEED], program_id);
+    if stats_pda != *pda_stats.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for global stats PDA"));
+    }
+    if pda_stats.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
     }
 
-    if !account_data.is_initialized {
-        msg!("Account is not initialized");
-        return Err(ReviewError::UninitializedAccount.into());
+    let mut stats_data = account_type::read::<GlobalStats>(pda_stats).unwrap();
+    if !stats_data.is_initialized() {
+        return Err(reason!(ReviewError::UninitializedAccount, "Global stats not initialized"));
     }
+    if account_type::body_len(pda_stats) >= GlobalStats::SIZE {
+        snapshot::roll_stats(&mut stats_data, Clock::get()?.epoch);
+    }
+
+    Ok(stats_data)
+}
 
-    let total_len: usize = 1 + (4 + account_data.name.len()) + (4 + message.len());
-    if total_len > 1000 {
-        msg!("Data length is larger than 1000 bytes");
-        return Err(ReviewError::InvalidDataLength.into());
+// Loads the wallet's user record, creating it on the wallet's first action
+pub(crate) fn load_or_create_user_record<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    payer: &AccountInfo<'a>,
+    wallet: &Pubkey,
+    pda_user: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> Result<UserRecord, ProgramError> {
+    let (user_pda, user_bump) = Pubkey::find_program_address(
+        &[ns, USER_SEED, wallet.as_ref()], program_id
+    );
+    if user_pda != *pda_user.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for user record PDA"));
     }
 
-    account_data.name = name;
-    account_data.message = message;
+    if pda_user.data_is_empty() {
+        msg!("Creating user record");
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_user.key,
+                rent.minimum_balance(UserRecord::SIZE + account_type::TAG_LEN),
+                (UserRecord::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[payer.clone(), pda_user.clone(), system_program.clone()],
+            &[&[ns, USER_SEED, wallet.as_ref(), &[user_bump]]],
+        )?;
+    } else if pda_user.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
 
-    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    let mut user_data = account_type::read::<UserRecord>(pda_user).unwrap();
+    if !user_data.is_initialized() {
+        user_data.discriminator = UserRecord::DISCRIMINATOR.to_string();
+        user_data.is_initialized = true;
+        user_data.wallet = *wallet;
+    }
 
-    Ok(())
+    Ok(user_data)
 }
 
-pub fn add_student_intro_comment(
+// The record load_or_create_user_record would create for a first-time wallet
+fn preview_user_record(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    comment: String
-) -> ProgramResult {
-    msg!("Adding comment ...");
-    msg!("Comment: {}",comment);
+    ns: &[u8],
+    wallet: &Pubkey,
+    pda_user: &AccountInfo,
+) -> Result<UserRecord, ProgramError> {
+    let (user_pda, _user_bump) = Pubkey::find_program_address(
+        &[ns, USER_SEED, wallet.as_ref()], program_id
+    );
+    if user_pda != *pda_user.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for user record PDA"));
+    }
 
-    let account_info_iter = &mut accounts.iter();
+    let mut user_data = try_from_slice_unchecked::<UserRecord>(&[0u8; UserRecord::SIZE]).unwrap();
+    user_data.discriminator = UserRecord::DISCRIMINATOR.to_string();
+    user_data.is_initialized = true;
+    user_data.wallet = *wallet;
 
-    let commenter = next_account_info(account_info_iter)?;
-    let pda_review = next_account_info(account_info_iter)?;
-    let pda_counter = next_account_info(account_info_iter)?;
-    let pda_comment = next_account_info(account_info_iter)?;
+    Ok(user_data)
+}
 
-    let token_mint = next_account_info(account_info_iter)?;
-    let mint_auth = next_account_info(account_info_iter)?;
-    let user_ata = next_account_info(account_info_iter)?;
+// Accounts every reward payout needs, checked once by the handler
+struct RewardAccounts<'a, 'b> {
+    token_program: &'b AccountInfo<'a>,
+    token_mint: &'b AccountInfo<'a>,
+    mint_auth: &'b AccountInfo<'a>,
+    mint_auth_bump: u8,
+    treasury: &'b AccountInfo<'a>,
+    ns: &'b [u8],
+    schedule: RewardSchedule,
+}
 
-    let system_program = next_account_info(account_info_iter)?;
+impl<'a, 'b> RewardAccounts<'a, 'b> {
+    // The mint and its authority are the ones of the cohort the schedule was loaded from
+    fn check(
+        program_id: &Pubkey,
+        ns: &'b [u8],
+        schedule: RewardSchedule,
+        token_program: &'b AccountInfo<'a>,
+        token_mint: &'b AccountInfo<'a>,
+        mint_auth: &'b AccountInfo<'a>,
+        treasury: &'b AccountInfo<'a>,
+    ) -> Result<Self, ProgramError> {
+        msg!("Deriving mint authority");
+        let cohort = schedule.cohort.as_bytes();
+        let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED, cohort], program_id);
+        let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED, cohort], program_id);
 
-    let token_program = next_account_info(account_info_iter)?;
+        if *token_mint.key != mint_pda {
+            return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+        }
+        if *mint_auth.key != mint_auth_pda {
+            return Err(reason!(ReviewError::InvalidPDA, "Mint passed in and mint derived do not match"));
+        }
+        check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
 
+        Ok(RewardAccounts {
+            token_program,
+            token_mint,
+            mint_auth,
+            mint_auth_bump,
+            treasury,
+            ns,
+            schedule,
+        })
+    }
 
-    let mut counter_data = try_from_slice_unchecked::<StudentIntroCommentCounter>(
-        &pda_counter.data.borrow()).unwrap();
-    
-    let account_len = StudentIntroComment::get_account_size(comment.clone());
-    
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(account_len);
+    // The treasury only receives rewards in accrue-and-claim mode. It holds the default
+    // cohort's mint, and pending rewards don't say which mint they are owed in.
+    fn check_treasury(&self, program_id: &Pubkey, ns: &[u8], config_data: &Config) -> ProgramResult {
+        if config_data.accrue_rewards {
+            if !self.schedule.cohort.is_empty() {
+                return Err(reason!(ReviewError::CohortCannotAccrue,
+                    "Cohort {} pays rewards directly", self.schedule.cohort));
+            }
+            let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[ns, TREASURY_SEED], program_id);
+            if treasury_pda != *self.treasury.key {
+                return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect treasury account"));
+            }
+        }
 
-    let (pda, bump_seed) = Pubkey::find_program_address(
-        &[pda_review.key.as_ref(),
-        counter_data.counter.to_be_bytes().as_ref()],
-        program_id
-    );
-    if pda != *pda_comment.key {
-        msg!("Invalid seeds for PDA");
-        return Err(ReviewError::InvalidPDA.into());
+        Ok(())
+    }
+}
+
+// Mints a reward straight to the recipient's ATA or, in accrue-and-claim mode, into the
+// treasury where it waits as pending on the recipient's user record until claimed
+fn pay_reward<'a>(
+    rewards: &RewardAccounts<'a, '_>,
+    config_data: &Config,
+    stats_data: &mut GlobalStats,
+    recipient: &mut UserRecord,
+    recipient_ata: &AccountInfo<'a>,
+    amount: u64,
+    slot: u64,
+) -> ProgramResult {
+    if config_data.rewards_halted {
+        msg!("Reward minting is halted, {} not minted", amount);
+        return Ok(());
     }
 
+    let destination = if config_data.accrue_rewards {
+        rewards.treasury
+    } else {
+        check_reward_ata(recipient_ata, rewards.token_mint, &recipient.reward_owner())?;
+        recipient_ata
+    };
+
     invoke_signed(
-        &system_instruction::create_account(
-        commenter.key, 
-        pda_comment.key, 
-        rent_lamports, 
-        account_len.try_into().unwrap(), 
-        program_id,
-        ),
-        &[commenter.clone(),
-        pda_comment.clone(),
-        system_program.clone()],
-    &[&[pda_review.key.as_ref(),
-    counter_data.counter.to_be_bytes().as_ref(),
-    &[bump_seed]]],
+        &mint_to(
+            rewards.token_program.key,
+            rewards.token_mint.key,
+            destination.key,
+            rewards.mint_auth.key,
+            &[],
+            amount,
+        )?,
+        &[rewards.token_mint.clone(), destination.clone(), rewards.mint_auth.clone()],
+        &[&[rewards.ns, TOKEN_AUTH_SEED, rewards.schedule.cohort.as_bytes(), &[rewards.mint_auth_bump]]],
     )?;
 
-    msg!("Created comment account.");
+    stats_data.tokens_minted = stats_data.tokens_minted
+        .checked_add(amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
 
-    let mut comment_data = try_from_slice_unchecked::<StudentIntroComment>(
-        &pda_comment.data.borrow()).unwrap();
+    if config_data.accrue_rewards {
+        expire_pending_rewards(config_data, stats_data, recipient, slot)?;
 
-    msg!("Checking if comment account is already initialized.");
-    if comment_data.is_initialized() {
-        msg!("Account already initialized");
-        return Err(ProgramError::AccountAlreadyInitialized);
+        if recipient.pending_rewards == 0 {
+            recipient.pending_since_slot = slot;
+        }
+        recipient.pending_rewards = recipient.pending_rewards
+            .checked_add(amount)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        stats_data.rewards_pending = stats_data.rewards_pending
+            .checked_add(amount)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        msg!("Accrued {}, {} pending for {}", amount, recipient.pending_rewards, recipient.wallet);
     }
 
-    comment_data.discriminator = StudentIntroComment::DISCRIMINATOR.to_string();
-    comment_data.review = *pda_review.key;
-    comment_data.commenter = *commenter.key;
-    comment_data.comment = comment;
-    comment_data.is_initialized = true;
-    comment_data.serialize(&mut &mut pda_comment.data.borrow_mut()[..])?;
+    Ok(())
+}
 
-    msg!("Comment count: {}", counter_data.counter);
+fn pay_group_share<'a>(
+    rewards: &RewardAccounts<'a, '_>,
+    config_data: &Config,
+    stats_data: &mut GlobalStats,
+    group_vault: &AccountInfo<'a>,
+    group: &Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    if config_data.rewards_halted {
+        msg!("Reward minting is halted, {} not minted", amount);
+        return Ok(());
+    }
 
-    counter_data.counter += 1;
-    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+    if *group_vault.key != get_associated_token_address(group, rewards.token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect group vault account"));
+    }
+    check_reward_ata(group_vault, rewards.token_mint, group)?;
 
-    // Mint tokens here
-    msg!("deriving mint authority");
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
-    let (mint_auth_pda, mint_auth_bump) =
-        Pubkey::find_program_address(&[b"token_auth"], program_id);
+    invoke_signed(
+        &mint_to(
+            rewards.token_program.key,
+            rewards.token_mint.key,
+            group_vault.key,
+            rewards.mint_auth.key,
+            &[],
+            amount,
+        )?,
+        &[rewards.token_mint.clone(), group_vault.clone(), rewards.mint_auth.clone()],
+        &[&[rewards.ns, TOKEN_AUTH_SEED, rewards.schedule.cohort.as_bytes(), &[rewards.mint_auth_bump]]],
+    )?;
 
-    if *token_mint.key != mint_pda {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
+    stats_data.tokens_minted = stats_data.tokens_minted
+        .checked_add(amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+// An address that matches the ATA derivation may still not exist or be usable, so check
+// the account's state before minting into it
+fn check_reward_ata(recipient_ata: &AccountInfo, token_mint: &AccountInfo, wallet: &Pubkey) -> ProgramResult {
+    if *recipient_ata.owner != TOKEN_PROGRAM_ID {
+        return Err(reason!(ReviewError::IncorrectAccountError,
+            "Token account {} is not owned by the token program", recipient_ata.key));
     }
 
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Mint passed in and mint derived do not match");
-        return Err(ReviewError::InvalidPDA.into());
+    let ata_data = TokenAccount::unpack_unchecked(&recipient_ata.data.borrow())?;
+    if !ata_data.is_initialized() {
+        return Err(reason!(ReviewError::UninitializedAccount,
+            "Token account {} is not initialized", recipient_ata.key));
+    }
+    if ata_data.mint != *token_mint.key || ata_data.owner != *wallet {
+        return Err(reason!(ReviewError::IncorrectAccountError,
+            "Token account {} does not hold the reward mint for {}", recipient_ata.key, wallet));
     }
 
-    if *user_ata.key != get_associated_token_address(commenter.key, token_mint.key) {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
+    Ok(())
+}
+
+// Drops pending rewards that outlived the claim window. The tokens already sit in the
+// treasury, so expiring them only releases the claim. Returns the amount expired.
+fn expire_pending_rewards(
+    config_data: &Config,
+    stats_data: &mut GlobalStats,
+    user_data: &mut UserRecord,
+    slot: u64,
+) -> Result<u64, ProgramError> {
+    if user_data.pending_rewards == 0
+        || config_data.claim_window_slots == 0
+        || slot <= user_data.pending_since_slot.saturating_add(config_data.claim_window_slots)
+    {
+        return Ok(0);
     }
 
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(ReviewError::IncorrectAccountError.into());
+    let expired = user_data.pending_rewards;
+    user_data.pending_rewards = 0;
+    // Stats may have been initialized after these rewards accrued
+    stats_data.rewards_pending = stats_data.rewards_pending.saturating_sub(expired);
+    stats_data.rewards_expired = stats_data.rewards_expired
+        .checked_add(expired)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    msg!("Expired {} unclaimed rewards for {}", expired, user_data.wallet);
+
+    Ok(expired)
+}
+
+// Accounts the caller supplies as data accounts must never be programs
+pub(crate) fn check_not_executable(accounts: &[&AccountInfo]) -> ProgramResult {
+    for account in accounts {
+        if account.executable {
+            return Err(reason!(ReviewError::IncorrectAccountError,
+                "{} is executable but was passed as a data account", account.key));
+        }
     }
 
-    msg!("Minting 5 tokens to User associated token account");
-    invoke_signed(
-        // Instruction
-        &mint_to(
-            token_program.key,
-            token_mint.key,
-            user_ata.key,
-            mint_auth.key,
-            &[],
-            5 * LAMPORTS_PER_SOL,
-        )?,
-        // Account_infos
-        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
-        // Seeds
-        &[&[b"token_auth", &[mint_auth_bump]]],
-    )?;
+    Ok(())
+}
+
+// A program account must be the expected program and actually be deployed
+pub(crate) fn check_program_account(account: &AccountInfo, program: &Pubkey) -> ProgramResult {
+    if account.key != program || !account.executable {
+        return Err(reason!(ReviewError::IncorrectAccountError,
+            "Expected the {} program, got {}", program, account.key));
+    }
 
+    Ok(())
+}
 
+pub(crate) fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let destination_lamports = destination.lamports();
+    **destination.lamports.borrow_mut() = destination_lamports
+        .checked_add(account.lamports())
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    **account.lamports.borrow_mut() = 0;
+    account.data.borrow_mut().fill(0);
 
     Ok(())
 }
 
 pub fn initialize_token_mint(
     program_id: &Pubkey,
+    ns: &[u8],
     accounts: &[AccountInfo]
 ) -> ProgramResult {
+    check_account_count(accounts, 6)?;
     let account_info_iter = &mut accounts.iter();
 
     let initializer = next_account_info(account_info_iter)?;
@@ -432,39 +4265,32 @@ pub fn initialize_token_mint(
     let sysvar_rent = next_account_info(account_info_iter)?;
 
     let (mint_pda, mint_bump) = Pubkey::find_program_address(
-        &[b"token_mint"], program_id
+        &[ns, TOKEN_MINT_SEED], program_id
     );
     
-    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(
-        &[b"token_auth"], program_id
+    let (mint_auth_pda, _mint_auth_bump) = Pubkey::find_program_address(
+        &[ns, TOKEN_AUTH_SEED], program_id
     );
 
     msg!("Token mint: {:?}", mint_pda);
     msg!("Mint authority: {:?}", mint_auth_pda);
 
     if mint_pda != *token_mint.key {
-        msg!("Incorrect token mint account");
-        return Err(ReviewError::IncorrectAccountError.into());
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint account"));
     }
     if mint_auth_pda != *mint_auth.key {
-        msg!("Incorrect mint auth account");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(ReviewError::IncorrectAccountError.into());
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect mint auth account"));
     }
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
     if *system_program.key != SYSTEM_PROGRAM_ID {
-        msg!("Incorrect system program");
-        return  Err(ReviewError::IncorrectAccountError.into());
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect system program"));
     }
     if *sysvar_rent.key != RENT_PROGRAM_ID {
-        msg!("Incorrect rent program");
-        return Err(ReviewError::IncorrectAccountError.into());
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect rent program"));
     }
 
     let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(82);
+    let rent_lamports = rent.minimum_balance(Mint::LEN);
 
     // Create the token mint PDA
     invoke_signed(
@@ -472,7 +4298,7 @@ pub fn initialize_token_mint(
             initializer.key,
             token_mint.key,
             rent_lamports,
-            82, // Size of the token mint account
+            Mint::LEN as u64, // Size of the token mint account
             token_program.key,
         ),
         // Accounts we're reading from or writing to
@@ -482,7 +4308,7 @@ pub fn initialize_token_mint(
             system_program.clone(),
         ],
         // Seeds for out token mint account
-    &[&[b"token_mint", &[mint_bump]]],
+    &[&[ns, TOKEN_MINT_SEED, &[mint_bump]]],
     )?;
 
     msg!("Created token mint account");
@@ -494,12 +4320,12 @@ pub fn initialize_token_mint(
             token_mint.key,
             mint_auth.key,
             Option::None, // Freeze authority - we don't want anyone to be able to freeze!
-            9, // Number of decimals
+            REWARD_DECIMALS,
         )?,
         // Which accounts we're reading from or writing to
         &[token_mint.clone(), sysvar_rent.clone(), mint_auth.clone()],
         // The seeds for out token mint PDA
-        &[&[b"token_mint", &[mint_bump]]],
+        &[&[ns, TOKEN_MINT_SEED, &[mint_bump]]],
     )?;   
 
     Ok(())
@@ -510,6 +4336,8 @@ mod tests {
     use {
         super::*,
         assert_matches::*,
+        crate::constants::{COHORT_SEED, CONTENT_SEED, DEFAULT_INTRO_REWARD, SUMMARY_SEED},
+        crate::state::Cohort,
         solana_program::{
             instruction::{AccountMeta, Instruction},
             system_program::ID as SYSTEM_PROGRAM_ID,
@@ -531,11 +4359,11 @@ mod tests {
     fn create_init_mint_ix (payer:Pubkey, program_id: Pubkey) -> 
     (Pubkey, Pubkey, Instruction) {
         // Derive PDA for token mint authority
-        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
-        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[TOKEN_MINT_SEED], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[TOKEN_AUTH_SEED], &program_id);
 
         let init_mint_ix = Instruction {
-            program_id: program_id,
+            program_id,
             accounts: vec![
                 AccountMeta::new_readonly(payer, true),
                 AccountMeta::new(mint, false),
@@ -595,14 +4423,14 @@ mod tests {
         let name: String = "Celal Melal".to_owned();
         // const RATING: u8 = 3;
         let message: String = "Here to learn".to_owned();
-        let (review_pda, bump_seed) = Pubkey::find_program_address(
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(
             &[payer.pubkey().as_ref(), name.as_bytes()],
             &program_id
         );
 
         // Create comment PDA
         let (comment_pda, _bump_seed) = Pubkey::find_program_address(
-            &[review_pda.as_ref(), b"comment"],
+            &[review_pda.as_ref(), COMMENT_SEED],
             &program_id
         );
 
@@ -615,19 +4443,57 @@ mod tests {
 
         let user_ata: Pubkey = get_associated_token_address(&payer.pubkey(), &mint);
 
+        // Reviewer's user record and the first slot of their intro index
+        let (user_pda, _bump_seed) = Pubkey::find_program_address(
+            &[USER_SEED, payer.pubkey().as_ref()],
+            &program_id
+        );
+        let (user_intro_pda, _bump_seed) = Pubkey::find_program_address(
+            &[payer.pubkey().as_ref(), INTRO_SEED, 0u64.to_be_bytes().as_ref()],
+            &program_id
+        );
+        let (stats_pda, _bump_seed) = Pubkey::find_program_address(&[GLOBAL_STATS_SEED], &program_id);
+        let (intro_index_pda, _bump_seed) = Pubkey::find_program_address(
+            &[INTRO_SEED, 0u64.to_be_bytes().as_ref()],
+            &program_id
+        );
+        let init_stats_ix = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(stats_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data: vec![5],
+        };
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id);
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[TREASURY_SEED], &program_id);
+        let (cohort_pda, _bump_seed) = Pubkey::find_program_address(&[COHORT_SEED], &program_id);
+        let (content_pda, _bump_seed) = Pubkey::find_program_address(
+            &[CONTENT_SEED, content::message_hash(&message).as_ref()], &program_id);
+        let (summary_pda, _bump_seed) = Pubkey::find_program_address(
+            &[review_pda.as_ref(), SUMMARY_SEED], &program_id);
+        let mut config_data = vec![7];
+        config_data.append(&mut ConfigParams::default().try_to_vec().unwrap());
+        let init_config_ix = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(config_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data: config_data,
+        };
+
         // Concat data to single buffer
         let mut data_vec = vec![0];
         data_vec.append(
-            &mut (TryInto::<u32>::try_into(name.len()).unwrap().to_le_bytes())
-            .try_into()
-            .unwrap(),
+            &mut (TryInto::<u32>::try_into(name.len()).unwrap().to_le_bytes()).into(),
         );
         data_vec.append(&mut name.into_bytes());
         // data_vec.push(RATING);
         data_vec.append(
-            &mut (TryInto::<u32>::try_into(message.len()).unwrap().to_le_bytes())
-            .try_into()
-            .unwrap(),
+            &mut (TryInto::<u32>::try_into(message.len()).unwrap().to_le_bytes()).into(),
         );
         data_vec.append(&mut message.into_bytes());
 
@@ -635,9 +4501,11 @@ mod tests {
         let mut transaction = Transaction::new_with_payer(
             &[
                 init_mint_ix,
+                init_stats_ix,
+                init_config_ix,
                 init_ata_ix,
                 Instruction {
-                    program_id: program_id,
+                    program_id,
                     accounts: vec![
                         AccountMeta::new_readonly(payer.pubkey(), true),
                         AccountMeta::new(review_pda, false),
@@ -647,6 +4515,19 @@ mod tests {
                         AccountMeta::new(user_ata, false),
                         AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
                         AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                        AccountMeta::new(user_pda, false),
+                        AccountMeta::new(user_intro_pda, false),
+                        AccountMeta::new(stats_pda, false),
+                        AccountMeta::new(intro_index_pda, false),
+                        AccountMeta::new_readonly(config_pda, false),
+                        AccountMeta::new(treasury_pda, false),
+                        // No creation fee configured, so neither fee account is used
+                        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                        // Default cohort, which has no account
+                        AccountMeta::new_readonly(cohort_pda, false),
+                        AccountMeta::new(content_pda, false),
+                        AccountMeta::new(summary_pda, false),
                     ],
                     data: data_vec,
                 },
@@ -659,4 +4540,570 @@ mod tests {
         assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_close_account_moves_lamports_and_wipes_data() {
+        let owner = Pubkey::new_unique();
+        let (account_key, destination_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut account_lamports, mut destination_lamports) = (500, 100);
+        let (mut account_data, mut destination_data) = (vec![7u8; 16], vec![]);
+
+        let account = AccountInfo::new(
+            &account_key, false, true, &mut account_lamports, &mut account_data, &owner, false, 0,
+        );
+        let destination = AccountInfo::new(
+            &destination_key, true, true, &mut destination_lamports, &mut destination_data, &owner, false, 0,
+        );
+
+        assert_matches!(close_account(&account, &destination), Ok(()));
+        assert_eq!(account.lamports(), 0);
+        assert_eq!(destination.lamports(), 600);
+        assert!(account.data.borrow().iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_full_export_page_fits_in_return_data() {
+        let page = ExportPage {
+            next_cursor: u64::MAX,
+            intros: (0..ExportPage::MAX_INTROS)
+                .map(|_| IntroSummary {
+                    review: Pubkey::new_unique(),
+                    reviewer: Pubkey::new_unique(),
+                    name_hash: [u8::MAX; 32],
+                    comment_count: u64::MAX,
+                })
+                .collect(),
+        };
+
+        let data = page.try_to_vec().unwrap();
+        assert_eq!(data.len(), 8 + 4 + ExportPage::MAX_INTROS * IntroSummary::SIZE);
+        assert!(data.len() <= solana_program::program::MAX_RETURN_DATA);
+    }
+
+    #[test]
+    fn test_comment_link_and_legacy_comment_layout() {
+        assert!(check_comment_link("").is_ok());
+        assert!(check_comment_link("https://gist.github.com/someone/abc123").is_ok());
+        assert!(check_comment_link("http://gist.github.com/someone/abc123").is_err());
+        assert!(check_comment_link("https://").is_err());
+        assert!(check_comment_link(&format!("https://{}", "a".repeat(StudentIntroComment::MAX_LINK_LEN))).is_err());
+
+        // Comments written before attachments end after the count
+        let legacy = (
+            StudentIntroComment::DISCRIMINATOR.to_string(),
+            true,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            "nice intro".to_string(),
+            3u64,
+        ).try_to_vec().unwrap();
+        let comment_data = try_from_slice_unchecked::<StudentIntroComment>(&legacy).unwrap();
+        assert_eq!(comment_data.count, 3);
+        assert!(comment_data.link.is_empty());
+        assert_eq!(comment_data.content_hash, [0u8; 32]);
+        assert!(!comment_data.acknowledged);
+        assert_eq!(comment_data.translations, 0);
+    }
+
+    #[test]
+    fn test_edit_window_binds_commenters_but_not_the_admin() {
+        let (admin, commenter) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        config_data.admin = admin;
+        let mut comment_data = try_from_slice_unchecked::<StudentIntroComment>(&(
+            StudentIntroComment::DISCRIMINATOR.to_string(),
+            true,
+            Pubkey::new_unique(),
+            commenter,
+            "typo".to_string(),
+            0u64,
+        ).try_to_vec().unwrap()).unwrap();
+
+        // No window configured, so even legacy comments stay editable
+        assert!(check_edit_window(&config_data, &commenter, &comment_data, 1_000).is_ok());
+
+        config_data.edit_window_slots = 100;
+        assert_eq!(
+            check_edit_window(&config_data, &commenter, &comment_data, 1_000),
+            Err(ReviewError::EditWindowClosed.into())
+        );
+        comment_data.created_slot = 950;
+        assert!(check_edit_window(&config_data, &commenter, &comment_data, 1_000).is_ok());
+        assert!(check_edit_window(&config_data, &commenter, &comment_data, 1_051).is_err());
+        assert!(check_edit_window(&config_data, &admin, &comment_data, 1_051).is_ok());
+    }
+
+    #[test]
+    fn test_anonymous_comments_are_rate_limited_per_window() {
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        let mut user_data = try_from_slice_unchecked::<UserRecord>(&[0u8; UserRecord::SIZE]).unwrap();
+        assert!(check_anonymous_rate(&config_data, &mut user_data, 100).is_err());
+
+        config_data.anonymous_window_slots = 50;
+        config_data.anonymous_comments_per_window = 2;
+        assert!(check_anonymous_rate(&config_data, &mut user_data, 100).is_ok());
+        assert!(check_anonymous_rate(&config_data, &mut user_data, 120).is_ok());
+        assert_eq!(check_anonymous_rate(&config_data, &mut user_data, 149).err(),
+            Some(ReviewError::AnonymousCommentLimit.into()));
+        assert!(check_anonymous_rate(&config_data, &mut user_data, 150).is_ok());
+        assert_eq!(user_data.anonymous_window_start_slot, 150);
+    }
+
+    #[test]
+    fn test_reaction_bonus_pays_once_at_the_threshold_within_the_cap() {
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        let mut comment_data = try_from_slice_unchecked::<StudentIntroComment>(&(
+            StudentIntroComment::DISCRIMINATOR.to_string(),
+            true,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            "Great feedback".to_string(),
+            0u64,
+        ).try_to_vec().unwrap()).unwrap();
+        comment_data.reactions = 3;
+
+        // Disabled until both the threshold and the amount are set
+        config_data.reaction_reward = 10;
+        assert!(!reaction_bonus_due(&config_data, &comment_data));
+        config_data.reaction_reward_threshold = 4;
+        assert!(!reaction_bonus_due(&config_data, &comment_data));
+        comment_data.reactions = 4;
+        assert!(reaction_bonus_due(&config_data, &comment_data));
+        comment_data.anonymous = true;
+        assert!(!reaction_bonus_due(&config_data, &comment_data));
+        comment_data.anonymous = false;
+        // Held back while minting is halted, and paid on a later reaction
+        config_data.rewards_halted = true;
+        assert!(!reaction_bonus_due(&config_data, &comment_data));
+        config_data.rewards_halted = false;
+        comment_data.reaction_rewarded = true;
+        assert!(!reaction_bonus_due(&config_data, &comment_data));
+
+        assert!(within_bonus_supply_cap(&config_data, u64::MAX, 10));
+        config_data.bonus_supply_cap = 1_000;
+        assert!(within_bonus_supply_cap(&config_data, 990, 10));
+        assert!(!within_bonus_supply_cap(&config_data, 991, 10));
+        assert!(!within_bonus_supply_cap(&config_data, u64::MAX, 10));
+    }
+
+    #[test]
+    fn test_economic_changes_wait_out_the_delay() {
+        let config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        let mut params = ConfigParams { edit_window_slots: 100, ..ConfigParams::default() };
+        assert!(!changes_economics(&config_data, &params));
+        params.reaction_reward = 5;
+        assert!(changes_economics(&config_data, &params));
+        let params_to = |edit: fn(&mut ConfigParams)| {
+            let mut changed = ConfigParams::default();
+            edit(&mut changed);
+            changed
+        };
+        assert!(changes_economics(&config_data, &params_to(|p| p.accrue_rewards = true)));
+        assert!(changes_economics(&config_data, &params_to(|p| p.claim_window_slots = 10)));
+        assert!(changes_economics(&config_data, &params_to(|p| p.delete_penalty_window_slots = 10)));
+        assert!(changes_economics(&config_data, &params_to(|p| p.fee_receiver = Pubkey::new_unique())));
+        assert!(changes_economics(&config_data, &params_to(|p| p.price_feed = Pubkey::new_unique())));
+
+        let pending_data = PendingConfigChange {
+            discriminator: PendingConfigChange::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            proposer: Pubkey::new_unique(),
+            params: params.clone(),
+            proposed_slot: 1_000,
+            apply_after_slot: 1_500,
+            base: ConfigParams::default(),
+        };
+        assert!(pending_data.try_to_vec().unwrap().len() <= PendingConfigChange::SIZE);
+        assert_eq!(check_apply_slot(&pending_data, 1_499), Err(ReviewError::ConfigChangeTimelocked.into()));
+        assert!(check_apply_slot(&pending_data, 1_500).is_ok());
+
+        // An indexer updated while the proposal waited isn't reverted by it
+        let current = ConfigParams { edit_window_slots: 7, indexer: Pubkey::new_unique(), ..ConfigParams::default() };
+        let merged = merge_proposal(current.clone(), &pending_data.base, pending_data.params);
+        assert_eq!((merged.edit_window_slots, merged.reaction_reward), (100, 5));
+        assert_eq!(merged.indexer, current.indexer);
+    }
+
+    #[test]
+    fn test_legacy_counter_keeps_big_endian_slot_seeds() {
+        let mut counter_data = StudentIntroCommentCounter {
+            discriminator: StudentIntroCommentCounter::DISCRIMINATOR.to_string(),
+            is_intialized: true,
+            counter: 3,
+            seed_version: StudentIntroCommentCounter::CURRENT_SEED_VERSION,
+        };
+        let mut data = counter_data.try_to_vec().unwrap();
+        assert_eq!(data.len(), StudentIntroCommentCounter::SIZE);
+
+        // Counters from before seed versions don't have the last byte
+        data.pop();
+        let legacy = try_from_slice_unchecked::<StudentIntroCommentCounter>(&data).unwrap();
+        assert_eq!(legacy.counter, 3);
+        assert_eq!(legacy.seed_version, StudentIntroCommentCounter::SEED_VERSION_BIG_ENDIAN);
+        assert_eq!(legacy.slot_seed(7), 7u64.to_be_bytes());
+
+        assert_eq!(counter_data.slot_seed(7), 7u64.to_le_bytes());
+        counter_data.seed_version = StudentIntroCommentCounter::SEED_VERSION_BIG_ENDIAN;
+        assert_eq!(counter_data.slot_seed(7), legacy.slot_seed(7));
+    }
+
+    #[test]
+    fn test_expected_hash_guards_against_crossed_edits() {
+        let stored = intro_content_hash("Ada", "first draft").unwrap();
+        assert!(check_expected_hash(None, stored).is_ok());
+        assert!(check_expected_hash(Some(stored), stored).is_ok());
+        assert_eq!(
+            check_expected_hash(Some(intro_content_hash("Ada", "other draft").unwrap()), stored),
+            Err(ReviewError::StaleUpdate.into())
+        );
+        // The name and message are encoded with their lengths, so moving text between them changes the hash
+        assert_ne!(intro_content_hash("Ad", "afirst draft").unwrap(), stored);
+        assert_ne!(comment_content_hash("nice", "").unwrap(), comment_content_hash("nic", "e").unwrap());
+    }
+
+    #[test]
+    fn test_integer_sqrt() {
+        for (value, root) in [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (15, 3), (16, 4), (99, 9), (100, 10)] {
+            assert_eq!(integer_sqrt(value), root);
+        }
+        assert_eq!(integer_sqrt(u64::MAX), u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_executable_checks() {
+        let owner = Pubkey::new_unique();
+        let data_key = Pubkey::new_unique();
+        let (mut lamports, mut program_lamports) = (1, 1);
+        let (mut data, mut program_data) = (vec![], vec![]);
+
+        let data_account = AccountInfo::new(&data_key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        let program = AccountInfo::new(
+            &TOKEN_PROGRAM_ID, false, false, &mut program_lamports, &mut program_data, &owner, true, 0);
+
+        assert!(check_not_executable(&[&data_account]).is_ok());
+        assert!(check_not_executable(&[&data_account, &program]).is_err());
+        assert!(check_program_account(&program, &TOKEN_PROGRAM_ID).is_ok());
+        assert!(check_program_account(&program, &SYSTEM_PROGRAM_ID).is_err());
+        // Right key, but not a deployed program
+        let fake = AccountInfo::new(&TOKEN_PROGRAM_ID, false, true, &mut lamports, &mut data, &owner, false, 0);
+        assert!(check_program_account(&fake, &TOKEN_PROGRAM_ID).is_err());
+    }
+
+    #[test]
+    fn test_short_account_list_reports_not_enough_accounts() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let (mut lamports, mut data) = (1, vec![]);
+        let accounts = [AccountInfo::new(&key, true, true, &mut lamports, &mut data, &program_id, false, 0)];
+
+        assert!(check_account_count(&accounts, 1).is_ok());
+        assert_eq!(
+            update_student_intro(&program_id, &[], &accounts, "name".to_string(), "message".to_string(), None, false),
+            Err(ReviewError::NotEnoughAccounts.into())
+        );
+    }
+
+    #[test]
+    fn test_finalized_config_is_read_only() {
+        let program_id = Pubkey::new_unique();
+        let (config_key, _bump) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id);
+        let mut data = vec![0u8; Config::SIZE];
+        let mut config_data = try_from_slice_unchecked::<Config>(&data).unwrap();
+        config_data.is_initialized = true;
+        config_data.serialize(&mut &mut data[..]).unwrap();
+
+        let mut lamports = 1;
+        let pda_config = AccountInfo::new(&config_key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        assert!(load_writable_config(&program_id, &[], &pda_config).is_ok());
+
+        config_data.finalized = true;
+        account_type::write(&config_data, &pda_config).unwrap();
+        assert!(load_config(&program_id, &[], &pda_config).is_ok());
+        assert_eq!(
+            load_writable_config(&program_id, &[], &pda_config).err(),
+            Some(ReviewError::CourseFinalized.into())
+        );
+    }
+
+    #[test]
+    fn test_reward_destination_defaults_to_the_wallet() {
+        // Records written before the destination existed end in zeroed headroom
+        let wallet = Pubkey::new_unique();
+        let mut data = vec![0u8; UserRecord::SIZE];
+        let mut user_data = try_from_slice_unchecked::<UserRecord>(&data).unwrap();
+        user_data.wallet = wallet;
+        user_data.serialize(&mut &mut data[..]).unwrap();
+
+        let mut user_data = try_from_slice_unchecked::<UserRecord>(&data).unwrap();
+        assert_eq!(user_data.reward_owner(), wallet);
+
+        let cold_wallet = Pubkey::new_unique();
+        user_data.reward_destination = cold_wallet;
+        assert_eq!(user_data.reward_owner(), cold_wallet);
+    }
+
+    #[test]
+    fn test_delete_penalty_takes_back_what_was_minted() {
+        let mut review_data = try_from_slice_unchecked::<StudentIntroState>(&[0u8; StudentIntroState::ACCOUNT_LEN]).unwrap();
+        // Intros from before either amount was recorded were minted 10 tokens
+        assert_eq!(review_data.minted_reward(), DEFAULT_INTRO_REWARD);
+
+        review_data.intro_reward = 3;
+        assert_eq!(review_data.minted_reward(), 3);
+
+        // Nothing was minted while rewards were halted, so nothing is burned
+        review_data.reward_minted = Some(0);
+        assert_eq!(review_data.minted_reward(), 0);
+    }
+
+    #[test]
+    fn test_admin_quorum_counts_distinct_admin_signers() {
+        let owner = Pubkey::new_unique();
+        let (admin_key, co_admin_key, outsider_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        config_data.admin = admin_key;
+        config_data.co_admins = vec![co_admin_key];
+
+        let (mut l1, mut l2, mut l3, mut l4) = (1, 1, 1, 1);
+        let (mut d1, mut d2, mut d3, mut d4) = (vec![], vec![], vec![], vec![]);
+        let admin = AccountInfo::new(&admin_key, true, false, &mut l1, &mut d1, &owner, false, 0);
+        let co_admin = AccountInfo::new(&co_admin_key, true, false, &mut l2, &mut d2, &owner, false, 0);
+        let unsigned_co_admin = AccountInfo::new(&co_admin_key, false, false, &mut l3, &mut d3, &owner, false, 0);
+        let outsider = AccountInfo::new(&outsider_key, true, false, &mut l4, &mut d4, &owner, false, 0);
+
+        // Without a threshold the admin acts alone
+        assert!(check_admin_quorum(&config_data, &admin, &[]).is_ok());
+
+        config_data.admin_threshold = 2;
+        assert_eq!(check_admin_quorum(&config_data, &admin, &[]), Err(ReviewError::AdminQuorumNotMet.into()));
+        assert_eq!(
+            check_admin_quorum(&config_data, &admin, &[unsigned_co_admin, outsider, admin.clone()]),
+            Err(ReviewError::AdminQuorumNotMet.into())
+        );
+        assert!(check_admin_quorum(&config_data, &admin, std::slice::from_ref(&co_admin)).is_ok());
+        // The co-admin can't act as the admin
+        assert!(check_admin_quorum(&config_data, &co_admin, &[admin]).is_err());
+    }
+
+    #[test]
+    fn test_bootstrap_refuses_existing_accounts() {
+        let program_id = Pubkey::new_unique();
+        let mut keys: Vec<Pubkey> = (0..11).map(|_| Pubkey::new_unique()).collect();
+        keys[8] = SYSTEM_PROGRAM_ID;
+        let mut lamports = [0u64; 11];
+        lamports[0] = 1_000_000_000;
+        let mut data = vec![vec![]; 11];
+        // The config is already there
+        data[1] = vec![0u8; Config::SIZE];
+        lamports[1] = 1;
+
+        let accounts: Vec<AccountInfo> = keys.iter().zip(lamports.iter_mut()).zip(data.iter_mut()).enumerate()
+            .map(|(i, ((key, lamports), data))| AccountInfo::new(key, i == 0, true, lamports, data, &program_id, i >= 8, 0))
+            .collect();
+
+        assert_eq!(
+            bootstrap(&program_id, &[], &accounts, ConfigParams::default()),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn test_only_staff_leave_private_feedback() {
+        let (admin, co_admin, student) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config_data = try_from_slice_unchecked::<Config>(&[0u8; Config::SIZE]).unwrap();
+        config_data.admin = admin;
+        config_data.co_admins = vec![co_admin];
+
+        assert!(check_visibility(&config_data, &student, StudentIntroComment::VISIBILITY_PUBLIC).is_ok());
+        assert!(check_visibility(&config_data, &admin, StudentIntroComment::VISIBILITY_STAFF).is_ok());
+        assert!(check_visibility(&config_data, &co_admin, StudentIntroComment::VISIBILITY_OWNER).is_ok());
+        assert_eq!(
+            check_visibility(&config_data, &student, StudentIntroComment::VISIBILITY_OWNER),
+            Err(ReviewError::Unauthorized.into())
+        );
+        assert_eq!(check_visibility(&config_data, &admin, 3), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn test_check_reward_ata() {
+        let (wallet, mint_key, ata_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut mint_lamports, mut mint_data) = (1, vec![]);
+        let token_mint = AccountInfo::new(
+            &mint_key, false, false, &mut mint_lamports, &mut mint_data, &TOKEN_PROGRAM_ID, false, 0);
+
+        let mut ata_data = vec![0u8; TokenAccount::LEN];
+        let mut lamports = 1;
+        {
+            let ata = AccountInfo::new(&ata_key, false, true, &mut lamports, &mut ata_data, &TOKEN_PROGRAM_ID, false, 0);
+            assert!(check_reward_ata(&ata, &token_mint, &wallet).is_err());
+        }
+
+        TokenAccount {
+            mint: mint_key,
+            owner: wallet,
+            state: spl_token::state::AccountState::Initialized,
+            ..TokenAccount::default()
+        }.pack_into_slice(&mut ata_data);
+        let ata = AccountInfo::new(&ata_key, false, true, &mut lamports, &mut ata_data, &TOKEN_PROGRAM_ID, false, 0);
+        assert!(check_reward_ata(&ata, &token_mint, &wallet).is_ok());
+        assert!(check_reward_ata(&ata, &token_mint, &Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_comment_index_closes_only_after_its_comment() {
+        let program_id = Pubkey::new_unique();
+        let (caller_key, comment_key, commenter_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (index_key, _index_bump) = Pubkey::find_program_address(
+            &[b"", commenter_key.as_ref(), COMMENT_SEED, 3u64.to_be_bytes().as_ref()], &program_id);
+
+        let mut index_bytes = UserCommentIndex {
+            discriminator: UserCommentIndex::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            commenter: commenter_key,
+            index: 3,
+            review: Pubkey::new_unique(),
+            comment: comment_key,
+        }.try_to_vec().unwrap();
+        let size = StudentIntroComment::get_account_size("Nice".to_string(), String::new());
+        let mut comment_bytes = vec![0u8; size];
+        let mut comment_data = try_from_slice_unchecked::<StudentIntroComment>(&comment_bytes).unwrap();
+        comment_data.discriminator = StudentIntroComment::DISCRIMINATOR.to_string();
+        comment_data.is_initialized = true;
+        comment_data.commenter = commenter_key;
+        comment_data.comment = "Nice".to_string();
+        comment_data.serialize(&mut &mut comment_bytes[..]).unwrap();
+
+        let (mut caller_lamports, mut index_lamports, mut comment_lamports, mut commenter_lamports) = (1, 5, 7, 1);
+        let (mut caller_bytes, mut commenter_bytes) = (vec![], vec![]);
+        let accounts = [
+            AccountInfo::new(&caller_key, true, false, &mut caller_lamports, &mut caller_bytes,
+                &SYSTEM_PROGRAM_ID, false, 0),
+            AccountInfo::new(&index_key, false, true, &mut index_lamports, &mut index_bytes, &program_id, false, 0),
+            AccountInfo::new(&comment_key, false, true, &mut comment_lamports, &mut comment_bytes,
+                &program_id, false, 0),
+            AccountInfo::new(&commenter_key, false, true, &mut commenter_lamports, &mut commenter_bytes,
+                &SYSTEM_PROGRAM_ID, false, 0),
+        ];
+
+        // The comment is still open
+        assert!(close_comment_index(&program_id, b"", &accounts).is_err());
+
+        close_account(&accounts[2], &accounts[3]).unwrap();
+        // The rent goes to the commenter only
+        let misdirected = [accounts[0].clone(), accounts[1].clone(), accounts[2].clone(), accounts[0].clone()];
+        assert!(close_comment_index(&program_id, b"", &misdirected).is_err());
+
+        close_comment_index(&program_id, b"", &accounts).unwrap();
+        assert_eq!((accounts[1].lamports(), accounts[3].lamports()), (0, 13));
+    }
+
+    #[test]
+    fn test_comment_counter_rejects_foreign_accounts() {
+        let program_id = Pubkey::new_unique();
+        let intro_key = Pubkey::new_unique();
+        let (counter_key, _counter_bump) =
+            Pubkey::find_program_address(&[b"", intro_key.as_ref(), COMMENT_SEED], &program_id);
+
+        let counter_bytes = StudentIntroCommentCounter {
+            discriminator: StudentIntroCommentCounter::DISCRIMINATOR.to_string(),
+            is_intialized: true,
+            counter: 4,
+            seed_version: StudentIntroCommentCounter::CURRENT_SEED_VERSION,
+        }.try_to_vec().unwrap();
+        let mut intro_bytes = vec![0u8; StudentIntroState::ACCOUNT_LEN];
+        let mut intro_data = try_from_slice_unchecked::<StudentIntroState>(&intro_bytes).unwrap();
+        intro_data.discriminator = StudentIntroState::DISCRIMINATOR.to_string();
+        intro_data.is_initialized = true;
+        intro_data.serialize(&mut &mut intro_bytes[..]).unwrap();
+
+        let mut lamports = 1;
+        let mut data = counter_bytes.clone();
+        let counter = AccountInfo::new(&counter_key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        assert_eq!(load_comment_counter(&program_id, b"", &intro_key, &counter).unwrap().counter, 4);
+
+        // Another program account, or one at another intro's counter address
+        let other_key = Pubkey::new_unique();
+        let (mut lamports, mut data) = (1, counter_bytes.clone());
+        let elsewhere = AccountInfo::new(&other_key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        assert!(load_comment_counter(&program_id, b"", &intro_key, &elsewhere).is_err());
+
+        // An intro passed at the counter's address
+        let (mut lamports, mut data) = (1, intro_bytes);
+        let intro = AccountInfo::new(&counter_key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        assert!(load_comment_counter(&program_id, b"", &intro_key, &intro).is_err());
+
+        let system_owned = SYSTEM_PROGRAM_ID;
+        let (mut lamports, mut data) = (1, counter_bytes);
+        let foreign = AccountInfo::new(&counter_key, false, true, &mut lamports, &mut data, &system_owned, false, 0);
+        assert!(load_comment_counter(&program_id, b"", &intro_key, &foreign).is_err());
+    }
+
+    #[test]
+    fn test_comments_read_the_counter_only_for_unrecorded_seed_versions() {
+        let program_id = Pubkey::new_unique();
+        let intro_key = Pubkey::new_unique();
+        let (counter_key, _counter_bump) =
+            Pubkey::find_program_address(&[b"", intro_key.as_ref(), COMMENT_SEED], &program_id);
+        let mut review_data =
+            try_from_slice_unchecked::<StudentIntroState>(&[0u8; StudentIntroState::ACCOUNT_LEN]).unwrap();
+        assert_eq!(review_data.comment_seed_version, None);
+
+        // Intros recording their version don't care what sits in the counter's place
+        let other_key = Pubkey::new_unique();
+        let (mut lamports, mut data) = (1, vec![]);
+        let anything =
+            AccountInfo::new(&other_key, false, false, &mut lamports, &mut data, &SYSTEM_PROGRAM_ID, false, 0);
+        review_data.comment_seed_version = Some(StudentIntroCommentCounter::SEED_VERSION_LITTLE_ENDIAN);
+        assert_eq!(comment_seed_version(&program_id, b"", &intro_key, &review_data, &anything).unwrap(),
+            StudentIntroCommentCounter::SEED_VERSION_LITTLE_ENDIAN);
+
+        // Older ones take it from their counter, which must be the real one
+        review_data.comment_seed_version = None;
+        assert!(comment_seed_version(&program_id, b"", &intro_key, &review_data, &anything).is_err());
+        // A legacy counter, which ends after the count
+        let mut counter_bytes =
+            (StudentIntroCommentCounter::DISCRIMINATOR.to_string(), true, 5u64).try_to_vec().unwrap();
+        let mut lamports = 1;
+        let counter =
+            AccountInfo::new(&counter_key, false, false, &mut lamports, &mut counter_bytes, &program_id, false, 0);
+        assert_eq!(comment_seed_version(&program_id, b"", &intro_key, &review_data, &counter).unwrap(),
+            StudentIntroCommentCounter::SEED_VERSION_BIG_ENDIAN);
+
+        // Comments counted by the counter before and on the intro after both count
+        review_data.comments_added = 2;
+        let counter_data = load_comment_counter(&program_id, b"", &intro_key, &counter).unwrap();
+        assert_eq!(review_data.comment_total(&counter_data), 7);
+    }
+
+    #[test]
+    fn test_transfers_send_the_passed_cohorts_token() {
+        let program_id = Pubkey::new_unique();
+        let (cohort_key, _bump) = Pubkey::find_program_address(&[COHORT_SEED, b"fall24"], &program_id);
+        let (cohort_mint_key, _bump) = Pubkey::find_program_address(&[TOKEN_MINT_SEED, b"fall24"], &program_id);
+        let (default_mint_key, _bump) = Pubkey::find_program_address(&[TOKEN_MINT_SEED], &program_id);
+
+        let mut cohort_data = vec![0u8; Cohort::SIZE];
+        Cohort {
+            discriminator: Cohort::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            id: "fall24".to_string(),
+            intro_reward: 3,
+            comment_reward: 1,
+            member_count: 1,
+        }.serialize(&mut &mut cohort_data[..]).unwrap();
+        let (mut cohort_lamports, mut cohort_mint_lamports, mut default_mint_lamports) = (1, 1, 1);
+        let (mut cohort_mint_data, mut default_mint_data) = (vec![], vec![]);
+        let pda_cohort =
+            AccountInfo::new(&cohort_key, false, false, &mut cohort_lamports, &mut cohort_data, &program_id, false, 0);
+        let cohort_mint = AccountInfo::new(&cohort_mint_key, false, true, &mut cohort_mint_lamports,
+            &mut cohort_mint_data, &TOKEN_PROGRAM_ID, false, 0);
+        let default_mint = AccountInfo::new(&default_mint_key, false, true, &mut default_mint_lamports,
+            &mut default_mint_data, &TOKEN_PROGRAM_ID, false, 0);
+
+        assert!(check_transfer_mint(&program_id, b"", Some(&pda_cohort), &cohort_mint).is_ok());
+        assert!(check_transfer_mint(&program_id, b"", Some(&pda_cohort), &default_mint).is_err());
+        // Without a cohort only the default cohort's token can be sent
+        assert!(check_transfer_mint(&program_id, b"", None, &default_mint).is_ok());
+        assert!(check_transfer_mint(&program_id, b"", None, &cohort_mint).is_err());
+    }
+}
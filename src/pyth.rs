@@ -0,0 +1,151 @@
+use solana_program::{account_info::AccountInfo, native_token::LAMPORTS_PER_SOL, program_error::ProgramError};
+use std::convert::TryInto;
+use crate::error::ReviewError;
+use crate::reason;
+
+// Layout of a Pyth v2 price account, only the fields the fee needs
+const MAGIC: u32 = 0xa1b2c3d4;
+const VERSION_2: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 4;
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+const EXPO_OFFSET: usize = 20;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUBLISH_SLOT_OFFSET: usize = 232;
+const MIN_PRICE_ACCOUNT_LEN: usize = 240;
+
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_slot: u64,
+}
+
+pub fn load_price(price_account: &AccountInfo) -> Result<PythPrice, ProgramError> {
+    let data = price_account.data.borrow();
+    parse_price(&data)
+}
+
+pub fn parse_price(data: &[u8]) -> Result<PythPrice, ProgramError> {
+    if data.len() < MIN_PRICE_ACCOUNT_LEN {
+        return Err(reason!(ReviewError::InvalidPriceAccount, "Price account is too small"));
+    }
+
+    if read_u32(data, MAGIC_OFFSET) != MAGIC
+        || read_u32(data, VERSION_OFFSET) != VERSION_2
+        || read_u32(data, ACCOUNT_TYPE_OFFSET) != ACCOUNT_TYPE_PRICE
+    {
+        return Err(reason!(ReviewError::InvalidPriceAccount, "Not a Pyth v2 price account"));
+    }
+
+    if read_u32(data, AGG_STATUS_OFFSET) != STATUS_TRADING {
+        return Err(reason!(ReviewError::InvalidPriceAccount, "Price feed is not trading"));
+    }
+
+    Ok(PythPrice {
+        price: i64::from_le_bytes(data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].try_into().unwrap()),
+        conf: u64::from_le_bytes(data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].try_into().unwrap()),
+        expo: i32::from_le_bytes(data[EXPO_OFFSET..EXPO_OFFSET + 4].try_into().unwrap()),
+        publish_slot: u64::from_le_bytes(
+            data[AGG_PUBLISH_SLOT_OFFSET..AGG_PUBLISH_SLOT_OFFSET + 8].try_into().unwrap(),
+        ),
+    })
+}
+
+// Rejects prices older than `max_staleness_slots` or with a confidence interval wider
+// than `max_confidence_bps` of the price
+pub fn check_price(
+    price: &PythPrice,
+    current_slot: u64,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
+) -> Result<(), ProgramError> {
+    if price.price <= 0 {
+        return Err(reason!(ReviewError::InvalidPriceAccount, "Price is not positive"));
+    }
+
+    if current_slot.saturating_sub(price.publish_slot) > max_staleness_slots {
+        return Err(reason!(ReviewError::StalePrice, "Price published at slot {} is stale", price.publish_slot));
+    }
+
+    let confidence_bps = (price.conf as u128) * 10_000 / (price.price as u128);
+    if confidence_bps > max_confidence_bps as u128 {
+        return Err(reason!(ReviewError::PriceConfidenceTooWide,
+            "Price confidence {} bps is wider than {} bps", confidence_bps, max_confidence_bps));
+    }
+
+    Ok(())
+}
+
+// Converts a USD amount in cents to lamports at a SOL/USD price of price * 10^expo
+pub fn usd_cents_to_lamports(usd_cents: u64, price: &PythPrice) -> Result<u64, ProgramError> {
+    let scale = 10u128
+        .checked_pow(price.expo.unsigned_abs())
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    // lamports = cents / 100 * LAMPORTS_PER_SOL / (price * 10^expo)
+    let numerator = (usd_cents as u128)
+        .checked_mul(LAMPORTS_PER_SOL as u128)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    let lamports = if price.expo < 0 {
+        numerator
+            .checked_mul(scale)
+            .ok_or(ReviewError::ArithmeticOverflow)?
+            / (price.price as u128 * 100)
+    } else {
+        numerator / (price.price as u128 * 100 * scale)
+    };
+
+    lamports.try_into().map_err(|_| ReviewError::ArithmeticOverflow.into())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_account(price: i64, conf: u64, expo: i32, publish_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; MIN_PRICE_ACCOUNT_LEN];
+        data[MAGIC_OFFSET..MAGIC_OFFSET + 4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&VERSION_2.to_le_bytes());
+        data[ACCOUNT_TYPE_OFFSET..ACCOUNT_TYPE_OFFSET + 4].copy_from_slice(&ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&STATUS_TRADING.to_le_bytes());
+        data[AGG_PUBLISH_SLOT_OFFSET..AGG_PUBLISH_SLOT_OFFSET + 8].copy_from_slice(&publish_slot.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_usd_fee_at_twenty_dollars_per_sol() {
+        // $20.00000000 per SOL, so a $1.00 fee is 0.05 SOL
+        let price = parse_price(&price_account(2_000_000_000, 1_000_000, -8, 100)).unwrap();
+        assert_eq!(usd_cents_to_lamports(100, &price).unwrap(), LAMPORTS_PER_SOL / 20);
+    }
+
+    #[test]
+    fn test_check_price_rejects_stale_and_uncertain_prices() {
+        let price = parse_price(&price_account(2_000_000_000, 1_000_000, -8, 100)).unwrap();
+        assert!(check_price(&price, 110, 25, 100).is_ok());
+        assert!(check_price(&price, 200, 25, 100).is_err());
+        // Confidence is 0.05% of the price
+        assert!(check_price(&price, 110, 25, 4).is_err());
+    }
+
+    #[test]
+    fn test_parse_price_rejects_other_accounts() {
+        let mut data = price_account(2_000_000_000, 1_000_000, -8, 100);
+        data[MAGIC_OFFSET] ^= 0xff;
+        assert!(parse_price(&data).is_err());
+        assert!(parse_price(&[0u8; 16]).is_err());
+    }
+}
@@ -0,0 +1,415 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::{instruction::transfer, state::Account as TokenAccount, ID as TOKEN_PROGRAM_ID};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::{RAFFLE_SEED, TICKET_SEED, TOKEN_AUTH_SEED, TOKEN_MINT_SEED, TREASURY_SEED};
+use crate::audit;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::crank::{split_thread, start_run};
+use crate::processor::{check_account_count, check_admin_quorum, check_not_executable, check_program_account, load_config, load_global_stats, load_writable_config};
+use crate::state::{AuditEntry, MaintenanceThread, Raffle, RaffleTicket};
+
+// ORAO VRF keeps each request in a `Randomness` Anchor account at
+// [RANDOMNESS_SEED_PREFIX, seed] under the oracle program
+const RANDOMNESS_SEED_PREFIX: &[u8] = b"orao-vrf-randomness-request";
+const RANDOMNESS_SEED_OFFSET: usize = 8;
+const RANDOMNESS_VALUE_OFFSET: usize = 40;
+const RANDOMNESS_MIN_LEN: usize = 104;
+
+pub fn create_raffle(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    id: u64,
+    start_slot: u64,
+    end_slot: u64,
+    prize: u64,
+) -> ProgramResult {
+    msg!("Creating raffle {}...", id);
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_raffle = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    // The prize is minted when the raffle is drawn, so creating one is a high-value action
+    check_admin_quorum(&config_data, admin, account_info_iter.as_slice())?;
+
+    if end_slot <= start_slot || prize == 0 {
+        msg!("Raffle needs a non-empty window and a prize");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (raffle_pda, raffle_bump) = Pubkey::find_program_address(
+        &[ns, RAFFLE_SEED, id.to_be_bytes().as_ref()], program_id
+    );
+    if raffle_pda != *pda_raffle.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_raffle.key,
+            rent.minimum_balance(Raffle::SIZE + account_type::TAG_LEN),
+            (Raffle::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_raffle.clone(), system_program.clone()],
+        &[&[ns, RAFFLE_SEED, id.to_be_bytes().as_ref(), &[raffle_bump]]],
+    )?;
+
+    let raffle_data = Raffle {
+        discriminator: Raffle::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        id,
+        start_slot,
+        end_slot,
+        prize,
+        ticket_count: 0,
+        randomness_account: Pubkey::default(),
+        drawn: false,
+        winning_ticket: 0,
+        winner: Pubkey::default(),
+    };
+    account_type::write(&raffle_data, pda_raffle)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_CREATE_RAFFLE)
+}
+
+// Binds the raffle to an oracle request made after ticketing closed. The request must
+// still be unfulfilled so nobody could know the outcome while tickets were issued, which
+// is what lets anyone, including an automation thread, make the commitment.
+pub fn commit_raffle_randomness(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Committing raffle randomness...");
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_raffle = next_account_info(account_info_iter)?;
+    let randomness_account = next_account_info(account_info_iter)?;
+
+    let config_data = load_config(program_id, ns, pda_config)?;
+    let mut raffle_data = load_raffle(program_id, ns, pda_raffle)?;
+
+    if raffle_data.randomness_account == *randomness_account.key {
+        msg!("Randomness is already committed");
+        return Ok(());
+    }
+    if Clock::get()?.slot <= raffle_data.end_slot || raffle_data.randomness_account != Pubkey::default() {
+        return Err(reason!(ReviewError::InvalidRafflePhase,
+            "Randomness can only be committed once, after the ticket window"));
+    }
+
+    if read_randomness(randomness_account, &config_data.randomness_program)?.is_some() {
+        return Err(reason!(ReviewError::InvalidRandomnessAccount, "Randomness request was already fulfilled"));
+    }
+
+    raffle_data.randomness_account = *randomness_account.key;
+    account_type::write(&raffle_data, pda_raffle)?;
+
+    Ok(())
+}
+
+pub fn draw_raffle(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Drawing raffle...");
+
+    let (accounts, pda_thread) = split_thread(program_id, ns, MaintenanceThread::TASK_DRAW_RAFFLE, accounts);
+    check_account_count(accounts, 10)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_raffle = next_account_info(account_info_iter)?;
+    let randomness_account = next_account_info(account_info_iter)?;
+    let pda_ticket = next_account_info(account_info_iter)?;
+    let winner_ata = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+
+    let config_data = load_config(program_id, ns, pda_config)?;
+    let stats_data = load_global_stats(program_id, ns, pda_stats)?;
+    let mut raffle_data = load_raffle(program_id, ns, pda_raffle)?;
+
+    if let Some(pda_thread) = pda_thread {
+        if !start_run(program_id, pda_thread, Clock::get()?.slot)? {
+            return Ok(());
+        }
+    }
+
+    // A crank may fire before the draw is possible or after it happened
+    if raffle_data.drawn {
+        msg!("Raffle is already drawn");
+        return Ok(());
+    }
+    if raffle_data.randomness_account == Pubkey::default() {
+        msg!("Raffle has no committed randomness yet");
+        return Ok(());
+    }
+    if *randomness_account.key != raffle_data.randomness_account {
+        return Err(reason!(ReviewError::InvalidRandomnessAccount,
+            "Randomness account does not match the committed request"));
+    }
+
+    let randomness = match read_randomness(randomness_account, &config_data.randomness_program)? {
+        Some(randomness) => randomness,
+        None => {
+            msg!("Randomness request is not fulfilled yet");
+            return Ok(());
+        }
+    };
+
+    raffle_data.drawn = true;
+
+    if raffle_data.ticket_count == 0 {
+        msg!("No tickets were issued, nothing to pay out");
+        account_type::write(&raffle_data, pda_raffle)?;
+        return Ok(());
+    }
+
+    let winning_ticket = u64::from_le_bytes(randomness[..8].try_into().unwrap()) % raffle_data.ticket_count;
+
+    let (ticket_pda, _ticket_bump) = Pubkey::find_program_address(
+        &[ns, pda_raffle.key.as_ref(), TICKET_SEED, winning_ticket.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if ticket_pda != *pda_ticket.key {
+        return Err(reason!(ReviewError::InvalidPDA,
+            "Winning ticket is {}, a different ticket was passed", winning_ticket));
+    }
+    if pda_ticket.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let ticket_data = account_type::read::<RaffleTicket>(pda_ticket).unwrap();
+
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[ns, TREASURY_SEED], program_id);
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[ns, TOKEN_MINT_SEED], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[ns, TOKEN_AUTH_SEED], program_id);
+
+    if treasury_pda != *treasury.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect treasury account"));
+    }
+    if mint_pda != *token_mint.key {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect token mint"));
+    }
+    if mint_auth_pda != *mint_auth.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Mint passed in and mint derived do not match"));
+    }
+    if *winner_ata.key != get_associated_token_address(&ticket_data.owner, token_mint.key) {
+        return Err(reason!(ReviewError::IncorrectAccountError, "Incorrect winner token account"));
+    }
+    check_not_executable(&[winner_ata, treasury])?;
+    check_program_account(token_program, &TOKEN_PROGRAM_ID)?;
+
+    // Tokens that accrued to students are not up for grabs
+    let treasury_balance = TokenAccount::unpack(&treasury.data.borrow())?.amount;
+    if treasury_balance.saturating_sub(stats_data.rewards_pending) < raffle_data.prize {
+        return Err(reason!(ReviewError::InsufficientTreasury, "Treasury holds {} with {} reserved, prize is {}",
+            treasury_balance, stats_data.rewards_pending, raffle_data.prize));
+    }
+
+    msg!("Ticket {} owned by {} wins {}", winning_ticket, ticket_data.owner, raffle_data.prize);
+    invoke_signed(
+        &transfer(
+            token_program.key,
+            treasury.key,
+            winner_ata.key,
+            mint_auth.key,
+            &[],
+            raffle_data.prize,
+        )?,
+        &[treasury.clone(), winner_ata.clone(), mint_auth.clone()],
+        &[&[ns, TOKEN_AUTH_SEED, &[mint_auth_bump]]],
+    )?;
+
+    raffle_data.winning_ticket = winning_ticket;
+    raffle_data.winner = ticket_data.owner;
+    account_type::write(&raffle_data, pda_raffle)?;
+
+    Ok(())
+}
+
+// Gives the commenter the raffle's next ticket when the comment lands inside the
+// ticket window. Comments outside the window simply don't earn one.
+pub fn issue_ticket<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    commenter: &AccountInfo<'a>,
+    pda_raffle: &AccountInfo<'a>,
+    pda_ticket: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let mut raffle_data = load_raffle(program_id, ns, pda_raffle)?;
+
+    let slot = Clock::get()?.slot;
+    if slot < raffle_data.start_slot || slot > raffle_data.end_slot {
+        msg!("Raffle {} is not issuing tickets", raffle_data.id);
+        return Ok(());
+    }
+
+    let (ticket_pda, ticket_bump) = Pubkey::find_program_address(
+        &[ns, pda_raffle.key.as_ref(), TICKET_SEED, raffle_data.ticket_count.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if ticket_pda != *pda_ticket.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            commenter.key,
+            pda_ticket.key,
+            rent.minimum_balance(RaffleTicket::SIZE + account_type::TAG_LEN),
+            (RaffleTicket::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[commenter.clone(), pda_ticket.clone(), system_program.clone()],
+        &[&[
+            ns,
+            pda_raffle.key.as_ref(),
+            TICKET_SEED,
+            raffle_data.ticket_count.to_be_bytes().as_ref(),
+            &[ticket_bump],
+        ]],
+    )?;
+
+    let ticket_data = RaffleTicket {
+        discriminator: RaffleTicket::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        raffle: *pda_raffle.key,
+        index: raffle_data.ticket_count,
+        owner: *commenter.key,
+    };
+    account_type::write(&ticket_data, pda_ticket)?;
+
+    msg!("Issued raffle ticket {}", raffle_data.ticket_count);
+
+    raffle_data.ticket_count = raffle_data.ticket_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&raffle_data, pda_raffle)?;
+
+    Ok(())
+}
+
+fn load_raffle(program_id: &Pubkey, ns: &[u8], pda_raffle: &AccountInfo) -> Result<Raffle, ProgramError> {
+    if pda_raffle.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let raffle_data = account_type::read::<Raffle>(pda_raffle).unwrap();
+    if !raffle_data.is_initialized() || raffle_data.discriminator != Raffle::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Raffle not initialized"));
+    }
+
+    let (raffle_pda, _raffle_bump) = Pubkey::find_program_address(
+        &[ns, RAFFLE_SEED, raffle_data.id.to_be_bytes().as_ref()], program_id
+    );
+    if raffle_pda != *pda_raffle.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for raffle PDA"));
+    }
+
+    Ok(raffle_data)
+}
+
+// Verifies an ORAO randomness account (owner, Anchor discriminator and request PDA) and
+// returns its 64 bytes of randomness, or None while the request is unfulfilled
+fn read_randomness(
+    randomness_account: &AccountInfo,
+    randomness_program: &Pubkey,
+) -> Result<Option<[u8; 64]>, ProgramError> {
+    if *randomness_program == Pubkey::default() || randomness_account.owner != randomness_program {
+        return Err(reason!(ReviewError::InvalidRandomnessAccount,
+            "Randomness account is not owned by the configured oracle"));
+    }
+
+    let data = randomness_account.data.borrow();
+    if data.len() < RANDOMNESS_MIN_LEN || data[..8] != hash(b"account:Randomness").to_bytes()[..8] {
+        return Err(reason!(ReviewError::InvalidRandomnessAccount, "Not an oracle randomness account"));
+    }
+
+    let seed = &data[RANDOMNESS_SEED_OFFSET..RANDOMNESS_VALUE_OFFSET];
+    let (request_pda, _request_bump) = Pubkey::find_program_address(
+        &[RANDOMNESS_SEED_PREFIX, seed], randomness_program
+    );
+    if request_pda != *randomness_account.key {
+        return Err(reason!(ReviewError::InvalidRandomnessAccount,
+            "Randomness account is not the oracle's request PDA"));
+    }
+
+    let randomness: [u8; 64] = data[RANDOMNESS_VALUE_OFFSET..RANDOMNESS_MIN_LEN].try_into().unwrap();
+    if randomness.iter().all(|byte| *byte == 0) {
+        return Ok(None);
+    }
+
+    Ok(Some(randomness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn randomness_data(seed: &[u8; 32], randomness: &[u8; 64]) -> Vec<u8> {
+        let mut data = vec![0u8; RANDOMNESS_MIN_LEN];
+        data[..8].copy_from_slice(&hash(b"account:Randomness").to_bytes()[..8]);
+        data[RANDOMNESS_SEED_OFFSET..RANDOMNESS_VALUE_OFFSET].copy_from_slice(seed);
+        data[RANDOMNESS_VALUE_OFFSET..RANDOMNESS_MIN_LEN].copy_from_slice(randomness);
+        data
+    }
+
+    #[test]
+    fn test_read_randomness_checks_request_and_fulfillment() {
+        let oracle = Pubkey::new_unique();
+        let seed = [7u8; 32];
+        let (request, _bump) = Pubkey::find_program_address(&[RANDOMNESS_SEED_PREFIX, &seed], &oracle);
+        let mut lamports = 0;
+
+        let mut pending = randomness_data(&seed, &[0u8; 64]);
+        let account = AccountInfo::new(&request, false, false, &mut lamports, &mut pending, &oracle, false, 0);
+        assert_eq!(read_randomness(&account, &oracle).unwrap(), None);
+
+        let mut lamports = 0;
+        let mut fulfilled = randomness_data(&seed, &[3u8; 64]);
+        let account = AccountInfo::new(&request, false, false, &mut lamports, &mut fulfilled, &oracle, false, 0);
+        assert_eq!(read_randomness(&account, &oracle).unwrap(), Some([3u8; 64]));
+
+        // Same data under an address the oracle didn't derive
+        let other = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut forged = randomness_data(&seed, &[3u8; 64]);
+        let account = AccountInfo::new(&other, false, false, &mut lamports, &mut forged, &oracle, false, 0);
+        assert!(read_randomness(&account, &oracle).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::{state::Mint, ID as TOKEN_PROGRAM_ID};
+use crate::account_type;
+use crate::constants::TOKEN_MINT_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::events::{self, IndexEvent};
+use crate::processor::{check_account_count, load_config, load_global_stats};
+use crate::state::GlobalStats;
+
+// Checks the reward mints' supply against the global stats: what was minted less what was
+// burned. Anyone can run it, passing each cohort's mint in the order of cohorts, the empty
+// id being the default ["token_mint"]. Leaving a cohort out lowers the supply, so only a
+// supply above what was accounted for can halt minting, and only where the config opted
+// in with halt_on_supply_mismatch. Tokens holders burn themselves aren't accounted for and
+// show as a lower supply.
+pub fn reconcile_rewards(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    cohorts: Vec<String>,
+) -> ProgramResult {
+    msg!("Reconciling rewards of {} cohorts...", cohorts.len());
+
+    check_account_count(accounts, 3 + cohorts.len())?;
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_stats = next_account_info(account_info_iter)?;
+
+    if !caller.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut config_data = load_config(program_id, ns, pda_config)?;
+    let stats_data = load_global_stats(program_id, ns, pda_stats)?;
+
+    let mut supply = 0u64;
+    for (i, cohort) in cohorts.iter().enumerate() {
+        // Counting a mint twice would fake an excess
+        if cohorts[..i].contains(cohort) {
+            msg!("Cohort {:?} is listed twice", cohort);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let token_mint = next_account_info(account_info_iter)?;
+        let (mint_pda, _mint_bump) = Pubkey::find_program_address(
+            &[ns, TOKEN_MINT_SEED, cohort.as_bytes()], program_id);
+        if mint_pda != *token_mint.key {
+            return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for cohort {:?} mint PDA", cohort));
+        }
+        if *token_mint.owner != TOKEN_PROGRAM_ID {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        supply = supply
+            .checked_add(Mint::unpack(&token_mint.data.borrow())?.supply)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    let accounted = accounted_supply(&stats_data);
+    if supply == accounted {
+        msg!("Supply of {} matches the stats", supply);
+        return Ok(());
+    }
+
+    let halted = supply > accounted && config_data.halt_on_supply_mismatch && !config_data.rewards_halted;
+    if halted {
+        config_data.rewards_halted = true;
+        account_type::write(&config_data, pda_config)?;
+    }
+    msg!("Supply of {} differs from the {} the stats account for", supply, accounted);
+    events::emit(&IndexEvent::RewardSupplyMismatch { accounted, supply, halted })
+}
+
+fn accounted_supply(stats_data: &GlobalStats) -> u64 {
+    stats_data.tokens_minted.saturating_sub(stats_data.burned_total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::borsh::try_from_slice_unchecked;
+
+    #[test]
+    fn test_accounted_supply_is_minted_less_burned() {
+        let mut stats_data = try_from_slice_unchecked::<GlobalStats>(&[0u8; GlobalStats::SIZE]).unwrap();
+        stats_data.tokens_minted = 1_000;
+        stats_data.burned_total = 300;
+        assert_eq!(accounted_supply(&stats_data), 700);
+
+        // Stats initialized after burns started can't go below zero
+        stats_data.burned_total = 2_000;
+        assert_eq!(accounted_supply(&stats_data), 0);
+    }
+}
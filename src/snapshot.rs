@@ -0,0 +1,92 @@
+use crate::state::{EpochTotals, GlobalStats, IntroEpochCounts, IntroSummaryCache};
+
+// Epoch boundaries are noticed lazily: the first handler to load the counters in a new
+// epoch rolls them, before making its own change, so what it records is how they stood
+// when the last active epoch closed. Epochs nothing happened in get no entry, their
+// counters are the same as the entry before. Each account keeps its most recent epochs.
+pub fn roll_stats(stats_data: &mut GlobalStats, epoch: u64) {
+    if let Some(closed_epoch) = closed_epoch(&mut stats_data.snapshot_epoch, epoch) {
+        let totals = EpochTotals {
+            epoch: closed_epoch,
+            intros: stats_data.intros,
+            comments: stats_data.comments,
+            reactions: stats_data.reactions,
+            tokens_minted: stats_data.tokens_minted,
+            active_wallets: stats_data.active_wallets,
+        };
+        push(&mut stats_data.epochs, GlobalStats::MAX_EPOCHS, totals);
+    }
+}
+
+pub fn roll_summary(summary_data: &mut IntroSummaryCache, epoch: u64) {
+    if let Some(closed_epoch) = closed_epoch(&mut summary_data.snapshot_epoch, epoch) {
+        let counts = IntroEpochCounts {
+            epoch: closed_epoch,
+            comment_count: summary_data.comment_count,
+            like_count: summary_data.like_count,
+            boost_score: summary_data.boost_score,
+        };
+        push(&mut summary_data.epochs, IntroSummaryCache::MAX_EPOCHS, counts);
+    }
+}
+
+// The epoch that just closed, if the counters were last rolled in an earlier one.
+// Accounts that were never rolled start from the current epoch with nothing to record.
+fn closed_epoch(snapshot_epoch: &mut u64, epoch: u64) -> Option<u64> {
+    let last_epoch = *snapshot_epoch;
+    if epoch <= last_epoch {
+        return None;
+    }
+    *snapshot_epoch = epoch;
+
+    if last_epoch == 0 {
+        None
+    } else {
+        Some(last_epoch)
+    }
+}
+
+fn push<T>(ring: &mut Vec<T>, max: usize, entry: T) {
+    if ring.len() >= max {
+        ring.drain(..=ring.len() - max);
+    }
+    ring.push(entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use solana_program::borsh::try_from_slice_unchecked;
+
+    #[test]
+    fn test_counters_roll_once_per_epoch_and_fit() {
+        let mut stats_data = try_from_slice_unchecked::<GlobalStats>(&[0u8; GlobalStats::SIZE]).unwrap();
+        roll_stats(&mut stats_data, 5);
+        assert!(stats_data.epochs.is_empty());
+
+        stats_data.comments = 3;
+        roll_stats(&mut stats_data, 5);
+        assert!(stats_data.epochs.is_empty());
+
+        roll_stats(&mut stats_data, 8);
+        stats_data.comments = 4;
+        assert_eq!(stats_data.epochs.len(), 1);
+        assert_eq!((stats_data.epochs[0].epoch, stats_data.epochs[0].comments), (5, 3));
+
+        for epoch in 9..40 {
+            roll_stats(&mut stats_data, epoch);
+        }
+        assert_eq!(stats_data.epochs.len(), GlobalStats::MAX_EPOCHS);
+        assert_eq!(stats_data.epochs.last().unwrap().epoch, 38);
+        assert!(stats_data.try_to_vec().unwrap().len() <= GlobalStats::SIZE);
+
+        let mut summary_data = try_from_slice_unchecked::<IntroSummaryCache>(&[0u8; IntroSummaryCache::SIZE]).unwrap();
+        summary_data.snapshot_epoch = 1;
+        for epoch in 2..40 {
+            roll_summary(&mut summary_data, epoch);
+        }
+        assert_eq!(summary_data.epochs.len(), IntroSummaryCache::MAX_EPOCHS);
+        assert!(summary_data.try_to_vec().unwrap().len() <= IntroSummaryCache::SIZE);
+    }
+}
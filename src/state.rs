@@ -1,5 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{program_pack::{IsInitialized, Sealed}, pubkey::Pubkey};
+use crate::constants::{DEFAULT_INTRO_REWARD, INTRO_ACCOUNT_LEN};
+use crate::instruction::ConfigParams;
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct StudentIntroState {
@@ -8,23 +10,619 @@ pub struct StudentIntroState {
     pub is_initialized: bool,
     pub name: String,
     pub message: String,
+    // Set once a cascade delete has started; the intro takes no new comments or edits
+    pub closing: bool,
+    // Number of this intro's comment accounts closed so far
+    pub closed_comments: u64,
+    // Zero for intros created before the slot was recorded
+    pub created_slot: u64,
+    pub unique_commenters: u64,
+    // Batched in by the configured indexer, not counted per view on-chain
+    pub views: u64,
+    // Comment pages opened so far and closed by a cascade delete
+    pub comment_pages: u64,
+    pub closed_pages: u64,
+    // Cohort whose mint pays this intro's rewards. Empty for the default cohort.
+    pub cohort: String,
+    // Minted to the reviewer for creating the intro, burned again by an early delete.
+    // Zero for intros created before it was recorded, which earned 10 tokens.
+    pub intro_reward: u64,
+    // Template the intro was cloned from, with one answer per template prompt and the
+    // template's tags. Unset and empty for intros written freely.
+    pub template: Pubkey,
+    pub answers: Vec<String>,
+    pub tags: Vec<String>,
+    // Normalized hash of the message, registered at ["content", hash]. Zero for intros
+    // written before content was registered.
+    pub message_hash: [u8; 32],
+    // Set once the intro is listed in its tags' registries by IndexIntroTags
+    pub tags_indexed: bool,
+    // Slot the intro stays featured until, set by winning a featured-slot auction
+    pub featured_until: u64,
+    // Study group the reviewer was in when the intro was created, if any
+    pub group: Pubkey,
+    // What creating the intro actually minted, zero while minting was halted. None for
+    // intros created before it was recorded, which were minted intro_reward.
+    pub reward_minted: Option<u64>,
+    // Cohort the creation reward was minted in, once ReassignCohort moved the intro out of
+    // it. None while that is the intro's cohort.
+    pub minted_in: Option<String>,
+    // Comments added since they stopped being counted by the intro's counter, which
+    // holds the count from before. comment_total adds the two.
+    pub comments_added: u64,
+    // How the intro's comment page slots are seeded, see
+    // StudentIntroCommentCounter::slot_seed. None for intros from before it was recorded
+    // here, whose counter holds it until MigrateCommentSeeds copies it over.
+    pub comment_seed_version: Option<u8>,
 }
 
+// Lives at ["content", hash] for every intro message in use, so a copied intro finds
+// its hash already taken
 #[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContentEntry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub hash: [u8; 32],
+    pub intro: Pubkey,
+    pub author: Pubkey,
+    pub slot: u64,
+}
+
+// Lives at ["tag", tag_hash]. count is the append cursor: the next intro tagged with it
+// goes to page count / TagPage::CAPACITY.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TagIndex {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub tag_hash: [u8; 32],
+    pub tag: String,
+    pub count: u64,
+}
+
+// Lives at ["tag", tag_hash, page]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TagPage {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub tag_hash: [u8; 32],
+    pub page: u64,
+    pub intros: Vec<Pubkey>,
+}
+
+// Lives at ["template", id]. Published by an instructor and never changed afterwards, so
+// intros cloned from it keep matching its prompts.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroTemplate {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub id: u64,
+    pub author: Pubkey,
+    pub prompts: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+// Lives at ["cohort", id]. Pays the rewards of intros created in it from its own mint at
+// ["token_mint", id], minted by ["token_auth", id].
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Cohort {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub id: String,
+    pub intro_reward: u64,
+    pub comment_reward: u64,
+    // Intros created in or moved into the cohort, less those moved out. Deletes don't
+    // take the cohort account, so deleted intros still count.
+    pub member_count: u64,
+}
+
+// Lives at ["group", name]. Members who route rewards to it send reward_share_bps of
+// each comment reward to the group PDA's token account, which the manager pays out of
+// with WithdrawGroupVault.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Group {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub name: String,
+    pub manager: Pubkey,
+    pub reward_share_bps: u16,
+    pub member_count: u64,
+    // The group PDA's token account for the default cohort's mint. Other cohorts pay the
+    // group PDA's token account for their own mint.
+    pub vault: Pubkey,
+    // Comments members made while in the group, and the reward share they routed to it
+    pub comments: u64,
+    pub rewards_routed: u64,
+}
+
+// Lives at [group, "member", wallet] while the wallet is in the group
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct GroupMembership {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub group: Pubkey,
+    pub member: Pubkey,
+    pub joined_slot: u64,
+}
+
+// Lives at ["banned_phrases"]. Hashes of normalized phrases comments may not be.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct BannedPhrases {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub hashes: Vec<[u8; 32]>,
+}
+
+// First-comment marker at [intro, "commenter", commenter], one per distinct commenter
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CommenterRecord {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub commenter: Pubkey,
+    pub comment_count: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Config {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    // Deleting an intro within this many slots of creating it burns the intro reward
+    pub delete_penalty_window_slots: u64,
+    // Base bonus paid to the intro owner for each new distinct commenter, scaled by
+    // the square root of the distinct commenter count. Zero disables the bonus.
+    pub unique_commenter_bonus: u64,
+    // Mint rewards into the treasury and let users claim them instead of minting directly
+    pub accrue_rewards: bool,
+    // Pending rewards left unclaimed this long can be expired by anyone. Zero never expires.
+    pub claim_window_slots: u64,
+    // Flat lamport fee for creating an intro, paid to fee_receiver. Zero disables it.
+    pub creation_fee_lamports: u64,
+    pub fee_receiver: Pubkey,
+    // When set, the fee is creation_fee_usd_cents converted at this Pyth SOL/USD price
+    // instead of the flat lamport amount
+    pub price_feed: Pubkey,
+    pub creation_fee_usd_cents: u64,
+    pub max_price_staleness_slots: u64,
+    pub max_price_confidence_bps: u64,
+    // Oracle program that owns raffle randomness accounts (ORAO VRF)
+    pub randomness_program: Pubkey,
+    // Off-chain indexer allowed to report intro views. Unset disables IncrementViews.
+    pub indexer: Pubkey,
+    // Comments with fewer characters than this are accepted but earn nothing
+    pub min_reward_comment_chars: u64,
+    // Course id this deployment's PDAs are seeded with. Empty for the original deployment.
+    pub namespace: String,
+    // Number of audit entries written, and the index the next one is seeded with
+    pub audit_count: u64,
+    // Thank-you reward minted to a commenter when the intro owner acknowledges their
+    // comment. Zero disables it.
+    pub acknowledge_reward: u64,
+    // Set once by FinalizeCourse. Content and settings can't change afterwards, but
+    // rewards can still be claimed and intros deleted for their rent.
+    pub finalized: bool,
+    // Extra keys that, with the admin, make up the admin set. High-value actions need
+    // admin_threshold of the set to sign; zero or one leaves them to the admin alone.
+    pub co_admins: Vec<Pubkey>,
+    pub admin_threshold: u8,
+    // Commenters can edit or delete a comment only this many slots after posting it. The
+    // admin isn't bound by it. Zero leaves comments editable at any time.
+    pub edit_window_slots: u64,
+    // Minted to a commenter once their comment has reactions from this many distinct
+    // wallets. Either being zero disables the bonus.
+    pub reaction_reward_threshold: u16,
+    pub reaction_reward: u64,
+    // The reaction, unique commenter and acknowledge bonuses aren't minted when they would
+    // take the mint's supply past this. Zero leaves them uncapped.
+    pub bonus_supply_cap: u64,
+    // Changes to reward amounts, caps, fees and this delay itself have to be proposed
+    // and can only be applied this many slots later. Zero lets UpdateConfig make them.
+    pub config_change_delay_slots: u64,
+    // Paid from the ["migration_bounty"] vault to whoever cranks MigrateBatch, for each
+    // account it brings up to the current layout
+    pub migration_bounty_lamports: u64,
+    // Each wallet can post this many anonymous comments per window of slots. Either
+    // being zero turns anonymous comments off.
+    pub anonymous_window_slots: u64,
+    pub anonymous_comments_per_window: u16,
+    // Set by SetRewardsHalted to stop every reward mint at once, while intros and
+    // comments go on working without earning anything
+    pub rewards_halted: bool,
+    // Program called with every new intro, see hook::notify_intro_created. The default
+    // key registers none.
+    pub intro_hook_program: Pubkey,
+    // Lets ReconcileRewards halt minting when the mints hold more than the stats account
+    // for. Off for deployments whose stats were initialized after tokens were minted.
+    pub halt_on_supply_mismatch: bool,
+    // Cohorts InitializeCohort created, so FinalizeCourse can tell every cohort mint was
+    // passed to revoke. Cohorts created before they were counted aren't in it.
+    pub cohorts_created: u64,
+}
+
+// Lives at ["pending_config"] between ProposeConfigChange and ApplyConfigChange, so the
+// settings about to take effect are public before they do
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PendingConfigChange {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub proposer: Pubkey,
+    pub params: ConfigParams,
+    pub proposed_slot: u64,
+    pub apply_after_slot: u64,
+    // Settings the config had when the change was proposed. Applying takes over only the
+    // ones params changes from these, so updates made in the meantime stay.
+    pub base: ConfigParams,
+}
+
+// Lives at ["audit", index]. Written once per admin-gated change and never modified, so
+// participants can check what staff did and when.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AuditEntry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub actor: Pubkey,
+    pub action: u8,
+    pub slot: u64,
+}
+
+// Lives at ["raffle", id]. Comments made between start_slot and end_slot earn tickets.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Raffle {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub id: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub prize: u64,
+    pub ticket_count: u64,
+    // Committed after the ticket window closes, while still unfulfilled
+    pub randomness_account: Pubkey,
+    pub drawn: bool,
+    pub winning_ticket: u64,
+    pub winner: Pubkey,
+}
+
+// Lives at ["auction", id] with its escrow token account at [auction, "vault"]. Bids
+// are taken between start_slot and end_slot, and the winning intro is featured until
+// feature_end_slot.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Auction {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub id: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub feature_end_slot: u64,
+    pub highest_bid: u64,
+    pub highest_bidder: Pubkey,
+    pub highest_intro: Pubkey,
+    pub bid_count: u64,
+    pub settled: bool,
+}
+
+// Lives at [auction, "bid", bidder]. Raising a bid tops up the same account; an outbid
+// bidder claims the escrowed amount back, which closes it.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AuctionBid {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub intro: Pubkey,
+    pub amount: u64,
+}
+
+// Lives at [raffle, "ticket", index]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RaffleTicket {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub raffle: Pubkey,
+    pub index: u64,
+    pub owner: Pubkey,
+}
+
+// Lives at ["thread", task]. Holds the schedule an automation network triggers the
+// task's crank on; the cranks themselves are permissionless and safe to repeat.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MaintenanceThread {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub task: u8,
+    pub interval_slots: u64,
+    pub next_run_slot: u64,
+    pub last_run_slot: u64,
+    pub run_count: u64,
+}
+
+// Lives at [intro, "comments", page_index]. Comments on this page live at
+// [page, slot_in_page] for slot_in_page below count.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CommentPage {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub page_index: u64,
+    pub count: u64,
+    // Paid the page's rent and gets it back when the intro is deleted
+    pub payer: Pubkey,
+}
+
+#[derive(BorshSerialize)]
 pub struct StudentIntroCommentCounter {
     pub discriminator: String,
     pub is_intialized: bool,
+    // Comments from before the intro counted its own, and ones ResyncCounter found
     pub counter: u64,
+    // How the intro's comment slot seeds encode the slot index, see slot_seed
+    pub seed_version: u8,
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshSerialize)]
 pub struct StudentIntroComment {
     pub discriminator: String,
     pub is_initialized: bool,
     pub review: Pubkey,
     pub commenter: Pubkey,
     pub comment: String,
+    // Paged comments' place across the intro's pages, keyed ones' index in the commenter's
+    // history. Legacy comments were numbered by the intro's counter.
     pub count: u64,
+    // Optional gist or repo commit the feedback points at. Empty when there is none.
+    pub link: String,
+    // Hash of the referenced content so readers can check the link still shows it.
+    // All zeroes when there is none.
+    pub content_hash: [u8; 32],
+    // Set by the intro owner to show the feedback was read
+    pub acknowledged: bool,
+    // Translations attached at [comment, "translation", language]
+    pub translations: u8,
+    // Zero for comments created before the slot was recorded
+    pub created_slot: u64,
+    // One of the VISIBILITY_* levels. Clients hide non-public feedback from other readers;
+    // only staff can leave it.
+    pub visibility: u8,
+    // Distinct wallets that reacted, each with a marker at [comment, "reaction", wallet]
+    pub reactions: u32,
+    // Set once the reaction bonus was minted to the commenter
+    pub reaction_rewarded: bool,
+    // The commenter is then a salted hash commitment, not a wallet, so nobody can edit
+    // the comment or be rewarded for it
+    pub anonymous: bool,
+}
+
+// Lives at [comment, "reaction", reactor], so each wallet reacts to a comment once
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CommentReaction {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub comment: Pubkey,
+    pub reactor: Pubkey,
+    pub slot: u64,
+}
+
+// Lives at [comment, "translation", language] and is sized to its contents
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CommentTranslation {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub comment: Pubkey,
+    pub author: Pubkey,
+    pub language: String,
+    pub text: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct UserRecord {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub comment_count: u64,
+    pub intro_count: u64,
+    pub transfer_count: u64,
+    // Accrue-and-claim mode: rewards held in the treasury for this wallet
+    pub pending_rewards: u64,
+    // Slot of the oldest unclaimed accrual, the claim window runs from here
+    pub pending_since_slot: u64,
+    // Wallet whose ATA receives this user's rewards, the default key means the wallet itself
+    pub reward_destination: Pubkey,
+    // Study group whose vault takes its share of this user's comment rewards, if any.
+    // Only the group the user is a member of.
+    pub reward_group: Pubkey,
+    // Study group the user is a member of, if any
+    pub group: Pubkey,
+    // Events emitted by this wallet's own actions so far, the cursor of the latest one
+    pub action_seq: u64,
+    // Anonymous comments posted since the window starting at this slot
+    pub anonymous_window_start_slot: u64,
+    pub anonymous_comments_in_window: u16,
+    // Slot the wallet asked for its content to be erased at, zero if it never did
+    pub erasure_requested_slot: u64,
+}
+
+// Lives at [sender, "transfer", index] for every TransferReward
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TransferReceipt {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+// Points at the comment a user made at position `index` of their own history.
+// Lives at [commenter, "comment", index] and outlives the comment until anyone closes
+// it with CloseCommentIndex.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct UserCommentIndex {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub commenter: Pubkey,
+    pub index: u64,
+    pub review: Pubkey,
+    pub comment: Pubkey,
+}
+
+// Points at the intro a reviewer created at position `index`.
+// Lives at [reviewer, "intro", index] and outlives the intro if it is closed.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct UserIntroIndex {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub reviewer: Pubkey,
+    pub index: u64,
+    pub review: Pubkey,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct GlobalStats {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intros: u64,
+    pub comments: u64,
+    pub reactions: u64,
+    pub tokens_minted: u64,
+    // Counts wallets when their user record is created; a rough lower bound
+    pub active_wallets: u64,
+    // Never decremented, used as the next ["intro", index] slot
+    pub intros_created: u64,
+    // Accrued in the treasury and still claimable
+    pub rewards_pending: u64,
+    // Released back to the treasury after the claim window passed
+    pub rewards_expired: u64,
+    // Lamports paid in through TopUpRent to keep accounts rent exempt
+    pub rent_topped_up: u64,
+    // Reward tokens burned by every sink together
+    pub burned_total: u64,
+    // Epoch the counters were last rolled in, and the counters as each earlier active
+    // epoch closed, oldest first
+    pub snapshot_epoch: u64,
+    pub epochs: Vec<EpochTotals>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EpochTotals {
+    pub epoch: u64,
+    pub intros: u64,
+    pub comments: u64,
+    pub reactions: u64,
+    pub tokens_minted: u64,
+    pub active_wallets: u64,
+}
+
+// Lives at [intro, "summary"]. A small fixed-size copy of what the dashboard lists, kept
+// current by the handlers that change it. Closed when the intro is deleted.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroSummaryCache {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub reviewer: Pubkey,
+    pub name_hash: [u8; 32],
+    pub comment_count: u64,
+    // Reactions to the intro's comments
+    pub like_count: u64,
+    // Winning bids burned to feature the intro
+    pub boost_score: u64,
+    pub last_activity_slot: u64,
+    // Rolled like the global stats' epochs
+    pub snapshot_epoch: u64,
+    pub epochs: Vec<IntroEpochCounts>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct IntroEpochCounts {
+    pub epoch: u64,
+    pub comment_count: u64,
+    pub like_count: u64,
+    pub boost_score: u64,
+}
+
+// Lives at [intro, "editor", editor] while the reviewer lets that wallet edit the intro
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroEditor {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub editor: Pubkey,
+    pub granted_slot: u64,
+}
+
+// Lives at [intro, "attributes"]. Short key/value pairs frontends can add without a
+// change to the intro's own layout, kept sorted by key.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroAttributes {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    // Paid the rent and gets it back when the intro is deleted
+    pub payer: Pubkey,
+    pub entries: Vec<IntroAttribute>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct IntroAttribute {
+    pub key: String,
+    pub value: String,
+}
+
+// Lives at [from_intro, to_intro] once the first intro's message mentions the second
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroMention {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub created_slot: u64,
+}
+
+// Program-wide intro index for paged exports, lives at ["intro", index]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroIndex {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub review: Pubkey,
+}
+
+// Return data layouts for ExportPage
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntroSummary {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub name_hash: [u8; 32],
+    pub comment_count: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ExportPage {
+    pub next_cursor: u64,
+    pub intros: Vec<IntroSummary>,
+}
+
+// Return data of CheckAccountHealth. The type is the account's discriminator, and the
+// tag its account_type constant.
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+pub struct AccountHealth {
+    pub account_type: String,
+    pub tag: u8,
+    pub version: u8,
+    pub rent_exempt: bool,
+    pub rent_shortfall: u64,
+    pub needs_migration: bool,
+}
+
+// Return data of a dry run: what the instruction would cost the signer and pay them.
+// Only AddStudentIntro and UpdateStudentIntro take the dry-run flag; comments don't.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Default, PartialEq)]
+pub struct CostEstimate {
+    pub rent_lamports: u64,
+    pub fee_lamports: u64,
+    pub accounts_created: u8,
+    pub reward: u64,
 }
 
 impl Sealed for StudentIntroState {}
@@ -49,32 +647,734 @@ impl IsInitialized for StudentIntroComment {
     }
 }
 
+impl IsInitialized for UserRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for CommenterRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroTemplate {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for TagIndex {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for TagPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ContentEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AuditEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Group {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for GroupMembership {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Cohort {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for BannedPhrases {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for CommentPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Raffle {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for MaintenanceThread {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Auction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AuctionBid {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for RaffleTicket {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for GlobalStats {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroSummaryCache {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroEditor {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroAttributes {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroMention {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroIndex {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for UserIntroIndex {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for TransferReceipt {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for UserCommentIndex {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
 impl StudentIntroState {
     pub const DISCRIMINATOR: &'static str = "studentintro";
+    // Intros are allocated at a fixed size so edits can grow them in place
+    pub const ACCOUNT_LEN: usize = INTRO_ACCOUNT_LEN;
 
     pub fn get_account_size(name: String, message: String) -> usize {
                 // 4 bytes to store the size of the subsequent dynamic data (string)
-        return (4 + StudentIntroState::DISCRIMINATOR.len())  
+        (4 + StudentIntroState::DISCRIMINATOR.len())
+            + 32 // 32 bytes for the reviewer key
             + 1 // 1 byte for is_initialized (boolean)
             + (4 + name.len()) // 4 bytes to store the size of the subsequent dynamic data (string)
-            + (4 + message.len()); // Same as above
+            + (4 + message.len()) // Same as above
+            + 1 // 1 byte for closing (boolean)
+            + 8 // 8 bytes for closed_comments (u64)
+            + 8 // 8 bytes for created_slot (u64)
+            + 8 // 8 bytes for unique_commenters (u64)
+            + 8 // 8 bytes for views (u64)
+            + 8 // 8 bytes for comment_pages (u64)
+            + 8 // 8 bytes for closed_pages (u64)
+            + (4 + Cohort::MAX_ID_LEN) // Room for the longest cohort id
+            + 8 // 8 bytes for intro_reward (u64)
+            + 32 // 32 bytes for the template key
+            + 4 // 4 bytes for the length of answers (vec)
+            + 4 // 4 bytes for the length of tags (vec)
+            + 32 // 32 bytes for the message hash
+            + 1 // 1 byte for tags_indexed (boolean)
+            + 8 // 8 bytes for featured_until (u64)
+            + 32 // 32 bytes for the group key
+            + 9 // 9 bytes for reward_minted (Option<u64>)
+            + (1 + 4 + Cohort::MAX_ID_LEN) // Room for minted_in holding the longest cohort id
+            + 8 // 8 bytes for comments_added (u64)
+            + 2 // 2 bytes for comment_seed_version (Option<u8>)
+    }
+
+    // Every comment the intro has had, closed ones included
+    pub fn comment_total(&self, counter_data: &StudentIntroCommentCounter) -> u64 {
+        counter_data.counter.saturating_add(self.comments_added)
+    }
+
+    // The creation reward an early delete takes back
+    pub fn minted_reward(&self) -> u64 {
+        match self.reward_minted {
+            Some(amount) => amount,
+            None if self.intro_reward == 0 => DEFAULT_INTRO_REWARD,
+            None => self.intro_reward,
+        }
+    }
+
+    // Cohort whose mint holds the creation reward
+    pub fn minted_cohort(&self) -> &str {
+        self.minted_in.as_deref().unwrap_or(&self.cohort)
+    }
+
+    // What answers and tags add on top of get_account_size
+    pub fn template_content_size(answers: &[String], tags: &[String]) -> usize {
+        answers.iter().chain(tags).map(|text| 4 + text.len()).sum()
     }
 }
 
 impl StudentIntroComment {
     pub const DISCRIMINATOR: &'static str = "comment";
+    pub const MAX_LINK_LEN: usize = 200;
+    pub const LINK_SCHEME: &'static str = "https://";
+    // Layouts in the order fields were appended: attachments, acknowledgement,
+    // translation count, created slot, visibility, reactions, anonymity
+    pub const LAYOUT_VERSION: u8 = 7;
+
+    pub const VISIBILITY_PUBLIC: u8 = 0;
+    // Shown to the intro owner, the commenter and staff
+    pub const VISIBILITY_OWNER: u8 = 1;
+    pub const VISIBILITY_STAFF: u8 = 2;
 
-    pub fn get_account_size(comment: String) -> usize {
-        return (4 + StudentIntroComment::DISCRIMINATOR.len()) 
+    pub fn get_account_size(comment: String, link: String) -> usize {
+        (4 + StudentIntroComment::DISCRIMINATOR.len())
         + 1  // 1 byte for is_initialized (boolean)
         + 32 // 32 bytes for the movie review account key 
         + 32 // 32 bytes for the commenter key size
         + (4 + comment.len()) // 4 bytes to store the size of the subsequent dynamic data (string)
-        + 8; // 8 bytes for the count (u64)
+        + 8 // 8 bytes for the count (u64)
+        + (4 + link.len()) // Same as the comment
+        + 32 // 32 bytes for the content hash
+        + 1 // 1 byte for acknowledged (boolean)
+        + 1 // 1 byte for the translation count (u8)
+        + 8 // 8 bytes for created_slot (u64)
+        + 1 // 1 byte for visibility (u8)
+        + 4 // 4 bytes for the reaction count (u32)
+        + 1 // 1 byte for reaction_rewarded (boolean)
+        + 1 // 1 byte for anonymous (boolean)
+    }
+
+    // Layout the comment was stored with, from how many of the appended fields its
+    // account of data_len bytes has room for
+    pub fn layout_version(&self, data_len: usize) -> u8 {
+        let full_len = StudentIntroComment::get_account_size(self.comment.clone(), self.link.clone());
+        let mut missing = full_len.saturating_sub(data_len);
+        let mut version = StudentIntroComment::LAYOUT_VERSION;
+        for field_len in [1, 4 + 1, 1, 8, 1, 1, 4 + self.link.len() + 32] {
+            if missing < field_len {
+                break;
+            }
+            missing -= field_len;
+            version -= 1;
+        }
+        version
+    }
+}
+
+impl CommentReaction {
+    pub const DISCRIMINATOR: &'static str = "reaction";
+    pub const SIZE: usize = (4 + CommentReaction::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the comment key
+        + 32 // 32 bytes for the reactor key
+        + 8; // 8 bytes for the slot (u64)
+}
+
+impl CommentTranslation {
+    pub const DISCRIMINATOR: &'static str = "translation";
+    pub const MAX_PER_COMMENT: usize = 5;
+    pub const MAX_LANGUAGE_LEN: usize = 10;
+    pub const MAX_TEXT_LEN: usize = 500;
+
+    pub fn get_account_size(language: &str, text: &str) -> usize {
+        (4 + CommentTranslation::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the comment key
+        + 32 // 32 bytes for the author key
+        + (4 + language.len()) // 4 bytes to store the size of the subsequent dynamic data (string)
+        + (4 + text.len()) // Same as the language
+    }
+}
+
+impl IsInitialized for CommentTranslation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Comment accounts are sized to their contents, so ones created before attachments
+// existed end after the count, ones created before acknowledgements after the hash,
+// ones created before translations after the flag, ones created before the slot was
+// recorded after the translation count, ones created before visibility after the
+// slot and ones created before reactions after the visibility. Those read back with the
+// missing fields empty, which makes them public.
+impl BorshDeserialize for StudentIntroComment {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let discriminator = String::deserialize(buf)?;
+        let is_initialized = bool::deserialize(buf)?;
+        let review = Pubkey::deserialize(buf)?;
+        let commenter = Pubkey::deserialize(buf)?;
+        let comment = String::deserialize(buf)?;
+        let count = u64::deserialize(buf)?;
+        let (link, content_hash) = if buf.is_empty() {
+            (String::new(), [0u8; 32])
+        } else {
+            (String::deserialize(buf)?, <[u8; 32]>::deserialize(buf)?)
+        };
+        let acknowledged = if buf.is_empty() { false } else { bool::deserialize(buf)? };
+        let translations = if buf.is_empty() { 0 } else { u8::deserialize(buf)? };
+        let created_slot = if buf.is_empty() { 0 } else { u64::deserialize(buf)? };
+        let visibility = if buf.is_empty() {
+            StudentIntroComment::VISIBILITY_PUBLIC
+        } else {
+            u8::deserialize(buf)?
+        };
+        let (reactions, reaction_rewarded) = if buf.is_empty() {
+            (0, false)
+        } else {
+            (u32::deserialize(buf)?, bool::deserialize(buf)?)
+        };
+        let anonymous = if buf.is_empty() { false } else { bool::deserialize(buf)? };
+
+        Ok(StudentIntroComment {
+            discriminator,
+            is_initialized,
+            review,
+            commenter,
+            comment,
+            count,
+            link,
+            content_hash,
+            acknowledged,
+            translations,
+            created_slot,
+            visibility,
+            reactions,
+            reaction_rewarded,
+            anonymous,
+        })
     }
 }
 
+impl CommentPage {
+    pub const DISCRIMINATOR: &'static str = "commentpage";
+    pub const CAPACITY: u64 = 50;
+    pub const SIZE: usize = (4 + CommentPage::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the student intro account key
+        + 8 // 8 bytes for the page index (u64)
+        + 8 // 8 bytes for the count (u64)
+        + 32; // 32 bytes for the payer key
+}
+
 impl StudentIntroCommentCounter {
     pub const DISCRIMINATOR: &'static str = "counter";
-    pub const SIZE: usize = (4 + StudentIntroCommentCounter::DISCRIMINATOR.len()) + 1 + 8;
+    pub const SIZE: usize = (4 + StudentIntroCommentCounter::DISCRIMINATOR.len()) + 1 + 8 + 1;
+
+    // Intros from before seed versions were recorded encode comment slots big-endian.
+    // New intros encode them little-endian, and MigrateCommentSeeds moves old ones over.
+    pub const SEED_VERSION_BIG_ENDIAN: u8 = 0;
+    pub const SEED_VERSION_LITTLE_ENDIAN: u8 = 1;
+    pub const CURRENT_SEED_VERSION: u8 = StudentIntroCommentCounter::SEED_VERSION_LITTLE_ENDIAN;
+
+    // Seed a new comment at this page slot is derived with
+    pub fn slot_seed(&self, slot: u64) -> [u8; 8] {
+        StudentIntroCommentCounter::versioned_slot_seed(self.seed_version, slot)
+    }
+
+    pub fn versioned_slot_seed(seed_version: u8, slot: u64) -> [u8; 8] {
+        if seed_version == StudentIntroCommentCounter::SEED_VERSION_BIG_ENDIAN {
+            slot.to_be_bytes()
+        } else {
+            slot.to_le_bytes()
+        }
+    }
+}
+
+// Counters created before seed versions were recorded end after the count and read back
+// as big-endian
+impl BorshDeserialize for StudentIntroCommentCounter {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let discriminator = String::deserialize(buf)?;
+        let is_intialized = bool::deserialize(buf)?;
+        let counter = u64::deserialize(buf)?;
+        let seed_version = if buf.is_empty() {
+            StudentIntroCommentCounter::SEED_VERSION_BIG_ENDIAN
+        } else {
+            u8::deserialize(buf)?
+        };
+
+        Ok(StudentIntroCommentCounter {
+            discriminator,
+            is_intialized,
+            counter,
+            seed_version,
+        })
+    }
+}
+
+impl UserRecord {
+    pub const DISCRIMINATOR: &'static str = "user";
+    // Allocated with headroom so new per-user fields can be appended without a realloc
+    pub const SIZE: usize = 256;
+
+    // Owner that reward token accounts are checked against
+    pub fn reward_owner(&self) -> Pubkey {
+        if self.reward_destination == Pubkey::default() {
+            self.wallet
+        } else {
+            self.reward_destination
+        }
+    }
+}
+
+impl UserCommentIndex {
+    pub const DISCRIMINATOR: &'static str = "usercomment";
+    pub const SIZE: usize = (4 + UserCommentIndex::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the commenter key
+        + 8 // 8 bytes for the index (u64)
+        + 32 // 32 bytes for the student intro account key
+        + 32; // 32 bytes for the comment account key
+}
+
+impl UserIntroIndex {
+    pub const DISCRIMINATOR: &'static str = "userintro";
+    pub const SIZE: usize = (4 + UserIntroIndex::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the reviewer key
+        + 8 // 8 bytes for the index (u64)
+        + 32; // 32 bytes for the student intro account key
+}
+
+impl CommenterRecord {
+    pub const DISCRIMINATOR: &'static str = "commenter";
+    pub const SIZE: usize = (4 + CommenterRecord::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the student intro account key
+        + 32 // 32 bytes for the commenter key
+        + 8; // 8 bytes for the comment count (u64)
+}
+
+impl BannedPhrases {
+    pub const DISCRIMINATOR: &'static str = "banned";
+    pub const MAX_HASHES: usize = 256;
+    pub const SIZE: usize = (4 + BannedPhrases::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + (4 + BannedPhrases::MAX_HASHES * 32); // 4 bytes for the vec length and 32 per hash
+}
+
+impl ContentEntry {
+    pub const DISCRIMINATOR: &'static str = "content";
+    pub const SIZE: usize = (4 + ContentEntry::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the content hash
+        + 32 // 32 bytes for the intro key
+        + 32 // 32 bytes for the author key
+        + 8; // 8 bytes for the slot (u64)
+}
+
+impl AuditEntry {
+    pub const DISCRIMINATOR: &'static str = "audit";
+    pub const SIZE: usize = (4 + AuditEntry::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 8 // 8 bytes for the index (u64)
+        + 32 // 32 bytes for the actor key
+        + 1 // 1 byte for the action code (u8)
+        + 8; // 8 bytes for the slot (u64)
+
+    pub const ACTION_UPDATE_CONFIG: u8 = 0;
+    pub const ACTION_INITIALIZE_BANNED_PHRASES: u8 = 1;
+    pub const ACTION_UPDATE_BANNED_PHRASES: u8 = 2;
+    pub const ACTION_INITIALIZE_THREAD: u8 = 3;
+    pub const ACTION_CREATE_RAFFLE: u8 = 4;
+    pub const ACTION_INITIALIZE_COHORT: u8 = 5;
+    pub const ACTION_REASSIGN_COHORT: u8 = 6;
+    pub const ACTION_RESYNC_COUNTER: u8 = 7;
+    pub const ACTION_PUBLISH_TEMPLATE: u8 = 8;
+    pub const ACTION_FINALIZE_COURSE: u8 = 9;
+    pub const ACTION_SET_ADMINS: u8 = 10;
+    pub const ACTION_CREATE_AUCTION: u8 = 11;
+    pub const ACTION_BURN_TREASURY: u8 = 12;
+    pub const ACTION_PROPOSE_CONFIG: u8 = 13;
+    pub const ACTION_APPLY_CONFIG: u8 = 14;
+    pub const ACTION_SET_REWARDS_HALTED: u8 = 15;
+}
+
+impl IntroTemplate {
+    pub const DISCRIMINATOR: &'static str = "template";
+    pub const MAX_PROMPTS: usize = 8;
+    pub const MAX_PROMPT_LEN: usize = 100;
+    pub const MAX_ANSWER_LEN: usize = 200;
+    pub const MAX_TAGS: usize = 8;
+    pub const MAX_TAG_LEN: usize = 24;
+    pub const SIZE: usize = (4 + IntroTemplate::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 8 // 8 bytes for the id (u64)
+        + 32 // 32 bytes for the author key
+        + (4 + IntroTemplate::MAX_PROMPTS * (4 + IntroTemplate::MAX_PROMPT_LEN)) // Longest prompts
+        + (4 + IntroTemplate::MAX_TAGS * (4 + IntroTemplate::MAX_TAG_LEN)); // Longest tags
+}
+
+impl TagIndex {
+    pub const DISCRIMINATOR: &'static str = "tagindex";
+    pub const SIZE: usize = (4 + TagIndex::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the tag hash
+        + (4 + IntroTemplate::MAX_TAG_LEN) // Longest tag
+        + 8; // 8 bytes for the count (u64)
+}
+
+impl TagPage {
+    pub const DISCRIMINATOR: &'static str = "tagpage";
+    pub const CAPACITY: u64 = 32;
+    pub const SIZE: usize = (4 + TagPage::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the tag hash
+        + 8 // 8 bytes for the page (u64)
+        + (4 + TagPage::CAPACITY as usize * 32); // A full page of intro keys
+}
+
+impl Cohort {
+    pub const DISCRIMINATOR: &'static str = "cohort";
+    // The id is a single PDA seed, so it is capped at the seed length limit
+    pub const MAX_ID_LEN: usize = 32;
+    pub const SIZE: usize = (4 + Cohort::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + (4 + Cohort::MAX_ID_LEN) // 4 bytes for the string length and the id
+        + 8 // 8 bytes for the intro reward (u64)
+        + 8 // 8 bytes for the comment reward (u64)
+        + 8; // 8 bytes for the member count (u64)
+}
+
+impl Group {
+    pub const DISCRIMINATOR: &'static str = "group";
+    pub const MAX_NAME_LEN: usize = 32;
+    // Allocated with headroom so new group fields can be appended without a realloc
+    pub const SIZE: usize = 256;
+}
+
+impl GroupMembership {
+    pub const DISCRIMINATOR: &'static str = "groupmember";
+    pub const SIZE: usize = (4 + GroupMembership::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the group key
+        + 32 // 32 bytes for the member key
+        + 8; // 8 bytes for the joined slot (u64)
+}
+
+impl Config {
+    pub const DISCRIMINATOR: &'static str = "config";
+    // Allocated with headroom so new settings can be appended without a realloc
+    pub const SIZE: usize = 512;
+    pub const MAX_CO_ADMINS: usize = 4;
+}
+
+impl PendingConfigChange {
+    pub const DISCRIMINATOR: &'static str = "pendingconfig";
+    // Allocated with headroom like the config, so new settings still fit twice over
+    pub const SIZE: usize = 1024;
+}
+
+impl Raffle {
+    pub const DISCRIMINATOR: &'static str = "raffle";
+    pub const SIZE: usize = (4 + Raffle::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 8 // 8 bytes for the id (u64)
+        + 8 // 8 bytes for the start slot (u64)
+        + 8 // 8 bytes for the end slot (u64)
+        + 8 // 8 bytes for the prize (u64)
+        + 8 // 8 bytes for the ticket count (u64)
+        + 32 // 32 bytes for the randomness account key
+        + 1 // 1 byte for drawn (boolean)
+        + 8 // 8 bytes for the winning ticket (u64)
+        + 32; // 32 bytes for the winner key
+}
+
+impl Auction {
+    pub const DISCRIMINATOR: &'static str = "auction";
+    pub const SIZE: usize = (4 + Auction::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 8 // 8 bytes for the id (u64)
+        + 8 // 8 bytes for the start slot (u64)
+        + 8 // 8 bytes for the end slot (u64)
+        + 8 // 8 bytes for the feature end slot (u64)
+        + 8 // 8 bytes for the highest bid (u64)
+        + 32 // 32 bytes for the highest bidder key
+        + 32 // 32 bytes for the highest bidder's intro key
+        + 8 // 8 bytes for the bid count (u64)
+        + 1; // 1 byte for settled (boolean)
+}
+
+impl AuctionBid {
+    pub const DISCRIMINATOR: &'static str = "auctionbid";
+    pub const SIZE: usize = (4 + AuctionBid::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the auction key
+        + 32 // 32 bytes for the bidder key
+        + 32 // 32 bytes for the intro key
+        + 8; // 8 bytes for the amount (u64)
+}
+
+impl RaffleTicket {
+    pub const DISCRIMINATOR: &'static str = "ticket";
+    pub const SIZE: usize = (4 + RaffleTicket::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the raffle account key
+        + 8 // 8 bytes for the index (u64)
+        + 32; // 32 bytes for the owner key
+}
+
+impl MaintenanceThread {
+    pub const DISCRIMINATOR: &'static str = "thread";
+    pub const SIZE: usize = (4 + MaintenanceThread::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 1 // 1 byte for the task (u8)
+        + 8 // 8 bytes for the interval (u64)
+        + 8 // 8 bytes for the next run slot (u64)
+        + 8 // 8 bytes for the last run slot (u64)
+        + 8; // 8 bytes for the run count (u64)
+
+    pub const TASK_CLEANUP_INTROS: u8 = 0;
+    pub const TASK_DRAW_RAFFLE: u8 = 1;
+    pub const TASK_EXPIRE_REWARDS: u8 = 2;
+}
+
+impl GlobalStats {
+    pub const DISCRIMINATOR: &'static str = "globalstats";
+    // Allocated with headroom so new totals can be appended without a realloc. Stats
+    // allocated at the earlier 256 bytes take no epoch snapshots until MigrateBatch grows
+    // them.
+    pub const SIZE: usize = 1024;
+    pub const MAX_EPOCHS: usize = 12;
+}
+
+impl EpochTotals {
+    pub const SIZE: usize = 8 * 6;
+}
+
+impl IntroIndex {
+    pub const DISCRIMINATOR: &'static str = "introindex";
+    pub const SIZE: usize = (4 + IntroIndex::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 8 // 8 bytes for the index (u64)
+        + 32; // 32 bytes for the student intro account key
+}
+
+impl IntroSummaryCache {
+    pub const DISCRIMINATOR: &'static str = "introsummary";
+    pub const SIZE: usize = (4 + IntroSummaryCache::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the student intro account key
+        + 32 // 32 bytes for the reviewer key
+        + 32 // 32 bytes for the name hash
+        + 8 // 8 bytes for the comment count (u64)
+        + 8 // 8 bytes for the like count (u64)
+        + 8 // 8 bytes for the boost score (u64)
+        + 8 // 8 bytes for the last activity slot (u64)
+        + 8 // 8 bytes for the snapshot epoch (u64)
+        + (4 + IntroEpochCounts::SIZE * IntroSummaryCache::MAX_EPOCHS); // epoch ring
+    pub const MAX_EPOCHS: usize = 12;
+}
+
+impl IntroEpochCounts {
+    pub const SIZE: usize = 8 * 4;
+}
+
+impl IntroEditor {
+    pub const DISCRIMINATOR: &'static str = "introeditor";
+    pub const SIZE: usize = (4 + IntroEditor::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the student intro account key
+        + 32 // 32 bytes for the editor key
+        + 8; // 8 bytes for the granted slot (u64)
+}
+
+impl IntroAttributes {
+    pub const DISCRIMINATOR: &'static str = "introattrs";
+    pub const MAX_ATTRIBUTES: usize = 8;
+    pub const MAX_KEY_LEN: usize = 16;
+    pub const MAX_VALUE_LEN: usize = 64;
+    pub const SIZE: usize = (4 + IntroAttributes::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the student intro account key
+        + 32 // 32 bytes for the payer key
+        + 4 + IntroAttributes::MAX_ATTRIBUTES
+            * (4 + IntroAttributes::MAX_KEY_LEN + 4 + IntroAttributes::MAX_VALUE_LEN); // entries
+}
+
+impl IntroMention {
+    pub const DISCRIMINATOR: &'static str = "intromention";
+    // Mentions one RecordMentions call takes
+    pub const MAX_MENTIONS: usize = 8;
+    pub const SIZE: usize = (4 + IntroMention::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the mentioning intro key
+        + 32 // 32 bytes for the mentioned intro key
+        + 8; // 8 bytes for created_slot (u64)
+}
+
+impl IntroSummary {
+    pub const SIZE: usize = 32 + 32 + 32 + 8;
+}
+
+impl ExportPage {
+    // Enough summaries to stay within MAX_RETURN_DATA alongside the cursor and vec length
+    pub const MAX_INTROS: usize = 9;
+}
+
+impl TransferReceipt {
+    pub const DISCRIMINATOR: &'static str = "transfer";
+    pub const SIZE: usize = (4 + TransferReceipt::DISCRIMINATOR.len())
+        + 1 // 1 byte for is_initialized (boolean)
+        + 32 // 32 bytes for the sender key
+        + 32 // 32 bytes for the recipient key
+        + 8 // 8 bytes for the index (u64)
+        + 8 // 8 bytes for the amount (u64)
+        + 8; // 8 bytes for the slot (u64)
 }
@@ -0,0 +1,221 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    hash::hash,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::{COMMENT_SEED, SUMMARY_SEED};
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_program_account, close_account, resize_account};
+use crate::snapshot;
+use crate::state::{IntroSummaryCache, StudentIntroCommentCounter, StudentIntroState};
+
+// Creates the intro's summary from its current state. Likes and boosts start at zero.
+#[allow(clippy::too_many_arguments)]
+pub fn create<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    payer: &AccountInfo<'a>,
+    pda_summary: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    intro: &Pubkey,
+    review_data: &StudentIntroState,
+    comment_count: u64,
+    rent: &Rent,
+) -> ProgramResult {
+    let summary_bump = check_summary(program_id, ns, intro, pda_summary)?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_summary.key,
+            rent.minimum_balance(IntroSummaryCache::SIZE + account_type::TAG_LEN),
+            (IntroSummaryCache::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[payer.clone(), pda_summary.clone(), system_program.clone()],
+        &[&[ns, intro.as_ref(), SUMMARY_SEED, &[summary_bump]]],
+    )?;
+
+    let clock = Clock::get()?;
+    let summary_data = IntroSummaryCache {
+        discriminator: IntroSummaryCache::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *intro,
+        reviewer: review_data.reviewer,
+        name_hash: hash(review_data.name.as_bytes()).to_bytes(),
+        comment_count,
+        like_count: 0,
+        boost_score: 0,
+        last_activity_slot: clock.slot,
+        snapshot_epoch: clock.epoch,
+        epochs: Vec::new(),
+    };
+    account_type::write(&summary_data, pda_summary)?;
+
+    Ok(())
+}
+
+// Applies a change to the intro's summary and stamps the activity slot, rolling its
+// counters first if the epoch changed. Intros from before summaries have none until
+// SyncIntroSummary creates it, and ones from before epoch snapshots are too small until
+// it or MigrateBatch grows them, so those are skipped rather than failing the handler.
+pub fn touch(
+    program_id: &Pubkey,
+    ns: &[u8],
+    intro: &Pubkey,
+    pda_summary: &AccountInfo,
+    update: impl FnOnce(&mut IntroSummaryCache),
+) -> ProgramResult {
+    check_summary(program_id, ns, intro, pda_summary)?;
+    if account_type::body_len(pda_summary) < IntroSummaryCache::SIZE {
+        return Ok(());
+    }
+    let mut summary_data = load_summary(program_id, pda_summary)?;
+
+    let clock = Clock::get()?;
+    snapshot::roll_summary(&mut summary_data, clock.epoch);
+    update(&mut summary_data);
+    summary_data.last_activity_slot = clock.slot;
+    account_type::write(&summary_data, pda_summary)?;
+
+    Ok(())
+}
+
+// Returns the summary's rent to the reviewer once the intro starts closing. Batches after
+// the first find it already closed.
+pub fn close(
+    program_id: &Pubkey,
+    ns: &[u8],
+    intro: &Pubkey,
+    pda_summary: &AccountInfo,
+    reviewer: &AccountInfo,
+) -> ProgramResult {
+    check_summary(program_id, ns, intro, pda_summary)?;
+    if pda_summary.data_is_empty() {
+        return Ok(());
+    }
+    if pda_summary.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    close_account(pda_summary, reviewer)
+}
+
+// Creates a missing summary, or recounts an existing one's comments and name, from the
+// intro and its counter, growing it to the current size first. Anyone can pay for it, and the rent goes to the reviewer when
+// the intro is deleted.
+pub fn sync_intro_summary(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Syncing intro summary...");
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_summary = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    if pda_review.owner != program_id || pda_counter.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.closing {
+        return Err(reason!(ReviewError::UninitializedAccount, "Intro is not open"));
+    }
+    let (counter_pda, _counter_bump) = Pubkey::find_program_address(
+        &[ns, pda_review.key.as_ref(), COMMENT_SEED], program_id);
+    if counter_pda != *pda_counter.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for PDA"));
+    }
+    let counter_data = account_type::read::<StudentIntroCommentCounter>(pda_counter).unwrap();
+    let comment_count = review_data.comment_total(&counter_data).saturating_sub(review_data.closed_comments);
+
+    if pda_summary.data_is_empty() {
+        msg!("Creating summary of {}", pda_review.key);
+        return create(program_id, ns, payer, pda_summary, system_program, pda_review.key, &review_data,
+            comment_count, &Rent::get()?);
+    }
+    if pda_summary.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    resize_account(payer, pda_summary, system_program, IntroSummaryCache::SIZE, &Rent::get()?)?;
+
+    touch(program_id, ns, pda_review.key, pda_summary, |summary_data| {
+        summary_data.reviewer = review_data.reviewer;
+        summary_data.name_hash = hash(review_data.name.as_bytes()).to_bytes();
+        summary_data.comment_count = comment_count;
+    })
+}
+
+fn check_summary(program_id: &Pubkey, ns: &[u8], intro: &Pubkey, pda_summary: &AccountInfo) -> Result<u8, ProgramError> {
+    let (summary_pda, summary_bump) = Pubkey::find_program_address(&[ns, intro.as_ref(), SUMMARY_SEED], program_id);
+    if summary_pda != *pda_summary.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for intro summary PDA"));
+    }
+
+    Ok(summary_bump)
+}
+
+fn load_summary(program_id: &Pubkey, pda_summary: &AccountInfo) -> Result<IntroSummaryCache, ProgramError> {
+    if pda_summary.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let summary_data = account_type::read::<IntroSummaryCache>(pda_summary).unwrap();
+    if !summary_data.is_initialized() || summary_data.discriminator != IntroSummaryCache::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Intro summary is not initialized"));
+    }
+
+    Ok(summary_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use crate::state::IntroEpochCounts;
+
+    #[test]
+    fn test_full_summary_fits_its_size() {
+        let summary_data = IntroSummaryCache {
+            discriminator: IntroSummaryCache::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: Pubkey::new_unique(),
+            reviewer: Pubkey::new_unique(),
+            name_hash: [7u8; 32],
+            comment_count: u64::MAX,
+            like_count: u64::MAX,
+            boost_score: u64::MAX,
+            last_activity_slot: u64::MAX,
+            snapshot_epoch: u64::MAX,
+            epochs: vec![IntroEpochCounts {
+                epoch: u64::MAX,
+                comment_count: u64::MAX,
+                like_count: u64::MAX,
+                boost_score: u64::MAX,
+            }; IntroSummaryCache::MAX_EPOCHS],
+        };
+        assert_eq!(summary_data.try_to_vec().unwrap().len(), IntroSummaryCache::SIZE);
+    }
+}
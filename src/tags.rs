@@ -0,0 +1,195 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::TAG_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_program_account};
+use crate::state::{StudentIntroState, TagIndex, TagPage};
+
+pub fn tag_hash(tag: &str) -> [u8; 32] {
+    hash(tag.as_bytes()).to_bytes()
+}
+
+// Adds a templated intro to the registry of each of its tags, so browsing a tag is a
+// fetch of its index and then of one page. Anyone can pay for it, once per intro. The
+// accounts after the first three are the index and the current page of each tag, in the
+// intro's tag order. Deleted intros stay listed and are skipped by clients.
+pub fn index_intro_tags(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Indexing intro tags...");
+
+    check_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if !review_data.is_initialized() || review_data.closing {
+        return Err(reason!(ReviewError::UninitializedAccount, "Intro is not open"));
+    }
+    if review_data.tags_indexed {
+        msg!("Intro tags are already indexed");
+        return Ok(());
+    }
+    check_account_count(accounts, 3 + 2 * review_data.tags.len())?;
+
+    let rent = Rent::get()?;
+    for tag in &review_data.tags {
+        let pda_index = next_account_info(account_info_iter)?;
+        let pda_page = next_account_info(account_info_iter)?;
+        append(program_id, ns, payer, pda_index, pda_page, system_program, tag, pda_review.key, &rent)?;
+    }
+
+    review_data.tags_indexed = true;
+    account_type::write(&review_data, pda_review)?;
+
+    Ok(())
+}
+
+// Appends the intro at the tag's cursor, creating the index on the tag's first intro and
+// each page on its first slot
+#[allow(clippy::too_many_arguments)]
+fn append<'a>(
+    program_id: &Pubkey,
+    ns: &[u8],
+    payer: &AccountInfo<'a>,
+    pda_index: &AccountInfo<'a>,
+    pda_page: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    tag: &str,
+    intro: &Pubkey,
+    rent: &Rent,
+) -> ProgramResult {
+    let tag_hash = tag_hash(tag);
+    let (index_pda, index_bump) = Pubkey::find_program_address(&[ns, TAG_SEED, tag_hash.as_ref()], program_id);
+    if index_pda != *pda_index.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for tag {:?} index PDA", tag));
+    }
+
+    let mut index_data = if pda_index.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_index.key,
+                rent.minimum_balance(TagIndex::SIZE + account_type::TAG_LEN),
+                (TagIndex::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[payer.clone(), pda_index.clone(), system_program.clone()],
+            &[&[ns, TAG_SEED, tag_hash.as_ref(), &[index_bump]]],
+        )?;
+        TagIndex {
+            discriminator: TagIndex::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            tag_hash,
+            tag: tag.to_string(),
+            count: 0,
+        }
+    } else {
+        if pda_index.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        account_type::read::<TagIndex>(pda_index).unwrap()
+    };
+
+    let page = index_data.count / TagPage::CAPACITY;
+    let (page_pda, page_bump) = Pubkey::find_program_address(
+        &[ns, TAG_SEED, tag_hash.as_ref(), page.to_be_bytes().as_ref()], program_id);
+    if page_pda != *pda_page.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Tag {:?} appends to page {}", tag, page));
+    }
+
+    let mut page_data = if index_data.count % TagPage::CAPACITY == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_page.key,
+                rent.minimum_balance(TagPage::SIZE + account_type::TAG_LEN),
+                (TagPage::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+                program_id,
+            ),
+            &[payer.clone(), pda_page.clone(), system_program.clone()],
+            &[&[ns, TAG_SEED, tag_hash.as_ref(), page.to_be_bytes().as_ref(), &[page_bump]]],
+        )?;
+        TagPage {
+            discriminator: TagPage::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            tag_hash,
+            page,
+            intros: Vec::new(),
+        }
+    } else {
+        if pda_page.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        account_type::read::<TagPage>(pda_page).unwrap()
+    };
+
+    page_data.intros.push(*intro);
+    account_type::write(&page_data, pda_page)?;
+
+    index_data.count = index_data.count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    account_type::write(&index_data, pda_index)?;
+
+    msg!("Intro {} is number {} under tag {:?}", intro, index_data.count, tag);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use crate::state::IntroTemplate;
+
+    #[test]
+    fn test_full_tag_accounts_fit_their_size() {
+        let tag = "x".repeat(IntroTemplate::MAX_TAG_LEN);
+        let index_data = TagIndex {
+            discriminator: TagIndex::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            tag_hash: tag_hash(&tag),
+            tag,
+            count: u64::MAX,
+        };
+        assert_eq!(index_data.try_to_vec().unwrap().len(), TagIndex::SIZE);
+
+        let page_data = TagPage {
+            discriminator: TagPage::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            tag_hash: index_data.tag_hash,
+            page: 0,
+            intros: vec![Pubkey::new_unique(); TagPage::CAPACITY as usize],
+        };
+        assert_eq!(page_data.try_to_vec().unwrap().len(), TagPage::SIZE);
+    }
+}
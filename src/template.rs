@@ -0,0 +1,149 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::TEMPLATE_SEED;
+use crate::audit;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::processor::{check_account_count, check_admin, load_writable_config};
+use crate::state::{AuditEntry, IntroTemplate};
+
+pub fn publish_template(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    id: u64,
+    prompts: Vec<String>,
+    tags: Vec<String>,
+) -> ProgramResult {
+    msg!("Publishing intro template {}...", id);
+
+    check_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_template = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_audit = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_writable_config(program_id, ns, pda_config)?;
+    check_admin(&config_data, admin)?;
+
+    check_texts("prompts", &prompts, IntroTemplate::MAX_PROMPTS, IntroTemplate::MAX_PROMPT_LEN)?;
+    if prompts.is_empty() {
+        return Err(reason!(ReviewError::InvalidDataLength, "A template needs at least one prompt"));
+    }
+    check_texts("tags", &tags, IntroTemplate::MAX_TAGS, IntroTemplate::MAX_TAG_LEN)?;
+
+    let (template_pda, template_bump) = Pubkey::find_program_address(
+        &[ns, TEMPLATE_SEED, id.to_be_bytes().as_ref()], program_id
+    );
+    if template_pda != *pda_template.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for template PDA"));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_template.key,
+            rent.minimum_balance(IntroTemplate::SIZE + account_type::TAG_LEN),
+            (IntroTemplate::SIZE + account_type::TAG_LEN).try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), pda_template.clone(), system_program.clone()],
+        &[&[ns, TEMPLATE_SEED, id.to_be_bytes().as_ref(), &[template_bump]]],
+    )?;
+
+    let template_data = IntroTemplate {
+        discriminator: IntroTemplate::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        id,
+        author: *admin.key,
+        prompts,
+        tags,
+    };
+    account_type::write(&template_data, pda_template)?;
+
+    audit::record(program_id, ns, admin, pda_config, &mut config_data, pda_audit, system_program,
+        AuditEntry::ACTION_PUBLISH_TEMPLATE)
+}
+
+pub fn load_template(
+    program_id: &Pubkey,
+    ns: &[u8],
+    pda_template: &AccountInfo,
+) -> Result<IntroTemplate, ProgramError> {
+    if pda_template.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let template_data = account_type::read::<IntroTemplate>(pda_template).unwrap();
+    if !template_data.is_initialized() || template_data.discriminator != IntroTemplate::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Template not initialized"));
+    }
+
+    let (template_pda, _template_bump) = Pubkey::find_program_address(
+        &[ns, TEMPLATE_SEED, template_data.id.to_be_bytes().as_ref()], program_id
+    );
+    if template_pda != *pda_template.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for template PDA"));
+    }
+
+    Ok(template_data)
+}
+
+// A cloned intro answers every prompt of its template, in order
+pub fn check_answers(template_data: &IntroTemplate, answers: &[String]) -> ProgramResult {
+    if answers.len() != template_data.prompts.len() {
+        return Err(reason!(ReviewError::InvalidDataLength, "Template {} has {} prompts but {} answers were given",
+            template_data.id, template_data.prompts.len(), answers.len()));
+    }
+
+    check_texts("answers", answers, IntroTemplate::MAX_PROMPTS, IntroTemplate::MAX_ANSWER_LEN)
+}
+
+fn check_texts(what: &str, texts: &[String], max_count: usize, max_len: usize) -> ProgramResult {
+    if texts.len() > max_count {
+        return Err(reason!(ReviewError::InvalidDataLength, "At most {} {} fit", max_count, what));
+    }
+    if texts.iter().any(|text| text.trim().is_empty() || text.len() > max_len) {
+        return Err(reason!(ReviewError::InvalidDataLength, "Each of the {} must be 1 to {} bytes", what, max_len));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_must_match_the_prompts() {
+        let template_data = IntroTemplate {
+            discriminator: IntroTemplate::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            id: 1,
+            author: Pubkey::new_unique(),
+            prompts: vec!["Where are you from?".to_string(), "Why are you here?".to_string()],
+            tags: vec!["rust".to_string()],
+        };
+
+        let answers = vec!["Lisbon".to_string(), "To learn Solana".to_string()];
+        assert!(check_answers(&template_data, &answers).is_ok());
+        assert!(check_answers(&template_data, &answers[..1]).is_err());
+        assert!(check_answers(&template_data, &["Lisbon".to_string(), " ".to_string()]).is_err());
+        assert!(check_answers(&template_data, &["Lisbon".to_string(), "x".repeat(IntroTemplate::MAX_ANSWER_LEN + 1)]).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{rent::Rent, Sysvar},
+};
+use std::convert::TryInto;
+use crate::account_type;
+use crate::constants::TRANSLATION_SEED;
+use crate::error::ReviewError;
+use crate::reason;
+use crate::moderation;
+use crate::processor::{check_account_count, check_program_account, close_account, load_writable_config, resize_account};
+use crate::state::{CommentTranslation, StudentIntroComment, StudentIntroState};
+
+// Attaches a translation of a comment at [comment, "translation", language], one per
+// language and at most MAX_PER_COMMENT per comment. The original is left as written.
+// The commenter or the instructor can add one, and the author pays its rent, which
+// comes back when the comment is deleted.
+pub fn add_translation(
+    program_id: &Pubkey,
+    ns: &[u8],
+    accounts: &[AccountInfo],
+    language: String,
+    text: String,
+) -> ProgramResult {
+    msg!("Adding {:?} translation...", language);
+
+    check_account_count(accounts, 7)?;
+    let account_info_iter = &mut accounts.iter();
+
+    let author = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let pda_translation = next_account_info(account_info_iter)?;
+    let pda_banned = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !author.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_program_account(system_program, &SYSTEM_PROGRAM_ID)?;
+    if pda_review.owner != program_id || pda_comment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config_data = load_writable_config(program_id, ns, pda_config)?;
+
+    let review_data = account_type::read::<StudentIntroState>(pda_review).unwrap();
+    if review_data.closing {
+        return Err(reason!(ReviewError::IntroClosing, "Student intro is being closed"));
+    }
+
+    let mut comment_data = account_type::read::<StudentIntroComment>(pda_comment).unwrap();
+    if !comment_data.is_initialized() || comment_data.discriminator != StudentIntroComment::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Comment account is not initialized"));
+    }
+    if comment_data.review != *pda_review.key {
+        return Err(reason!(ReviewError::CommentIntroMismatch,
+            "Comment {} does not belong to this intro", pda_comment.key));
+    }
+    if comment_data.commenter != *author.key && config_data.admin != *author.key {
+        return Err(reason!(ReviewError::Unauthorized, "Only the commenter or the instructor can translate a comment"));
+    }
+    if comment_data.translations as usize >= CommentTranslation::MAX_PER_COMMENT {
+        return Err(reason!(ReviewError::TooManyTranslations,
+            "Comment already has {} translations", comment_data.translations));
+    }
+
+    check_language(&language)?;
+    if text.trim().is_empty() || text.len() > CommentTranslation::MAX_TEXT_LEN {
+        return Err(reason!(ReviewError::InvalidDataLength,
+            "Translation must be 1 to {} bytes", CommentTranslation::MAX_TEXT_LEN));
+    }
+    moderation::check_comment(program_id, ns, pda_banned, &text)?;
+
+    let (translation_pda, translation_bump) = Pubkey::find_program_address(
+        &[ns, pda_comment.key.as_ref(), TRANSLATION_SEED, language.as_bytes()], program_id);
+    if translation_pda != *pda_translation.key {
+        return Err(reason!(ReviewError::InvalidPDA, "Invalid seeds for translation PDA"));
+    }
+
+    let rent = Rent::get()?;
+    let account_len = CommentTranslation::get_account_size(&language, &text) + account_type::TAG_LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            author.key,
+            pda_translation.key,
+            rent.minimum_balance(account_len),
+            account_len.try_into().unwrap(),
+            program_id,
+        ),
+        &[author.clone(), pda_translation.clone(), system_program.clone()],
+        &[&[ns, pda_comment.key.as_ref(), TRANSLATION_SEED, language.as_bytes(), &[translation_bump]]],
+    )?;
+
+    let translation_data = CommentTranslation {
+        discriminator: CommentTranslation::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        comment: *pda_comment.key,
+        author: *author.key,
+        language,
+        text,
+    };
+    account_type::write(&translation_data, pda_translation)?;
+
+    // Comments from before translations need room for the count. The author covers the
+    // extra rent.
+    comment_data.translations += 1;
+    let needed_len = StudentIntroComment::get_account_size(
+        comment_data.comment.clone(), comment_data.link.clone());
+    if account_type::body_len(pda_comment) < needed_len {
+        resize_account(author, pda_comment, system_program, needed_len, &rent)?;
+    }
+    account_type::write(&comment_data, pda_comment)?;
+
+    msg!("Comment {} now has {} translations", pda_comment.key, comment_data.translations);
+
+    Ok(())
+}
+
+// Closes one of the comment's translations along with the comment, refunding its author
+pub(crate) fn close_translation<'a>(
+    program_id: &Pubkey,
+    comment: &Pubkey,
+    pda_translation: &AccountInfo<'a>,
+    author: &AccountInfo<'a>,
+) -> ProgramResult {
+    if pda_translation.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let translation_data = account_type::read::<CommentTranslation>(pda_translation).unwrap();
+    if !translation_data.is_initialized() || translation_data.discriminator != CommentTranslation::DISCRIMINATOR {
+        return Err(reason!(ReviewError::UninitializedAccount, "Translation {} is not initialized", pda_translation.key));
+    }
+    if translation_data.comment != *comment || translation_data.author != *author.key {
+        return Err(reason!(ReviewError::IncorrectAccountError,
+            "Translation {} must be closed to its author along with comment {}", pda_translation.key, comment));
+    }
+
+    close_account(pda_translation, author)
+}
+
+// Language codes are BCP 47 style tags like "es" or "pt-BR"
+fn check_language(language: &str) -> ProgramResult {
+    if language.len() < 2
+        || language.len() > CommentTranslation::MAX_LANGUAGE_LEN
+        || !language.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        msg!("Language must be a code like \"es\" or \"pt-BR\"");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_language_codes() {
+        assert!(check_language("es").is_ok());
+        assert!(check_language("pt-BR").is_ok());
+        assert!(check_language("e").is_err());
+        assert!(check_language("en/../x").is_err());
+        assert!(check_language("abcdefghijk").is_err());
+    }
+
+    #[test]
+    fn test_translations_close_only_with_their_comment() {
+        let program_id = Pubkey::new_unique();
+        let (comment_key, translation_key, author_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut translation_bytes = CommentTranslation {
+            discriminator: CommentTranslation::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            comment: comment_key,
+            author: author_key,
+            language: "es".to_string(),
+            text: "Hola".to_string(),
+        }.try_to_vec().unwrap();
+        let (mut translation_lamports, mut author_lamports, mut author_bytes) = (5, 1, vec![]);
+        let pda_translation = AccountInfo::new(
+            &translation_key, false, true, &mut translation_lamports, &mut translation_bytes, &program_id, false, 0);
+        let author = AccountInfo::new(
+            &author_key, false, true, &mut author_lamports, &mut author_bytes, &SYSTEM_PROGRAM_ID, false, 0);
+
+        assert!(close_translation(&program_id, &Pubkey::new_unique(), &pda_translation, &author).is_err());
+        assert!(close_translation(&program_id, &comment_key, &pda_translation, &pda_translation).is_err());
+        close_translation(&program_id, &comment_key, &pda_translation, &author).unwrap();
+        assert_eq!((pda_translation.lamports(), author.lamports()), (0, 6));
+    }
+}